@@ -0,0 +1,69 @@
+use std::time::Instant;
+
+use anon::incognito::{IncognitoParams, IncognitoSignature};
+use anon::schnorr::SchnorrSignature;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use elliptic_curve::Field;
+use k256::sha2::Sha256;
+use k256::ProjectivePoint;
+use k256::Scalar;
+use k256::Secp256k1;
+use rand::rngs::ThreadRng;
+use rand::Rng;
+
+const SGNUMBER: usize = 256;
+
+fn setup(params: &IncognitoParams<Secp256k1, 256>, n: usize) -> (Vec<ProjectivePoint>, Vec<Vec<u8>>, Vec<IncognitoSignature<Secp256k1>>) {
+    let mut rng = ThreadRng::default();
+    let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+    let messages: Vec<_> = (0..SGNUMBER).map(|i| format!("the {}-th transaction in the same interval", i).into_bytes()).collect();
+    let incsigs = messages.iter().map(|message| {
+        let index = rng.gen_range(0..n);
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], message);
+        params.convert::<Sha256>(&pks, message, &signature, index).unwrap()
+    }).collect();
+    (pks, messages, incsigs)
+}
+
+fn batch_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Incognito Batch Verification");
+    for n in [64, 256].iter() {
+        let params = IncognitoParams::<Secp256k1, 256>::new();
+        let (pks, messages, incsigs) = setup(&params, *n);
+        let batch: Vec<_> = messages.iter().zip(incsigs.iter()).map(|(message, incsig)| (&pks[..], &message[..], incsig)).collect();
+
+        let start = Instant::now();
+        for (pks, message, incsig) in batch.iter() {
+            params.verify::<Sha256>(pks, message, incsig).unwrap();
+        }
+        let sequential = start.elapsed();
+        let start = Instant::now();
+        params.verify_batch::<Sha256>(&batch).unwrap();
+        let batched = start.elapsed();
+        println!("Ring size {}: sequential {:?}, batch {:?}, speedup {:.2}x", n, sequential, batched, sequential.as_secs_f64() / batched.as_secs_f64());
+        assert!(batched < sequential, "Batch verification is slower than sequential verification");
+
+        group.bench_with_input(BenchmarkId::new("Sequential", n), n, |b, _| {
+            b.iter(|| {
+                for (pks, message, incsig) in batch.iter() {
+                    params.verify::<Sha256>(pks, message, incsig).unwrap();
+                }
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("Batch", n), n, |b, _| {
+            b.iter(|| params.verify_batch::<Sha256>(&batch).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!{
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = batch_verify
+}
+criterion_main!(benches);
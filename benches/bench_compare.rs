@@ -0,0 +1,118 @@
+use anon::incognito::IncognitoParams;
+use anon::schnorr::SchnorrSignature;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use digest::Digest;
+use elliptic_curve::ops::Reduce;
+use elliptic_curve::Field;
+use elliptic_curve::group::GroupEncoding;
+use k256::sha2::Sha256;
+use k256::ProjectivePoint;
+use k256::Scalar;
+use k256::Secp256k1;
+use k256::U256;
+use rand::rngs::ThreadRng;
+use rand::Rng;
+
+const SIZES: [usize; 6] = [32, 64, 128, 256, 512, 1024];
+
+// DualRing as in `bench_dualring.rs`: one challenge per ring member plus `z`.
+fn dualring_sign(message: &[u8], sk: &Scalar, pks: &[ProjectivePoint], index: usize) -> (Vec<Scalar>, Scalar) {
+    let mut rng = ThreadRng::default();
+    let r = Scalar::random(&mut rng);
+    let mut cs: Vec<_> = (0..pks.len()).map(|i| if i == index { Scalar::ZERO } else { Scalar::random(&mut rng) }).collect();
+
+    let point_r = ProjectivePoint::GENERATOR * r + pks.iter().zip(cs.iter()).map(|(pk, c)| pk * c).sum::<ProjectivePoint>();
+    let c_index = dualring_challenge(message, &point_r, pks) - cs.iter().sum::<Scalar>();
+    cs[index] = c_index;
+
+    (cs, r - c_index * sk)
+}
+
+fn dualring_verify(message: &[u8], pks: &[ProjectivePoint], signature: &(Vec<Scalar>, Scalar)) -> anyhow::Result<()> {
+    let (cs, z) = signature;
+    anyhow::ensure!(pks.len() == cs.len());
+    let point_r = ProjectivePoint::GENERATOR * z + pks.iter().zip(cs.iter()).map(|(pk, c)| pk * c).sum::<ProjectivePoint>();
+    anyhow::ensure!(dualring_challenge(message, &point_r, pks) == cs.iter().sum());
+    Ok(())
+}
+
+fn dualring_challenge(message: &[u8], point_r: &ProjectivePoint, pks: &[ProjectivePoint]) -> Scalar {
+    let mut digest = Sha256::default();
+    digest.update(message);
+    digest.update(point_r.to_bytes());
+    for pk in pks {
+        digest.update(pk.to_bytes());
+    };
+    <Scalar as Reduce<U256>>::reduce_bytes(&digest.finalize())
+}
+
+fn report_sizes(params: &IncognitoParams<Secp256k1, 1024>) {
+    let mut rng = ThreadRng::default();
+    let message = [0, 3, 6, 9];
+    println!("{:>6} {:>14} {:>14}", "ring", "dualring (B)", "incognito (B)");
+    for n in SIZES {
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let index = rng.gen_range(0..n);
+
+        let (cs, _) = dualring_sign(&message, &sks[index], &pks, index);
+        let dualring = 32 * (cs.len() + 1);
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let incognito = bincode::serialize(&incsig).unwrap().len();
+        println!("{:>6} {:>14} {:>14}", n, dualring, incognito);
+    }
+}
+
+fn compare_sign(c: &mut Criterion) {
+    let params = IncognitoParams::<Secp256k1, 1024>::new();
+    report_sizes(&params);
+
+    let mut group = c.benchmark_group("Compare Signing");
+    for n in SIZES.iter() {
+        let mut rng = ThreadRng::default();
+        let sks: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let index = rng.gen_range(0..*n);
+        let message = [0, 3, 6, 9];
+
+        group.bench_with_input(BenchmarkId::new("DualRing", n), n, |b, _| {
+            b.iter(|| dualring_sign(&message, &sks[index], &pks, index));
+        });
+        group.bench_with_input(BenchmarkId::new("Incognito", n), n, |b, _| {
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            b.iter(|| params.convert::<Sha256>(&pks, &message, &signature, index).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn compare_verify(c: &mut Criterion) {
+    let params = IncognitoParams::<Secp256k1, 1024>::new();
+
+    let mut group = c.benchmark_group("Compare Verification");
+    for n in SIZES.iter() {
+        let mut rng = ThreadRng::default();
+        let sks: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let index = rng.gen_range(0..*n);
+        let message = [0, 3, 6, 9];
+
+        group.bench_with_input(BenchmarkId::new("DualRing", n), n, |b, _| {
+            let signature = dualring_sign(&message, &sks[index], &pks, index);
+            b.iter(|| dualring_verify(&message, &pks, &signature).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("Incognito", n), n, |b, _| {
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+            b.iter(|| params.verify::<Sha256>(&pks, &message, &incsig).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compare_sign, compare_verify);
+criterion_main!(benches);
@@ -37,14 +37,14 @@ fn verify(message: &[u8], pks: &[ProjectivePoint], signature: &(Vec<Scalar>, Sca
 
     let point_r = ProjectivePoint::GENERATOR * z + pks.iter().zip(cs.iter()).map(|(pk, c)| pk * c).sum::<ProjectivePoint>();
 
-    anyhow::ensure!(challenge(message, &point_r, &pks) == cs.iter().sum());
+    anyhow::ensure!(challenge(message, &point_r, pks) == cs.iter().sum());
 
     Ok(())
 }
 
 fn challenge(message: &[u8], point_r: &ProjectivePoint, pks: &[ProjectivePoint]) -> Scalar {
     let mut digest = Sha256::default();
-    digest.update(&message);
+    digest.update(message);
     digest.update(point_r.to_bytes());
     for pk in pks {
         digest.update(pk.to_bytes());
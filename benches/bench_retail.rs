@@ -9,17 +9,17 @@ use rand::rngs::OsRng;
 use tokio::{net::{TcpListener, TcpStream}, runtime::Runtime};
 use tokio_util::{bytes::Bytes, codec::{Framed, LengthDelimitedCodec}};
 
-const BANKADDR: &'static str = "127.0.0.1:0";
+const BANKADDR: &str = "127.0.0.1:0";
 
 async fn user(addr: &SocketAddr) -> Duration {
     let start = Instant::now();
     let socket = TcpStream::connect(addr).await.unwrap();
 
-    let mut rng = OsRng::default();
+    let mut rng = OsRng;
     let sk = Scalar::random(&mut rng);
     let pk = ProjectivePoint::GENERATOR * sk;
     let msg = "Send 10 dollars to 0x00000".to_string();
-    let sig = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &msg.as_bytes());
+    let sig = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, msg.as_bytes());
 
     let mut framed = Framed::new(socket, LengthDelimitedCodec::new());
 
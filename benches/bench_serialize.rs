@@ -0,0 +1,42 @@
+use anon::incognito::{IncognitoParams, IncognitoSignature};
+use anon::schnorr::SchnorrSignature;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use elliptic_curve::Field;
+use k256::sha2::Sha256;
+use k256::ProjectivePoint;
+use k256::Scalar;
+use k256::Secp256k1;
+use rand::rngs::ThreadRng;
+
+/// Handing a signature to another task: a bincode round trip, which normalizes every
+/// point to affine form and back, against a plain clone, which keeps them projective.
+fn serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Incognito Serialization");
+    for n in [64, 256].iter() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 256>::new();
+        let sks: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = b"the 0-th transaction in the same interval";
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[0], message);
+        let incsig = params.convert::<Sha256>(&pks, message, &signature, 0).unwrap();
+        let bytes = bincode::serialize(&incsig).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("Serialize", n), n, |b, _| {
+            b.iter(|| bincode::serialize(&incsig).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("Deserialize", n), n, |b, _| {
+            b.iter(|| bincode::deserialize::<IncognitoSignature<Secp256k1>>(&bytes).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("Clone", n), n, |b, _| {
+            b.iter(|| incsig.clone());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, serialize);
+criterion_main!(benches);
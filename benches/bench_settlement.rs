@@ -1,39 +1,40 @@
-use std::{net::SocketAddr, time::{Duration, Instant}};
+use std::{net::SocketAddr, sync::Arc, time::{Duration, Instant}};
 
-use anon::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+use anon::{incognito::{IncognitoParams, IncognitoSignature}, net::{key_table_codec, settlement_codec}, schnorr::SchnorrSignature};
 use criterion::{criterion_group, criterion_main, Criterion};
 use elliptic_curve::Field;
 use futures::{SinkExt, StreamExt};
 use k256::{sha2::Sha256, AffinePoint, ProjectivePoint, Scalar, Secp256k1};
-use rand::{rngs::OsRng, Rng};
+use rand::rngs::OsRng;
 use tokio::{net::{TcpListener, TcpStream}, runtime::Runtime, task::JoinSet};
-use tokio_util::{bytes::Bytes, codec::{Framed, LengthDelimitedCodec}};
+use tokio_util::{bytes::Bytes, codec::Framed};
 
-const CENTADDR: &'static str = "127.0.0.1:0";
+const CENTADDR: &str = "127.0.0.1:0";
 const BTCHSIZE: usize = 256;
 const RINGSIZE: usize = 128;
 const SGNUMBER: usize = 2560;
+const MSGLIMIT: usize = 64;
 
 async fn commbank(addr: SocketAddr, params: &IncognitoParams::<Secp256k1, RINGSIZE>) -> Duration {
     const RINGINDX: usize = 16;
 
     let start = Instant::now();
 
-    let mut rng = OsRng::default();
+    let mut rng = OsRng;
     let sks: Vec<_> = (0..BTCHSIZE).map(|_| Scalar::random(&mut rng)).collect();
-    let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+    let pks: Arc<Vec<_>> = Arc::new(sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect());
     
     let mut set = JoinSet::new();
 
     (0..SGNUMBER).for_each(|i| {
         let params = params.clone();
-        let ids = (0..RINGSIZE).map(|_| rng.gen_range(0..BTCHSIZE)).collect::<Vec<usize>>();
-        let pks = ids.iter().map(|i| pks[*i].clone()).collect::<Vec<_>>();
-        let ski = sks[ids[RINGINDX]].clone();
+        let pks = pks.clone();
+        let ids = rand::seq::index::sample(&mut rng, BTCHSIZE, RINGSIZE).into_vec();
+        let ski = sks[ids[RINGINDX]];
         set.spawn(async move {
             let msg = format!("the {}-th transaction in the same interval", i);
-            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&ski, &msg.as_bytes());
-            let incsig = params.convert::<Sha256>(&pks, &msg.as_bytes(), &signature, RINGINDX).unwrap();
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&ski, msg.as_bytes());
+            let incsig = params.convert_indexed::<Sha256>(&pks, &ids, msg.as_bytes(), &signature, RINGINDX).unwrap();
             Bytes::from(bincode::serialize(&(ids, msg, incsig)).unwrap())
         });
     });
@@ -44,12 +45,13 @@ async fn commbank(addr: SocketAddr, params: &IncognitoParams::<Secp256k1, RINGSI
     }
 
     let socket = TcpStream::connect(addr).await.unwrap();
-    let mut framed = Framed::new(socket, LengthDelimitedCodec::new());
+    let mut framed = Framed::new(socket, key_table_codec::<Secp256k1>(BTCHSIZE));
     framed.send(Bytes::from(
         bincode::serialize(
-            &pks.into_iter().map(|each| each.to_affine()).collect::<Vec<_>>()
+            &pks.iter().map(|each| each.to_affine()).collect::<Vec<_>>()
         ).unwrap()
     )).await.unwrap();
+    let mut framed = framed.map_codec(|_| settlement_codec::<Secp256k1, RINGSIZE>(MSGLIMIT));
     for data in data_pending.into_iter() {
         framed.send(data).await.unwrap();
     };
@@ -61,17 +63,19 @@ async fn centbank(listener: &TcpListener, params: &IncognitoParams::<Secp256k1,
     let (socket, _) = listener.accept().await.unwrap();
     let start = Instant::now();
 
-    let mut framed = Framed::new(socket, LengthDelimitedCodec::new());
+    let mut framed = Framed::new(socket, key_table_codec::<Secp256k1>(BTCHSIZE));
 
     let mut set = JoinSet::new();
 
     let pks: Vec<AffinePoint> = bincode::deserialize(&framed.next().await.unwrap().unwrap()).unwrap();
+    let pks: Arc<Vec<_>> = Arc::new(pks.into_iter().map(ProjectivePoint::from).collect());
+    let mut framed = framed.map_codec(|_| settlement_codec::<Secp256k1, RINGSIZE>(MSGLIMIT));
     for _ in 0..SGNUMBER {
         let params = params.clone();
+        let pks = pks.clone();
         let (ids, msg, incsig): (Vec<usize>, String, IncognitoSignature<Secp256k1>) = bincode::deserialize(&framed.next().await.unwrap().unwrap()).unwrap();
-        let pks = ids.into_iter().map(|i| ProjectivePoint::from(pks[i])).collect::<Vec<_>>();
         set.spawn(async move {
-            params.verify::<Sha256>(&pks, &msg.as_bytes(), &incsig)
+            params.verify_indexed::<Sha256>(&pks, &ids, msg.as_bytes(), &incsig)
         });
     };
 
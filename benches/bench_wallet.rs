@@ -9,14 +9,14 @@ use rand::{rngs::OsRng, RngCore};
 use tokio::{net::{TcpListener, TcpStream}, runtime::Runtime};
 use tokio_util::{bytes::{Buf, BufMut, Bytes, BytesMut}, codec::{Framed, LengthDelimitedCodec}};
 
-const BANKADDR: &'static str = "127.0.0.1:0";
+const BANKADDR: &str = "127.0.0.1:0";
 const TXSGSIZE: usize = 20; // The signature number that each transaction would contain
 
 async fn user(addr: &SocketAddr) -> Duration {
     let start = Instant::now();
     let socket = TcpStream::connect(addr).await.unwrap();
 
-    let mut rng = OsRng::default();
+    let mut rng = OsRng;
     let sk = Scalar::random(&mut rng);
     let pk = ProjectivePoint::GENERATOR * sk;
 
@@ -24,9 +24,9 @@ async fn user(addr: &SocketAddr) -> Duration {
 
     let uid = framed.next().await.unwrap().unwrap().get_u32();
     framed.send(Bytes::from(bincode::serialize(&pk.to_affine()).unwrap())).await.unwrap();
-    for i in 0..TXSGSIZE {
-        let msg = format!("User {} with money {}", uid, 2u32.shl(i));
-        let sig = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &msg.as_bytes());
+    let msgs: Vec<_> = (0..TXSGSIZE).map(|i| format!("User {} with money {}", uid, 2u32.shl(i))).collect();
+    let msgs: Vec<_> = msgs.iter().map(|msg| msg.as_bytes()).collect();
+    for sig in SchnorrSignature::<Secp256k1>::sign_batch::<Sha256, _>(&sk, &msgs, &mut rng) {
         framed.send(Bytes::from(bincode::serialize(&sig).unwrap())).await.unwrap();
     }
 
@@ -37,7 +37,7 @@ async fn bank(listener: &TcpListener) -> Duration{
     let (socket, _) = listener.accept().await.unwrap();
     let start = Instant::now();
 
-    let mut rng = OsRng::default();
+    let mut rng = OsRng;
     let uid = rng.next_u32();
     let mut uid_bytes = BytesMut::new();
     uid_bytes.put_u32(uid);
@@ -51,7 +51,7 @@ async fn bank(listener: &TcpListener) -> Duration{
     for i in 0..TXSGSIZE {
         let msg = format!("User {} with money {}", uid, 2u32.shl(i));
         let sig: SchnorrSignature<Secp256k1> = bincode::deserialize(&framed.next().await.unwrap().unwrap()).unwrap();
-        sig.verify::<Sha256>(&pk, &msg.as_bytes()).unwrap();
+        sig.verify::<Sha256>(&pk, msg.as_bytes()).unwrap();
     }
     
     start.elapsed()
@@ -0,0 +1,72 @@
+//! A commercial bank converts a batch of customer signatures over one ring and
+//! hands them to the central bank, which verifies every one of them.
+//!
+//! cargo run --release --example cbdc_settlement -- --ring-size 64 --batch 128
+
+use std::{process::ExitCode, time::Instant};
+
+use anon::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+use clap::Parser;
+use elliptic_curve::Field;
+use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+use rand::{rngs::OsRng, Rng};
+
+const MAXN: usize = 1024;
+
+#[derive(Parser)]
+#[command(about = "End-to-end CBDC settlement with incognito Schnorr signatures")]
+struct Args {
+    /// Number of public keys in the ring, a power of two up to 1024.
+    #[arg(long, default_value_t = 64)]
+    ring_size: usize,
+    /// Number of transactions settled in the batch.
+    #[arg(long, default_value_t = 128)]
+    batch: usize,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if !args.ring_size.is_power_of_two() || args.ring_size > MAXN {
+        eprintln!("--ring-size must be a power of two no larger than {}", MAXN);
+        return ExitCode::FAILURE;
+    }
+
+    let mut rng = OsRng;
+    let params = IncognitoParams::<Secp256k1, MAXN>::new();
+    let sks: Vec<_> = (0..args.ring_size).map(|_| Scalar::random(&mut rng)).collect();
+    let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+    // Commercial bank: customers sign, the bank converts and serializes.
+    let start = Instant::now();
+    let frames: Vec<Vec<u8>> = (0..args.batch).map(|i| {
+        let index = rng.gen_range(0..args.ring_size);
+        let message = format!("the {}-th transaction in the same interval", i).into_bytes();
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).expect("conversion");
+        bincode::serialize(&(message, incsig)).expect("serialization")
+    }).collect();
+    let converted = start.elapsed();
+    let total_bytes: usize = frames.iter().map(Vec::len).sum();
+
+    // Central bank: deserialize and verify every frame.
+    let start = Instant::now();
+    let failures = frames.iter().filter(|frame| {
+        let verified = bincode::deserialize::<(Vec<u8>, IncognitoSignature<Secp256k1>)>(frame)
+            .map_err(anyhow::Error::from)
+            .and_then(|(message, incsig)| params.verify::<Sha256>(&pks, &message, &incsig));
+        verified.is_err()
+    }).count();
+    let verified = start.elapsed();
+
+    println!("ring size:   {}", args.ring_size);
+    println!("batch:       {}", args.batch);
+    println!("total bytes: {} ({} per signature)", total_bytes, total_bytes / args.batch.max(1));
+    println!("conversion:  {:?} ({:.1} sig/s)", converted, args.batch as f64 / converted.as_secs_f64());
+    println!("settlement:  {:?} ({:.1} sig/s)", verified, args.batch as f64 / verified.as_secs_f64());
+
+    if failures > 0 {
+        eprintln!("{} of {} signatures failed verification", failures, args.batch);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,45 @@
+//! Round-trips an incognito signature through JSON. In human-readable formats the
+//! curve crates encode points and scalars as hex strings, so the JSON needs no
+//! extra helpers.
+
+use anon::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+use elliptic_curve::Field;
+use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+use rand::rngs::ThreadRng;
+use serde_json::Value;
+
+/// Every string in `value`, which for a signature are the encoded points and scalars.
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(values) => values.iter().flat_map(strings).collect(),
+        Value::Object(fields) => fields.values().flat_map(strings).collect(),
+        _ => Vec::new()
+    }
+}
+
+fn main() {
+    let n = 8;
+    let index = 3;
+    let mut rng = ThreadRng::default();
+    let params = IncognitoParams::<Secp256k1, 8>::new();
+    let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+    let message = [0, 3, 6, 9];
+    let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+    let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+
+    let json = serde_json::to_string_pretty(&incsig).unwrap();
+    println!("{}", json);
+
+    let value: Value = serde_json::from_str(&json).unwrap();
+    let hex_strings: Vec<_> = strings(&value).into_iter().filter(|s| *s != "V1" && *s != "V2").collect();
+    assert!(!hex_strings.is_empty());
+    assert!(hex_strings.iter().all(|s| s.len() % 2 == 0 && s.bytes().all(|b| b.is_ascii_hexdigit())));
+
+    let decoded: IncognitoSignature<Secp256k1> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, incsig);
+    params.verify::<Sha256>(&pks, &message, &decoded).unwrap();
+    println!("JSON: {} bytes, bincode: {} bytes", json.len(), bincode::serialize(&incsig).unwrap().len());
+}
@@ -0,0 +1,32 @@
+//! Signs, converts and verifies over NIST P-256 instead of secp256k1. Nothing but the
+//! curve type changes: every API is generic over `CurveArithmetic`.
+
+use std::time::Instant;
+
+use anon::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+use elliptic_curve::Field;
+use k256::sha2::Sha256;
+use p256::{NistP256, ProjectivePoint, Scalar};
+use rand::rngs::ThreadRng;
+
+fn main() {
+    let n = 32;
+    let index = 17;
+    let mut rng = ThreadRng::default();
+    let params = IncognitoParams::<NistP256, 32>::new();
+    let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+    let message = b"P-256 incognito demo";
+    let signature = SchnorrSignature::<NistP256>::sign::<Sha256>(&sks[index], message);
+    signature.verify::<Sha256>(&pks[index], message).unwrap();
+
+    let start = Instant::now();
+    let incsig = params.convert::<Sha256>(&pks, message, &signature, index).unwrap();
+    let converted = start.elapsed();
+    params.verify::<Sha256>(&pks, message, &incsig).unwrap();
+    let verified = start.elapsed() - converted;
+    assert!(params.verify::<Sha256>(&pks, b"another message", &incsig).is_err());
+
+    println!("Ring of {} P-256 keys: convert {:?}, verify {:?}", n, converted, verified);
+}
@@ -6,7 +6,7 @@ use rand::rngs::ThreadRng;
 use tokio::net::{TcpListener, TcpStream};
 use tokio_util::{bytes::Bytes, codec::{Framed, FramedRead, LengthDelimitedCodec}};
 
-const BANKADDR: &'static str = "192.168.70.1";
+const BANKADDR: &str = "192.168.70.1";
 
 #[tokio::main]
 async fn main() {
@@ -36,7 +36,7 @@ async fn main() {
         let sk = Scalar::random(&mut rng);
         let pk = ProjectivePoint::GENERATOR * sk;
         let msg = "TEST MESSAGE".to_string();
-        let sig = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &msg.as_bytes());
+        let sig = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, msg.as_bytes());
 
         let mut framed = Framed::new(TcpStream::connect(BANKADDR).await.unwrap(), LengthDelimitedCodec::new());
 
@@ -0,0 +1,13 @@
+#![no_main]
+
+use anon::{bulletproof::BulletProof, incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+use k256::Secp256k1;
+use libfuzzer_sys::fuzz_target;
+
+// Deserializing untrusted bytes must fail cleanly, never panic.
+fuzz_target!(|data: &[u8]| {
+    let _ = bincode::deserialize::<SchnorrSignature<Secp256k1>>(data);
+    let _ = bincode::deserialize::<BulletProof<Secp256k1>>(data);
+    let _ = bincode::deserialize::<IncognitoSignature<Secp256k1>>(data);
+    let _ = bincode::deserialize::<IncognitoParams<Secp256k1, 8>>(data);
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use std::sync::OnceLock;
+
+use anon::incognito::{IncognitoParams, IncognitoSignature};
+use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+use libfuzzer_sys::fuzz_target;
+
+fn setup() -> &'static (IncognitoParams<Secp256k1, 8>, Vec<ProjectivePoint>) {
+    static SETUP: OnceLock<(IncognitoParams<Secp256k1, 8>, Vec<ProjectivePoint>)> = OnceLock::new();
+    SETUP.get_or_init(|| {
+        let pks = (1..=8u64).map(|i| ProjectivePoint::GENERATOR * Scalar::from(i)).collect();
+        (IncognitoParams::new(), pks)
+    })
+}
+
+// Verifying a well-formed but forged signature must return an error, never panic.
+fuzz_target!(|input: (u8, IncognitoSignature<Secp256k1>)| {
+    let (params, pks) = setup();
+    let (ring_size, signature) = input;
+    let ring = &pks[..(ring_size as usize % 9)];
+    assert!(params.verify::<Sha256>(ring, b"fuzz", &signature).is_err());
+});
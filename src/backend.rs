@@ -0,0 +1,128 @@
+use elliptic_curve::{CurveArithmetic, ProjectivePoint, Scalar};
+#[cfg(feature = "lincomb")]
+use elliptic_curve::ops::LinearCombinationExt;
+
+/// Group arithmetic used by the verifiers. `GenericBackend` works for every curve;
+/// optimized implementations are enabled through features.
+///
+/// `IncognitoParams::verify_with_backend` performs all of its group operations through
+/// these methods, so a backend wrapping them sees every one of them.
+pub trait CurveBackend<C: CurveArithmetic> {
+    /// Computes `sum(points[i] * scalars[i])`.
+    fn msm(points: &[ProjectivePoint<C>], scalars: &[Scalar<C>]) -> ProjectivePoint<C>;
+
+    /// Computes `point * scalar`, for multiplications outside of an `msm`.
+    fn mul(point: &ProjectivePoint<C>, scalar: &Scalar<C>) -> ProjectivePoint<C> {
+        *point * scalar
+    }
+
+    /// Computes `a + b`, for additions outside of an `msm`.
+    fn add(a: &ProjectivePoint<C>, b: &ProjectivePoint<C>) -> ProjectivePoint<C> {
+        *a + b
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenericBackend;
+
+impl <C: CurveArithmetic> CurveBackend<C> for GenericBackend {
+    fn msm(points: &[ProjectivePoint<C>], scalars: &[Scalar<C>]) -> ProjectivePoint<C> {
+        debug_assert_eq!(points.len(), scalars.len());
+        points.iter().zip(scalars.iter()).map(|(point, scalar)| *point * scalar).sum()
+    }
+}
+
+/// Dispatches to the curve's own `LinearCombinationExt`, e.g. the interleaved
+/// wNAF multiplication provided by `k256`.
+#[cfg(feature = "lincomb")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinCombBackend;
+
+#[cfg(feature = "lincomb")]
+impl <C: CurveArithmetic> CurveBackend<C> for LinCombBackend
+where
+    ProjectivePoint<C>: LinearCombinationExt<[(ProjectivePoint<C>, Scalar<C>)]>
+{
+    fn msm(points: &[ProjectivePoint<C>], scalars: &[Scalar<C>]) -> ProjectivePoint<C> {
+        debug_assert_eq!(points.len(), scalars.len());
+        let pairs: Vec<_> = points.iter().copied().zip(scalars.iter().copied()).collect();
+        ProjectivePoint::<C>::lincomb_ext(&pairs)
+    }
+}
+
+/// Device multi-scalar multiplication plugged into `GpuBackend`, typically a thin
+/// wrapper around a CUDA or OpenCL kernel crate. This is an extension hook: the crate
+/// ships no implementation besides `NoDevice`.
+#[cfg(feature = "gpu")]
+pub trait GpuKernel<C: CurveArithmetic> {
+    /// Returns `None` when no device is available or the input cannot be offloaded,
+    /// in which case `GpuBackend` computes the result on the CPU.
+    fn msm(points: &[ProjectivePoint<C>], scalars: &[Scalar<C>]) -> Option<ProjectivePoint<C>>;
+}
+
+/// Kernel that never finds a device, so `GpuBackend<NoDevice>` always falls back.
+#[cfg(feature = "gpu")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDevice;
+
+#[cfg(feature = "gpu")]
+impl <C: CurveArithmetic> GpuKernel<C> for NoDevice {
+    fn msm(_points: &[ProjectivePoint<C>], _scalars: &[Scalar<C>]) -> Option<ProjectivePoint<C>> {
+        None
+    }
+}
+
+/// Routes `msm` to the kernel `K`, falling back to `GenericBackend` when it declines.
+/// Only used when passed explicitly, e.g. to `IncognitoParams::verify_with_backend`;
+/// with the default `NoDevice` it is `GenericBackend` with an extra branch.
+#[cfg(feature = "gpu")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpuBackend<K = NoDevice>(std::marker::PhantomData<K>);
+
+#[cfg(feature = "gpu")]
+impl <C: CurveArithmetic, K: GpuKernel<C>> CurveBackend<C> for GpuBackend<K> {
+    fn msm(points: &[ProjectivePoint<C>], scalars: &[Scalar<C>]) -> ProjectivePoint<C> {
+        debug_assert_eq!(points.len(), scalars.len());
+        K::msm(points, scalars).unwrap_or_else(|| <GenericBackend as CurveBackend<C>>::msm(points, scalars))
+    }
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod gpu_tests {
+    use elliptic_curve::Field;
+    use k256::{ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use super::{CurveBackend, GenericBackend, GpuBackend};
+
+    #[test]
+    fn test_cpu_fallback() {
+        let mut rng = ThreadRng::default();
+        let points: Vec<_> = (0..17).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let scalars: Vec<_> = (0..17).map(|_| Scalar::random(&mut rng)).collect();
+        assert_eq!(
+            <GpuBackend as CurveBackend<Secp256k1>>::msm(&points, &scalars),
+            <GenericBackend as CurveBackend<Secp256k1>>::msm(&points, &scalars)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "lincomb"))]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use super::{CurveBackend, GenericBackend, LinCombBackend};
+
+    #[test]
+    fn test_msm() {
+        let mut rng = ThreadRng::default();
+        let points: Vec<_> = (0..17).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let scalars: Vec<_> = (0..17).map(|_| Scalar::random(&mut rng)).collect();
+        assert_eq!(
+            <GenericBackend as CurveBackend<Secp256k1>>::msm(&points, &scalars),
+            <LinCombBackend as CurveBackend<Secp256k1>>::msm(&points, &scalars)
+        );
+    }
+}
@@ -1,33 +1,96 @@
-use digest::{Digest, FixedOutput};
-use elliptic_curve::{group::{Curve, GroupEncoding}, ops::Reduce, AffinePoint, CurveArithmetic, Field, FieldBytes, FieldBytesSize, ProjectivePoint, Scalar};
+use digest::{Digest, Output};
+use elliptic_curve::{group::GroupEncoding, subtle::{Choice, ConstantTimeEq}, CurveArithmetic, Field, Group, PrimeField, ProjectivePoint, Scalar};
+use rand::rngs::ThreadRng;
+
+use crate::{backend::{CurveBackend, GenericBackend}, challenge::{invert_challenge, reduce_digest}};
+#[cfg(feature = "serde")]
+use elliptic_curve::AffinePoint;
+#[cfg(feature = "serde")]
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned", from = "BulletProofSerde<C>", into = "BulletProofSerde<C>")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
 pub struct  BulletProof <C: CurveArithmetic> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     pub target: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::vec_point_serde"))]
     pub(crate) vec_point_l: Vec<ProjectivePoint<C>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::vec_point_serde"))]
     pub(crate) vec_point_r: Vec<ProjectivePoint<C>>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     pub(crate) l: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     pub(crate) r: Scalar<C>
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned")]
-pub(crate) struct BulletProofSerde <C: CurveArithmetic> {
-    target: AffinePoint<C>,
-    vec_point_l: Vec<AffinePoint<C>>,
-    vec_point_r: Vec<AffinePoint<C>>,
-    l: Scalar<C>,
-    r: Scalar<C>
+/// Proof from `BulletProof::prove_blinded`: `target` and every `L`/`R` of `proof` carry
+/// fresh multiples of a blinding base `point_u`, and `blind` is their aggregate. Kept
+/// apart from `BulletProof` so that unblinded proofs, and the signatures embedding
+/// them, do not carry it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct BlindedBulletProof<C: CurveArithmetic> {
+    pub proof: BulletProof<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    pub(crate) blind: Scalar<C>
+}
+
+/// One `(l, r)` opening passed to `BulletProof::prove_aggregated`.
+pub type Witness<'a, C> = (&'a [Scalar<C>], &'a [Scalar<C>]);
+
+/// Largest number of folding rounds accepted by the verifier, i.e. at most
+/// `2^MAX_ROUNDS` generators.
+pub const MAX_ROUNDS: usize = 24;
+
+/// Number of folding rounds of a proof over `n` generators, or `None` if `n` is not
+/// a power of two, which the folding does not support.
+pub fn required_rounds(n: usize) -> Option<u32> {
+    n.is_power_of_two().then(|| n.trailing_zeros())
+}
+
+/// Fiat–Shamir transcript used for the per-round challenges.
+///
+/// `V1` hashes only `target`, `L` and `R`. `V2` additionally binds a digest of the
+/// initial generators, the round index and the previous round's challenge, which
+/// together fix the folded generator state of every round.
+///
+/// The version is not part of the proof, so the verifier picks it: every verifier
+/// expects `V2`, and `V1` proofs are only accepted through `verify_versioned`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum TranscriptVersion {
+    V1,
+    #[default]
+    V2
+}
+
+/// How each round pairs up the entries that get folded together.
+///
+/// `Halves` combines index `i` with `i + n/2`; `Interleaved` combines `2i` with
+/// `2i + 1`. The strategy is not part of the proof, so prover and verifier must
+/// use the same one.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum FoldStrategy {
+    #[default]
+    Halves,
+    Interleaved
+}
+
+impl FoldStrategy {
+    /// Indices folded into entry `i` when `2 * half` entries remain.
+    #[inline]
+    fn pair(self, half: usize, i: usize) -> (usize, usize) {
+        match self {
+            FoldStrategy::Halves => (i, i + half),
+            FoldStrategy::Interleaved => (2 * i, 2 * i + 1)
+        }
+    }
 }
 
 impl <C: CurveArithmetic> BulletProof<C> 
 where
-    ProjectivePoint<C>: GroupEncoding,
-    Scalar<C>: Serialize + DeserializeOwned,
-    AffinePoint<C>: Serialize + DeserializeOwned
+    ProjectivePoint<C>: GroupEncoding
 {
     #[inline]
     fn challenge<D>(
@@ -36,138 +99,497 @@ where
         point_r: &ProjectivePoint<C>
     ) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
         let digest = D::new()
             .chain_update(target.to_bytes())
             .chain_update(point_l.to_bytes())
             .chain_update(point_r.to_bytes())
-            .finalize_fixed();
-        let fieldbytes = digest as FieldBytes<C>;
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&fieldbytes)
+            .finalize();
+        reduce_digest::<C>(&digest)
+    }
+
+    #[inline]
+    fn generators_digest<D>(vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> Output<D>
+    where
+        D: Digest
+    {
+        Self::points_digest::<D>(vec_g.iter().chain(vec_h.iter()).copied())
+    }
+
+    #[inline]
+    fn points_digest<D>(points: impl Iterator<Item = ProjectivePoint<C>>) -> Output<D>
+    where
+        D: Digest
+    {
+        let mut hasher = D::new();
+        for point in points {
+            Digest::update(&mut hasher, point.to_bytes());
+        }
+        hasher.finalize()
+    }
+
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn round_challenge<D>(
+        version: TranscriptVersion,
+        generators: &Output<D>,
+        round: usize,
+        prev: &Scalar<C>,
+        target: &ProjectivePoint<C>,
+        point_l: &ProjectivePoint<C>,
+        point_r: &ProjectivePoint<C>
+    ) -> Scalar<C>
+    where
+        D: Digest
+    {
+        match version {
+            TranscriptVersion::V1 => Self::challenge::<D>(target, point_l, point_r),
+            TranscriptVersion::V2 => {
+                let digest = D::new()
+                    .chain_update(b"bulletproof-v2")
+                    .chain_update(generators)
+                    .chain_update((round as u64).to_le_bytes())
+                    .chain_update(prev.to_repr())
+                    .chain_update(target.to_bytes())
+                    .chain_update(point_l.to_bytes())
+                    .chain_update(point_r.to_bytes())
+                    .finalize();
+                reduce_digest::<C>(&digest)
+            }
+        }
     }
 
     pub fn prove<D>(vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], vec_l: &[Scalar<C>], vec_r: &[Scalar<C>], target: &ProjectivePoint<C>) -> BulletProof<C> 
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
+        Self::prove_with_blinding::<D>(TranscriptVersion::default(), FoldStrategy::default(), vec_g, vec_h, vec_l, vec_r, target, None, None).0
+    }
+
+    /// Same as `prove`, with an explicit transcript version.
+    pub fn prove_versioned<D>(version: TranscriptVersion, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], vec_l: &[Scalar<C>], vec_r: &[Scalar<C>], target: &ProjectivePoint<C>) -> BulletProof<C> 
+    where
+        D: Digest
+    {
+        Self::prove_with_blinding::<D>(version, FoldStrategy::default(), vec_g, vec_h, vec_l, vec_r, target, None, None).0
+    }
+
+    /// Same as `prove`, folding with `strategy`. Verify with `verify_with_strategy`.
+    pub fn prove_with_strategy<D>(strategy: FoldStrategy, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], vec_l: &[Scalar<C>], vec_r: &[Scalar<C>], target: &ProjectivePoint<C>) -> BulletProof<C> 
+    where
+        D: Digest
+    {
+        Self::prove_with_blinding::<D>(TranscriptVersion::default(), strategy, vec_g, vec_h, vec_l, vec_r, target, None, None).0
+    }
+
+    /// Same statement as `prove`, but `target` and every `L`/`R` are blinded by
+    /// fresh multiples of `point_u`, so the transmitted target is randomized.
+    /// Only the aggregated blinding scalar is revealed next to the proof.
+    pub fn prove_blinded<D>(vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], vec_l: &[Scalar<C>], vec_r: &[Scalar<C>], target: &ProjectivePoint<C>, point_u: &ProjectivePoint<C>) -> BlindedBulletProof<C> 
+    where
+        D: Digest
+    {
+        let (proof, blind) = Self::prove_with_blinding::<D>(TranscriptVersion::default(), FoldStrategy::default(), vec_g, vec_h, vec_l, vec_r, target, Some(point_u), None);
+        BlindedBulletProof { proof, blind: blind.expect("blinding base given") }
+    }
+
+    /// Proves that `target` is `<l, vec_g> + <r, vec_h> + <l, r> * point_q`, so that the
+    /// committed inner product is bound to the final `l * r` check.
+    pub fn prove_inner_product<D>(vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], vec_l: &[Scalar<C>], vec_r: &[Scalar<C>], target: &ProjectivePoint<C>, point_q: &ProjectivePoint<C>) -> BulletProof<C> 
+    where
+        D: Digest
+    {
+        Self::prove_with_blinding::<D>(TranscriptVersion::default(), FoldStrategy::default(), vec_g, vec_h, vec_l, vec_r, target, None, Some(point_q)).0
+    }
+
+    /// Also returns the aggregated blinding scalar when `point_u` is given.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_with_blinding<D>(version: TranscriptVersion, strategy: FoldStrategy, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], vec_l: &[Scalar<C>], vec_r: &[Scalar<C>], target: &ProjectivePoint<C>, point_u: Option<&ProjectivePoint<C>>, point_q: Option<&ProjectivePoint<C>>) -> (BulletProof<C>, Option<Scalar<C>>) 
+    where
+        D: Digest
+    {
+        let mut rng = ThreadRng::default();
         let mut n = vec_g.len();
+        let generators = match version {
+            TranscriptVersion::V1 => Output::<D>::default(),
+            TranscriptVersion::V2 => Self::generators_digest::<D>(vec_g, vec_h)
+        };
+        let mut x = Scalar::<C>::ZERO;
 
         let mut vec_point_l = Vec::new();
         let mut vec_point_r = Vec::new();
 
+        let mut blind = point_u.map(|_| Scalar::<C>::random(&mut rng));
+        let target = match (point_u, blind) {
+            (Some(point_u), Some(rho)) => *target + *point_u * rho,
+            _ => target.to_owned()
+        };
+
         let mut vec_g = vec_g.to_owned();
         let mut vec_h = vec_h.to_owned();
         let mut vec_l = vec_l.to_owned();
         let mut vec_r = vec_r.to_owned();
-        let mut point_p = target.to_owned();
+        let mut point_p = target;
 
         while n > 1 {
-            n = n / 2;
-
-            let (vec_g0, vec_g1) = vec_g.split_at(n);
-            let (vec_h0, vec_h1) = vec_h.split_at(n);
-            let (vec_l0, vec_l1) = vec_l.split_at(n);
-            let (vec_r0, vec_r1) = vec_r.split_at(n);
-            let point_l: ProjectivePoint<C> = (0..n).map(|i| {
-                vec_g1[i] * vec_l0[i] + vec_h0[i] * vec_r1[i]
+            n /= 2;
+
+            let pairs: Vec<_> = (0..n).map(|i| strategy.pair(n, i)).collect();
+            let mut point_l: ProjectivePoint<C> = pairs.iter().map(|&(i0, i1)| {
+                vec_g[i1] * vec_l[i0] + vec_h[i0] * vec_r[i1]
             }).sum();
-            let point_r: ProjectivePoint<C> = (0..n).map(|i| {
-                vec_g0[i] * vec_l1[i] + vec_h1[i] * vec_r0[i]
+            let mut point_r: ProjectivePoint<C> = pairs.iter().map(|&(i0, i1)| {
+                vec_g[i0] * vec_l[i1] + vec_h[i1] * vec_r[i0]
             }).sum();
+            if let Some(point_q) = point_q {
+                point_l += *point_q * pairs.iter().map(|&(i0, i1)| vec_l[i0] * vec_r[i1]).sum::<Scalar<C>>();
+                point_r += *point_q * pairs.iter().map(|&(i0, i1)| vec_l[i1] * vec_r[i0]).sum::<Scalar<C>>();
+            }
+            let mut sigmas = None;
+            if let Some(point_u) = point_u {
+                let sigma_l = Scalar::<C>::random(&mut rng);
+                let sigma_r = Scalar::<C>::random(&mut rng);
+                point_l += *point_u * sigma_l;
+                point_r += *point_u * sigma_r;
+                sigmas = Some((sigma_l, sigma_r));
+            }
             vec_point_l.push(point_l);
             vec_point_r.push(point_r);
 
-            let x = Self::challenge::<D>(&target, &point_l, &point_r);
+            x = Self::round_challenge::<D>(version, &generators, vec_point_l.len() - 1, &x, &target, &point_l, &point_r);
             let x_inv = x.invert().unwrap();
 
             point_p = point_l * x * x + point_p + point_r * x_inv * x_inv;
-            vec_g = (0..n).map(|i| vec_g0[i] * x_inv + vec_g1[i] * x).collect();
-            vec_h = (0..n).map(|i| vec_h0[i] * x + vec_h1[i] * x_inv).collect();
-            vec_l = (0..n).map(|i| vec_l0[i] * x + vec_l1[i] * x_inv).collect();
-            vec_r = (0..n).map(|i| vec_r0[i] * x_inv + vec_r1[i] * x).collect();
+            if let (Some(blind), Some((sigma_l, sigma_r))) = (blind.as_mut(), sigmas) {
+                *blind += sigma_l * x * x + sigma_r * x_inv * x_inv;
+            }
+            vec_g = pairs.iter().map(|&(i0, i1)| vec_g[i0] * x_inv + vec_g[i1] * x).collect();
+            vec_h = pairs.iter().map(|&(i0, i1)| vec_h[i0] * x + vec_h[i1] * x_inv).collect();
+            vec_l = pairs.iter().map(|&(i0, i1)| vec_l[i0] * x + vec_l[i1] * x_inv).collect();
+            vec_r = pairs.iter().map(|&(i0, i1)| vec_r[i0] * x_inv + vec_r[i1] * x).collect();
 
             debug_assert_eq!(
                 point_p, 
-                (0..n).map(|i| vec_g[i] * vec_l[i] + vec_h[i] * vec_r[i]).sum()
-
+                (0..n).map(|i| vec_g[i] * vec_l[i] + vec_h[i] * vec_r[i]).sum::<ProjectivePoint<C>>()
+                    + point_u.zip(blind).map(|(point_u, blind)| *point_u * blind).unwrap_or_default()
+                    + point_q.map(|point_q| *point_q * (0..n).map(|i| vec_l[i] * vec_r[i]).sum::<Scalar<C>>()).unwrap_or_default()
             );
         }
 
         let l = vec_l[0];
         let r = vec_r[0];
 
-        BulletProof::<C>{
-            target: target.to_owned(),
+        (BulletProof::<C>{
+            target,
             vec_point_l,
             vec_point_r,
             l,
-            r,
+            r
+        }, blind)
+    }
+
+    /// Challenge weighting the statements folded by `prove_aggregated`.
+    #[inline]
+    fn aggregation_challenge<D>(vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], targets: &[ProjectivePoint<C>]) -> Scalar<C>
+    where
+        D: Digest
+    {
+        let mut hasher = D::new()
+            .chain_update(b"bulletproof-aggregate")
+            .chain_update(Self::generators_digest::<D>(vec_g, vec_h));
+        for target in targets {
+            Digest::update(&mut hasher, target.to_bytes());
         }
+        reduce_digest::<C>(&hasher.finalize())
+    }
+
+    /// Proves knowledge of every `(l, r)` in `witnesses` for the matching entry of
+    /// `targets`, all over the same generators, with a single folding proof.
+    ///
+    /// The statements are combined with powers of a challenge derived from all
+    /// targets, so the proof is only as large as one produced by `prove`.
+    pub fn prove_aggregated<D>(vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], witnesses: &[Witness<C>], targets: &[ProjectivePoint<C>]) -> anyhow::Result<BulletProof<C>> 
+    where
+        D: Digest
+    {
+        let n = vec_g.len();
+        anyhow::ensure!(!witnesses.is_empty(), "No witnesses to aggregate");
+        anyhow::ensure!(witnesses.len() == targets.len(), "Got {} witnesses for {} targets", witnesses.len(), targets.len());
+        anyhow::ensure!(vec_h.len() == n && witnesses.iter().all(|(l, r)| l.len() == n && r.len() == n), "Witness length does not match the generators");
+
+        let z = Self::aggregation_challenge::<D>(vec_g, vec_h, targets);
+        let mut vec_l = vec![Scalar::<C>::ZERO; n];
+        let mut vec_r = vec![Scalar::<C>::ZERO; n];
+        let mut target = ProjectivePoint::<C>::identity();
+        let mut weight = Scalar::<C>::ONE;
+        for ((l, r), point) in witnesses.iter().zip(targets.iter()) {
+            for i in 0..n {
+                vec_l[i] += l[i] * weight;
+                vec_r[i] += r[i] * weight;
+            }
+            target += *point * weight;
+            weight *= z;
+        }
+        Ok(Self::prove::<D>(vec_g, vec_h, &vec_l, &vec_r, &target))
+    }
+
+    /// Verifies a proof produced by `prove_aggregated` for the same `targets`.
+    pub fn verify_aggregated<D>(&self, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], targets: &[ProjectivePoint<C>]) -> anyhow::Result<()> 
+    where
+        D: Digest
+    {
+        anyhow::ensure!(!targets.is_empty(), "No targets to verify");
+        let z = Self::aggregation_challenge::<D>(vec_g, vec_h, targets);
+        let mut target = ProjectivePoint::<C>::identity();
+        let mut weight = Scalar::<C>::ONE;
+        for point in targets {
+            target += *point * weight;
+            weight *= z;
+        }
+        anyhow::ensure!(self.target == target, "Aggregated target mismatch");
+        self.verify::<D>(vec_g, vec_h)
     }
 
     pub fn verify<D>(&self, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> anyhow::Result<()> 
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
+    {
+        self.check_rounds(vec_g.len())?;
+        self.verify_with_blinding::<D>(TranscriptVersion::default(), FoldStrategy::default(), &mut vec_g.to_vec(), &mut vec_h.to_vec(), None, None)
+    }
+
+    /// Same as `verify`, under the transcript `version` instead of the default. This is
+    /// the only way to accept `V1` proofs, whose challenges do not bind the generators.
+    pub fn verify_versioned<D>(&self, version: TranscriptVersion, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> anyhow::Result<()> 
+    where
+        D: Digest
+    {
+        self.check_rounds(vec_g.len())?;
+        self.verify_with_blinding::<D>(version, FoldStrategy::default(), &mut vec_g.to_vec(), &mut vec_h.to_vec(), None, None)
+    }
+
+    /// Verifies a proof produced by `prove_with_strategy` with the same `strategy`.
+    pub fn verify_with_strategy<D>(&self, strategy: FoldStrategy, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> anyhow::Result<()> 
+    where
+        D: Digest
+    {
+        self.check_rounds(vec_g.len())?;
+        self.verify_with_blinding::<D>(TranscriptVersion::default(), strategy, &mut vec_g.to_vec(), &mut vec_h.to_vec(), None, None)
+    }
+
+    /// Same as `verify`, with the `n` generators produced by `base_g`/`base_h`.
+    ///
+    /// Nothing of length `n` is stored: the bases are evaluated once for the transcript
+    /// and once more straight into the final multiscalar sum, each weighted by the
+    /// product of round challenges that folding would have applied to it.
+    pub fn verify_with_bases<D, G, H>(&self, n: usize, base_g: G, base_h: H) -> anyhow::Result<()> 
+    where
+        D: Digest,
+        G: Fn(usize) -> ProjectivePoint<C>,
+        H: Fn(usize) -> ProjectivePoint<C>
+    {
+        self.check_rounds(n)?;
+        let rounds = self.vec_point_l.len();
+        let generators = Self::points_digest::<D>((0..n).map(&base_g).chain((0..n).map(&base_h)));
+
+        let mut x = Scalar::<C>::ZERO;
+        let mut point_p = self.target;
+        let mut challenges = Vec::with_capacity(rounds);
+        for (i, (point_l, point_r)) in self.vec_point_l.iter().zip(self.vec_point_r.iter()).enumerate() {
+            x = Self::round_challenge::<D>(TranscriptVersion::V2, &generators, i, &x, &self.target, point_l, point_r);
+            let x_inv = invert_challenge::<C>(&x)?;
+            point_p += *point_l * (x * x) + *point_r * (x_inv * x_inv);
+            challenges.push((x, x_inv));
+        }
+
+        // Folding by halves, round `k` scales `g_i` by `x_k` if bit `rounds - 1 - k` of
+        // `i` is set and by `x_k^-1` otherwise; `h_i` gets the inverse factor.
+        let folded = (0..n).fold(ProjectivePoint::<C>::identity(), |acc, i| {
+            let (s, s_inv) = challenges.iter().enumerate().fold((Scalar::<C>::ONE, Scalar::<C>::ONE), |(s, s_inv), (k, (x, x_inv))| {
+                match (i >> (rounds - 1 - k)) & 1 {
+                    1 => (s * x, s_inv * x_inv),
+                    _ => (s * x_inv, s_inv * x)
+                }
+            });
+            acc + base_g(i) * (s * self.l) + base_h(i) * (s_inv * self.r)
+        });
+        anyhow::ensure!(bool::from(point_p.ct_eq(&folded)), "Bulletproof verification failed");
+        Ok(())
+    }
+
+    /// Same as `verify`, folding `vec_g` and `vec_h` in place instead of copying them,
+    /// and returning the outcome of the final equation instead of failing on it. Errors
+    /// still report a proof of the wrong shape. The slices' contents are unspecified
+    /// afterwards. The group operations go through `B`.
+    pub(crate) fn check_in_place<D, B>(&self, target: &ProjectivePoint<C>, vec_g: &mut [ProjectivePoint<C>], vec_h: &mut [ProjectivePoint<C>]) -> anyhow::Result<Choice> 
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        self.check_rounds(vec_g.len())?;
+        self.check_with_blinding::<D, B>(TranscriptVersion::default(), FoldStrategy::default(), target, vec_g, vec_h, None, None).map(|(valid, _)| valid)
+    }
+
+    /// Same as `verify`, for the statement about `target` instead of the proof's own
+    /// `target`, which callers that can recompute it need not store or send.
+    pub fn verify_with_target<D>(&self, target: &ProjectivePoint<C>, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> anyhow::Result<()> 
+    where
+        D: Digest
+    {
+        self.check_rounds(vec_g.len())?;
+        let (valid, _) = self.check_with_blinding::<D, GenericBackend>(TranscriptVersion::default(), FoldStrategy::default(), target, &mut vec_g.to_vec(), &mut vec_h.to_vec(), None, None)?;
+        anyhow::ensure!(bool::from(valid), "Bulletproof verification failed");
+        Ok(())
+    }
+
+    /// Verifies the bare relation `prove` argues, outside of any incognito signature:
+    /// knowledge of `l` and `r` with `target = <l, vec_g> + <r, vec_h>`. The inner product
+    /// `<l, r>` is not bound; use `prove_inner_product` for that.
+    ///
+    /// Returns the folded commitment `target + sum(x_i^2 L_i + x_i^-2 R_i)`, which equals
+    /// `g' l + h' r` for the folded generators `g'`, `h'`, so callers can build further
+    /// checks on top of the same proof.
+    pub fn verify_relation<D>(&self, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> anyhow::Result<ProjectivePoint<C>>
+    where
+        D: Digest
+    {
+        self.check_rounds(vec_g.len())?;
+        let (valid, point_p) = self.check_with_blinding::<D, GenericBackend>(TranscriptVersion::default(), FoldStrategy::default(), &self.target, &mut vec_g.to_vec(), &mut vec_h.to_vec(), None, None)?;
+        anyhow::ensure!(bool::from(valid), "Bulletproof verification failed");
+        Ok(point_p)
+    }
+
+    /// Verifies a proof produced by `prove_inner_product` with the same `point_q`.
+    pub fn verify_inner_product<D>(&self, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], point_q: &ProjectivePoint<C>) -> anyhow::Result<()> 
+    where
+        D: Digest
     {
+        self.check_rounds(vec_g.len())?;
+        self.verify_with_blinding::<D>(TranscriptVersion::default(), FoldStrategy::default(), &mut vec_g.to_vec(), &mut vec_h.to_vec(), None, Some(point_q))
+    }
+
+    /// Expects `check_rounds` to have accepted `vec_g.len()`.
+    fn verify_with_blinding<D>(&self, version: TranscriptVersion, strategy: FoldStrategy, vec_g: &mut [ProjectivePoint<C>], vec_h: &mut [ProjectivePoint<C>], blinding: Option<(&ProjectivePoint<C>, &Scalar<C>)>, point_q: Option<&ProjectivePoint<C>>) -> anyhow::Result<()> 
+    where
+        D: Digest
+    {
+        let (valid, _) = self.check_with_blinding::<D, GenericBackend>(version, strategy, &self.target, vec_g, vec_h, blinding, point_q)?;
+        anyhow::ensure!(bool::from(valid), "Bulletproof verification failed");
+        Ok(())
+    }
+
+    /// Folds the generators and evaluates the final equation for `target`, also returning
+    /// the folded commitment `P` it was evaluated on. `blinding` is the base `point_u`
+    /// and aggregated scalar of a blinded proof. The round challenges follow `version`.
+    #[allow(clippy::too_many_arguments)]
+    fn check_with_blinding<D, B>(&self, version: TranscriptVersion, strategy: FoldStrategy, target: &ProjectivePoint<C>, vec_g: &mut [ProjectivePoint<C>], vec_h: &mut [ProjectivePoint<C>], blinding: Option<(&ProjectivePoint<C>, &Scalar<C>)>, point_q: Option<&ProjectivePoint<C>>) -> anyhow::Result<(Choice, ProjectivePoint<C>)> 
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        anyhow::ensure!(vec_h.len() == vec_g.len(), "Mismatched generator lengths");
         let mut n = vec_g.len();
-        anyhow::ensure!(n == 2_usize.pow(self.vec_point_l.len() as u32));
 
-        let mut vec_g = vec_g.to_owned();
-        let mut vec_h = vec_h.to_owned();
-        let mut point_p = self.target.to_owned();
+        // Bases and exponents of the final equation's right-hand side.
+        let (mut points, mut scalars) = (Vec::with_capacity(4), Vec::with_capacity(4));
+        if let Some((point_u, blind)) = blinding {
+            points.push(*point_u);
+            scalars.push(*blind);
+        }
+        if let Some(point_q) = point_q {
+            points.push(*point_q);
+            scalars.push(self.l * self.r);
+        }
+
+        let generators = match version {
+            TranscriptVersion::V1 => Output::<D>::default(),
+            TranscriptVersion::V2 => Self::generators_digest::<D>(vec_g, vec_h)
+        };
+        let mut x = Scalar::<C>::ZERO;
+
+        let mut point_p = target.to_owned();
 
         for i in 0..self.vec_point_l.len() {
-            n = n / 2;
+            n /= 2;
 
-            let (vec_g0, vec_g1) = vec_g.split_at(n);
-            let (vec_h0, vec_h1) = vec_h.split_at(n);
             let point_l = self.vec_point_l[i];
             let point_r = self.vec_point_r[i];
 
-            let x = Self::challenge::<D>(&self.target, &point_l, &point_r);
-            let x_inv = x.invert().unwrap();
-
-            point_p = point_l * x * x + point_p + point_r * x_inv * x_inv;
-            vec_g = (0..n).map(|i| vec_g0[i] * x_inv + vec_g1[i] * x).collect();
-            vec_h = (0..n).map(|i| vec_h0[i] * x + vec_h1[i] * x_inv).collect();
+            x = Self::round_challenge::<D>(version, &generators, i, &x, target, &point_l, &point_r);
+            let x_inv = invert_challenge::<C>(&x)?;
+
+            point_p = B::add(&point_p, &B::msm(&[point_l, point_r], &[x * x, x_inv * x_inv]));
+            // Fold in place: the first `n` entries become the next round's generators.
+            // Pairs never read an index below the one being written.
+            for j in 0..n {
+                let (j0, j1) = strategy.pair(n, j);
+                vec_g[j] = B::msm(&[vec_g[j0], vec_g[j1]], &[x_inv, x]);
+                vec_h[j] = B::msm(&[vec_h[j0], vec_h[j1]], &[x, x_inv]);
+            }
         }
 
-        anyhow::ensure!(point_p == vec_g[0] * self.l + vec_h[0] * self.r);
+        points.extend([vec_g[0], vec_h[0]]);
+        scalars.extend([self.l, self.r]);
+        Ok((point_p.ct_eq(&B::msm(&points, &scalars)), point_p))
+    }
+
+    /// Rejects proofs whose shape does not match `n` generators, before anything
+    /// proportional to the claimed size is allocated.
+    fn check_rounds(&self, n: usize) -> anyhow::Result<()> {
+        let rounds = self.vec_point_l.len();
+        anyhow::ensure!(rounds <= MAX_ROUNDS, "Bulletproof claims {} rounds, at most {} are supported", rounds, MAX_ROUNDS);
+        anyhow::ensure!(rounds == self.vec_point_r.len(), "Bulletproof has mismatched L/R lengths");
+        anyhow::ensure!(n == 1 << rounds, "Bulletproof has {} rounds for {} generators", rounds, n);
         Ok(())
+    }
 
+    /// Recomputes the per-round challenges against `vec_g`/`vec_h` without folding.
+    /// Uses the `V2` transcript, as `verify` does.
+    pub(crate) fn round_challenges<D>(&self, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>]) -> anyhow::Result<Vec<Scalar<C>>>
+    where
+        D: Digest
+    {
+        self.check_rounds(vec_g.len())?;
+
+        let generators = Self::generators_digest::<D>(vec_g, vec_h);
+        let mut x = Scalar::<C>::ZERO;
+        Ok(self.vec_point_l.iter().zip(self.vec_point_r.iter()).enumerate().map(|(i, (point_l, point_r))| {
+            x = Self::round_challenge::<D>(TranscriptVersion::V2, &generators, i, &x, &self.target, point_l, point_r);
+            x
+        }).collect())
     }
 }
 
-impl <C:CurveArithmetic> From<BulletProof<C>> for BulletProofSerde<C>
+impl <C: CurveArithmetic> BlindedBulletProof<C>
 where
-    Scalar<C>: Serialize + DeserializeOwned,
-    AffinePoint<C>: Serialize + DeserializeOwned
+    ProjectivePoint<C>: GroupEncoding
 {
-    fn from(value: BulletProof<C>) -> Self {
-        Self {
-            target: value.target.to_affine(),
-            vec_point_l: value.vec_point_l.into_iter().map(|each| each.to_affine()).collect(),
-            vec_point_r: value.vec_point_r.into_iter().map(|each| each.to_affine()).collect(),
-            l: value.l,
-            r: value.r,
-        }
+    /// Blinded target the proof argues about.
+    pub fn target(&self) -> &ProjectivePoint<C> {
+        &self.proof.target
+    }
+
+    /// Verifies the proof with the `point_u` it was produced with.
+    pub fn verify<D>(&self, vec_g: &[ProjectivePoint<C>], vec_h: &[ProjectivePoint<C>], point_u: &ProjectivePoint<C>) -> anyhow::Result<()> 
+    where
+        D: Digest
+    {
+        self.proof.check_rounds(vec_g.len())?;
+        self.proof.verify_with_blinding::<D>(TranscriptVersion::default(), FoldStrategy::default(), &mut vec_g.to_vec(), &mut vec_h.to_vec(), Some((point_u, &self.blind)), None)
     }
 }
 
-impl <C:CurveArithmetic> From<BulletProofSerde<C>> for BulletProof<C>
-where
-    Scalar<C>: Serialize + DeserializeOwned,
-    AffinePoint<C>: Serialize + DeserializeOwned
-{
-    fn from(value: BulletProofSerde<C>) -> Self {
-        Self {
-            target: ProjectivePoint::<C>::from(value.target),
-            vec_point_l: value.vec_point_l.into_iter().map(|each| ProjectivePoint::<C>::from(each)).collect(),
-            vec_point_r: value.vec_point_r.into_iter().map(|each| ProjectivePoint::<C>::from(each)).collect(),
-            l: value.l,
-            r: value.r,
-        }
+#[cfg(feature = "arbitrary")]
+impl <'a, C: CurveArithmetic> arbitrary::Arbitrary<'a> for BulletProof<C> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            target: crate::fuzzing::point::<C>(u)?,
+            vec_point_l: crate::fuzzing::points::<C>(u)?,
+            vec_point_r: crate::fuzzing::points::<C>(u)?,
+            l: crate::fuzzing::scalar::<C>(u)?,
+            r: crate::fuzzing::scalar::<C>(u)?
+        })
     }
 }
 
@@ -177,7 +599,7 @@ mod tests {
     use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
     use rand::rngs::ThreadRng;
 
-    use super::BulletProof;
+    use super::{BulletProof, FoldStrategy, TranscriptVersion, MAX_ROUNDS};
 
     #[test]
 
@@ -201,6 +623,28 @@ mod tests {
         assert!(target == proof.target);
     }
 
+    #[test]
+    fn test_fold_strategies() {
+        let n = 16;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+
+        let halves = BulletProof::<Secp256k1>::prove_with_strategy::<Sha256>(FoldStrategy::Halves, &g, &h, &l, &r, &target);
+        halves.verify_with_strategy::<Sha256>(FoldStrategy::Halves, &g, &h).unwrap();
+        halves.verify::<Sha256>(&g, &h).unwrap();
+        let interleaved = BulletProof::<Secp256k1>::prove_with_strategy::<Sha256>(FoldStrategy::Interleaved, &g, &h, &l, &r, &target);
+        interleaved.verify_with_strategy::<Sha256>(FoldStrategy::Interleaved, &g, &h).unwrap();
+
+        assert!(halves.verify_with_strategy::<Sha256>(FoldStrategy::Interleaved, &g, &h).is_err());
+        assert!(interleaved.verify_with_strategy::<Sha256>(FoldStrategy::Halves, &g, &h).is_err());
+        assert!(interleaved.verify::<Sha256>(&g, &h).is_err());
+    }
+
+    #[cfg(feature = "serde")]
     #[test]
     fn test_convertion_compact() {
         let n = 16;
@@ -219,4 +663,159 @@ mod tests {
         assert!(target == proof_converted.target);
         assert!(proof_converted == proof);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_blinded() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let u = ProjectivePoint::GENERATOR * Scalar::random(&mut rng);
+
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+
+        let proof1 = BulletProof::<Secp256k1>::prove_blinded::<Sha256>(&g, &h, &l, &r, &target, &u);
+        let proof2 = BulletProof::<Secp256k1>::prove_blinded::<Sha256>(&g, &h, &l, &r, &target, &u);
+        proof1.verify::<Sha256>(&g, &h, &u).unwrap();
+        proof2.verify::<Sha256>(&g, &h, &u).unwrap();
+        assert!(proof1.target() != proof2.target());
+        assert!(*proof1.target() != target);
+        assert!(proof1.proof.verify::<Sha256>(&g, &h).is_err());
+        assert!(proof1.verify::<Sha256>(&g, &h, &ProjectivePoint::GENERATOR).is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            let proof_converted: super::BlindedBulletProof<Secp256k1> = bincode::deserialize(&bincode::serialize(&proof1).unwrap()).unwrap();
+            proof_converted.verify::<Sha256>(&g, &h, &u).unwrap();
+            // The inner proof encodes exactly like an unblinded one.
+            let inner: BulletProof<Secp256k1> = bincode::deserialize(&bincode::serialize(&proof1.proof).unwrap()).unwrap();
+            assert_eq!(inner, proof1.proof);
+        }
+    }
+
+    #[test]
+    fn test_transcript_versions() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+
+        let proof_v1 = BulletProof::<Secp256k1>::prove_versioned::<Sha256>(TranscriptVersion::V1, &g, &h, &l, &r, &target);
+        let proof_v2 = BulletProof::<Secp256k1>::prove_versioned::<Sha256>(TranscriptVersion::V2, &g, &h, &l, &r, &target);
+        proof_v2.verify::<Sha256>(&g, &h).unwrap();
+        assert!(proof_v1.verify::<Sha256>(&g, &h).is_err());
+        proof_v1.verify_versioned::<Sha256>(TranscriptVersion::V1, &g, &h).unwrap();
+        assert!(proof_v2.verify_versioned::<Sha256>(TranscriptVersion::V1, &g, &h).is_err());
+
+        let mut g_swapped = g.clone();
+        g_swapped.swap(0, 1);
+        assert!(proof_v2.verify::<Sha256>(&g_swapped, &h).is_err());
+    }
+
+    #[test]
+    fn test_rounds_bounded() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+
+        let mut proof = BulletProof::<Secp256k1>::prove::<Sha256>(&g, &h, &l, &r, &target);
+        proof.vec_point_l = vec![ProjectivePoint::IDENTITY; 30];
+        proof.vec_point_r = vec![ProjectivePoint::IDENTITY; 30];
+        let err = proof.verify::<Sha256>(&g, &h).unwrap_err();
+        assert!(err.to_string().contains(&MAX_ROUNDS.to_string()));
+
+        proof.vec_point_l.truncate(3);
+        proof.vec_point_r.truncate(2);
+        assert!(proof.verify::<Sha256>(&g, &h).is_err());
+    }
+
+    #[test]
+    fn test_aggregated() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let witnesses: Vec<(Vec<_>, Vec<_>)> = (0..3).map(|_| (
+            (0..n).map(|_| Scalar::random(&mut rng)).collect(),
+            (0..n).map(|_| Scalar::random(&mut rng)).collect()
+        )).collect();
+        let targets: Vec<ProjectivePoint> = witnesses.iter().map(|(l, r)| (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum()).collect();
+        let slices: Vec<_> = witnesses.iter().map(|(l, r)| (l.as_slice(), r.as_slice())).collect();
+
+        let proof = BulletProof::<Secp256k1>::prove_aggregated::<Sha256>(&g, &h, &slices, &targets).unwrap();
+        proof.verify_aggregated::<Sha256>(&g, &h, &targets).unwrap();
+        assert!(proof.verify_aggregated::<Sha256>(&g, &h, &targets[..2]).is_err());
+        let mut swapped = targets.clone();
+        swapped.swap(0, 1);
+        assert!(proof.verify_aggregated::<Sha256>(&g, &h, &swapped).is_err());
+        let mut wrong = targets.clone();
+        wrong[2] += ProjectivePoint::GENERATOR;
+        assert!(proof.verify_aggregated::<Sha256>(&g, &h, &wrong).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_bases() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let d = Scalar::random(&mut rng);
+        let base_g: Vec<_> = (0..n).map(|i| g[i] + h[i] * d).collect();
+
+        let target: ProjectivePoint = (0..n).map(|i| base_g[i] * l[i] + h[i] * r[i]).sum();
+        let proof = BulletProof::<Secp256k1>::prove::<Sha256>(&base_g, &h, &l, &r, &target);
+
+        proof.verify::<Sha256>(&base_g, &h).unwrap();
+        proof.verify_with_bases::<Sha256, _, _>(n, |i| g[i] + h[i] * d, |i| h[i]).unwrap();
+        assert!(proof.verify::<Sha256>(&g, &h).is_err());
+        assert!(proof.verify_with_bases::<Sha256, _, _>(n, |i| g[i], |i| h[i]).is_err());
+        assert!(proof.verify_with_bases::<Sha256, _, _>(n / 2, |i| base_g[i], |i| h[i]).is_err());
+
+        // Every round's weighting matters, including a single-generator proof.
+        let mut tampered = proof.clone();
+        tampered.vec_point_l.swap(0, 1);
+        assert!(tampered.verify_with_bases::<Sha256, _, _>(n, |i| base_g[i], |i| h[i]).is_err());
+        let single = BulletProof::<Secp256k1>::prove::<Sha256>(&g[..1], &h[..1], &l[..1], &r[..1], &(g[0] * l[0] + h[0] * r[0]));
+        single.verify_with_bases::<Sha256, _, _>(1, |i| g[i], |i| h[i]).unwrap();
+    }
+
+    #[test]
+    fn test_verify_relation() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+        let proof = BulletProof::<Secp256k1>::prove::<Sha256>(&g, &h, &l, &r, &target);
+        let folded = proof.verify_relation::<Sha256>(&g, &h).unwrap();
+
+        let vec_x = proof.round_challenges::<Sha256>(&g, &h).unwrap();
+        let expected = vec_x.iter().zip(proof.vec_point_l.iter().zip(&proof.vec_point_r)).fold(target, |acc, (x, (point_l, point_r))| {
+            let x_inv = x.invert().unwrap();
+            acc + *point_l * x * x + *point_r * x_inv * x_inv
+        });
+        assert_eq!(folded, expected);
+
+        let mut forged = proof.clone();
+        forged.l += Scalar::ONE;
+        assert!(forged.verify_relation::<Sha256>(&g, &h).is_err());
+        assert!(proof.verify_relation::<Sha256>(&h, &g).is_err());
+        let blinded = BulletProof::<Secp256k1>::prove_blinded::<Sha256>(&g, &h, &l, &r, &target, &ProjectivePoint::GENERATOR);
+        assert!(blinded.proof.verify_relation::<Sha256>(&g, &h).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashMap};
+
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, AffinePoint, CurveArithmetic, ProjectivePoint, Scalar};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{incognito::{IncognitoParams, IncognitoSignature}, ring::ring_hash};
+
+/// Bounded least-recently-used memo of successful verifications, for servers that
+/// see the same signature more than once.
+///
+/// Entries are keyed by a digest of the ring, the message and the compact encoding
+/// of the signature, so any change to one of them is a miss. Only successes are
+/// stored; a rejected signature is verified again every time. The key does not cover
+/// the parameters, so keep one cache per `IncognitoParams`.
+#[derive(Debug, Clone)]
+pub struct VerifyCache {
+    capacity: usize,
+    entries: HashMap<Vec<u8>, u64>,
+    recency: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+    hits: u64,
+    misses: u64
+}
+
+impl VerifyCache {
+    /// Remembers at most `capacity` signatures, evicting the least recently used.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Verifications answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Verifications that had to run `IncognitoParams::verify`.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Same as `IncognitoParams::verify`, skipping the work for a signature this cache
+    /// has already accepted on the same ring and message.
+    pub fn verify<C, D, const MAXN: usize>(
+        &mut self,
+        params: &IncognitoParams<C, MAXN>,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        C: CurveArithmetic,
+        Scalar<C>: Serialize + DeserializeOwned,
+        AffinePoint<C>: Serialize + DeserializeOwned,
+        ProjectivePoint<C>: GroupEncoding,
+        D: Digest
+    {
+        let key = D::new()
+            .chain_update(b"incognito-verify-cache")
+            .chain_update(ring_hash::<C, D>(pks))
+            .chain_update((message.len() as u64).to_le_bytes())
+            .chain_update(message)
+            .chain_update(signature.to_bytes_compact()?)
+            .finalize()
+            .to_vec();
+        if self.touch(&key) {
+            self.hits += 1;
+            return Ok(());
+        }
+        self.misses += 1;
+        params.verify::<D>(pks, message, signature)?;
+        self.insert(key);
+        Ok(())
+    }
+
+    /// Marks `key` as most recently used, returning whether it was present.
+    fn touch(&mut self, key: &[u8]) -> bool {
+        let Some(tick) = self.entries.get_mut(key) else {
+            return false;
+        };
+        let entry = self.recency.remove(tick).expect("recency tracks every entry");
+        self.tick += 1;
+        *tick = self.tick;
+        self.recency.insert(self.tick, entry);
+        true
+    }
+
+    fn insert(&mut self, key: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key.clone(), self.tick);
+        self.recency.insert(self.tick, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+
+    use super::VerifyCache;
+
+    #[test]
+    fn test_cache_hits() {
+        let n = 4;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let incsigs: Vec<_> = (0..3).map(|index| {
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            params.convert::<Sha256>(&pks, &message, &signature, index).unwrap()
+        }).collect();
+
+        let mut cache = VerifyCache::new(2);
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[0]).unwrap();
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[0]).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // Flips a bit of the bulletproof's final `r`, ahead of the blinding and version.
+        let mut bytes = incsigs[0].to_bytes_compact().unwrap();
+        let at = bytes.len() - 3;
+        bytes[at] ^= 1;
+        let tampered = IncognitoSignature::from_bytes_compact(&bytes).unwrap();
+        for _ in 0..2 {
+            assert!(cache.verify::<_, Sha256, 4>(&params, &pks, &message, &tampered).is_err());
+        }
+        assert!(cache.verify::<_, Sha256, 4>(&params, &pks, &[0, 3, 6], &incsigs[0]).is_err());
+        assert_eq!((cache.hits(), cache.misses()), (1, 4));
+        assert_eq!(cache.len(), 1);
+
+        // Filling the cache evicts the least recently used signature, here the second.
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[1]).unwrap();
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[0]).unwrap();
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[2]).unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[0]).unwrap();
+        cache.verify::<_, Sha256, 4>(&params, &pks, &message, &incsigs[1]).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (3, 7));
+    }
+}
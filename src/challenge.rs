@@ -0,0 +1,193 @@
+use std::ops::{Add, Mul};
+
+use digest::Digest;
+use elliptic_curve::{generic_array::typenum::Unsigned, ops::Reduce, CurveArithmetic, Field, FieldBytes, FieldBytesSize, Scalar};
+
+use crate::error::VerifyError;
+
+/// How a Fiat–Shamir transcript is mapped to a scalar.
+pub trait ChallengeDerive<C: CurveArithmetic> {
+    /// Hashes the concatenation of `parts` to a scalar.
+    fn hash_to_scalar<D>(parts: &[&[u8]]) -> Scalar<C>
+    where
+        D: Digest;
+}
+
+/// Fails if `D` outputs fewer bytes than a scalar. Longer digests are fine, but a
+/// shorter one leaves the challenge space smaller than the group order.
+pub fn check_digest_size<C: CurveArithmetic, D: Digest>() -> Result<(), VerifyError> {
+    let expected = FieldBytesSize::<C>::USIZE;
+    let actual = <D as Digest>::output_size();
+    if actual < expected {
+        return Err(VerifyError::DigestSize { expected, actual });
+    }
+    Ok(())
+}
+
+/// Inverts a challenge. A zero challenge only arises from a hash collision with
+/// zero, but the inputs are attacker-chosen, so it is an error rather than a panic.
+pub fn invert_challenge<C: CurveArithmetic>(challenge: &Scalar<C>) -> Result<Scalar<C>, VerifyError> {
+    Option::from(challenge.invert()).ok_or(VerifyError::ZeroChallenge)
+}
+
+/// Reduces a big-endian digest of any length modulo the scalar field.
+///
+/// The digest is split into field-sized chunks from the right and combined as
+/// `acc * 2^(8 * FieldBytesSize) + chunk`, so a field-sized digest reduces exactly
+/// as `Reduce::reduce_bytes` would, and longer digests (e.g. SHA-512 on a 256-bit
+/// curve) are reduced as one wide integer.
+pub fn reduce_digest<C: CurveArithmetic>(bytes: &[u8]) -> Scalar<C> {
+    let size = FieldBytesSize::<C>::USIZE;
+    let radix = Scalar::<C>::from(2).pow_vartime([8 * size as u64]);
+    let head = match bytes.len() % size {
+        0 => size.min(bytes.len()),
+        rem => rem
+    };
+    let (first, rest) = bytes.split_at(head);
+    let mut chunk = FieldBytes::<C>::default();
+    chunk[size - first.len()..].copy_from_slice(first);
+    rest.chunks(size).fold(<Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&chunk), |acc, each| {
+        acc * radix + <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(FieldBytes::<C>::from_slice(each))
+    })
+}
+
+/// Reduces a single digest. This is what the signatures use by default;
+/// the result is biased when the group order is far below `2^(8 * FieldBytesSize)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NarrowReduce;
+
+/// Reduces two digests taken together as one double-width integer,
+/// which makes the bias negligible for any group order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WideReduce;
+
+impl <C: CurveArithmetic> ChallengeDerive<C> for NarrowReduce {
+    fn hash_to_scalar<D>(parts: &[&[u8]]) -> Scalar<C>
+    where
+        D: Digest
+    {
+        debug_assert!(check_digest_size::<C, D>().is_ok(), "digest shorter than the scalar field");
+        let mut hasher = D::new();
+        for part in parts {
+            Digest::update(&mut hasher, part);
+        }
+        reduce_digest::<C>(&hasher.finalize())
+    }
+}
+
+impl <C: CurveArithmetic> ChallengeDerive<C> for WideReduce {
+    fn hash_to_scalar<D>(parts: &[&[u8]]) -> Scalar<C>
+    where
+        D: Digest
+    {
+        debug_assert!(check_digest_size::<C, D>().is_ok(), "digest shorter than the scalar field");
+        let half = |index: u8| {
+            let mut hasher = D::new().chain_update([index]);
+            for part in parts {
+                Digest::update(&mut hasher, part);
+            }
+            reduce_digest::<C>(&hasher.finalize())
+        };
+        let bits = 8 * <D as Digest>::output_size() as u64;
+        let radix = Scalar::<C>::from(2).pow_vartime([bits]);
+        wide_combine(half(1), half(0), radix)
+    }
+}
+
+/// `hi * radix + lo`, with `radix = 2^bits` already reduced.
+fn wide_combine<S: Add<Output = S> + Mul<Output = S>>(hi: S, lo: S, radix: S) -> S {
+    hi * radix + lo
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::{Add, Mul};
+
+    use elliptic_curve::{ops::Reduce, Field, FieldBytes};
+    use k256::{sha2::{Digest, Sha224, Sha256, Sha512}, Scalar, Secp256k1, U256};
+
+    use crate::error::VerifyError;
+
+    use super::{check_digest_size, invert_challenge, reduce_digest, wide_combine, ChallengeDerive, NarrowReduce, WideReduce};
+
+    const Q: u32 = 193;
+
+    #[derive(Clone, Copy)]
+    struct Toy(u32);
+
+    impl Add for Toy {
+        type Output = Toy;
+        fn add(self, rhs: Toy) -> Toy {
+            Toy((self.0 + rhs.0) % Q)
+        }
+    }
+
+    impl Mul for Toy {
+        type Output = Toy;
+        fn mul(self, rhs: Toy) -> Toy {
+            Toy((self.0 * rhs.0) % Q)
+        }
+    }
+
+    /// Ratio between the most and least frequent residue.
+    fn skew(counts: &[u32]) -> f64 {
+        *counts.iter().max().unwrap() as f64 / *counts.iter().min().unwrap() as f64
+    }
+
+    #[test]
+    fn test_wide_reduction_bias() {
+        // One-byte "digests" reduced modulo a toy order of 193, over every input.
+        let mut narrow = vec![0; Q as usize];
+        for byte in 0..256u32 {
+            narrow[(byte % Q) as usize] += 1;
+        }
+        let mut wide = vec![0; Q as usize];
+        for hi in 0..256u32 {
+            for lo in 0..256u32 {
+                wide[wide_combine(Toy(hi % Q), Toy(lo % Q), Toy(256 % Q)).0 as usize] += 1;
+            }
+        }
+        assert_eq!(skew(&narrow), 2.0);
+        assert!(skew(&wide) < 1.01);
+    }
+
+    #[test]
+    fn test_reduce_digest() {
+        let narrow = Sha256::digest(b"incognito");
+        assert_eq!(reduce_digest::<Secp256k1>(&narrow), <Scalar as Reduce<U256>>::reduce_bytes(&narrow));
+
+        let wide = Sha512::digest(b"incognito");
+        let hi = <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::<Secp256k1>::from_slice(&wide[..32]));
+        let lo = <Scalar as Reduce<U256>>::reduce_bytes(FieldBytes::<Secp256k1>::from_slice(&wide[32..]));
+        let radix = Scalar::from(2u64).pow_vartime([256]);
+        assert_eq!(reduce_digest::<Secp256k1>(&wide), hi * radix + lo);
+
+        // Short digests are left-padded with zeros.
+        assert_eq!(reduce_digest::<Secp256k1>(&[1, 0]), Scalar::from(256u64));
+        assert_eq!(reduce_digest::<Secp256k1>(&[]), Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_digest_size() {
+        check_digest_size::<Secp256k1, Sha256>().unwrap();
+        check_digest_size::<Secp256k1, Sha512>().unwrap();
+        assert_eq!(check_digest_size::<Secp256k1, Sha224>(), Err(VerifyError::DigestSize { expected: 32, actual: 28 }));
+    }
+
+    #[test]
+    fn test_invert_challenge() {
+        assert_eq!(invert_challenge::<Secp256k1>(&Scalar::from(2u64)).unwrap() * Scalar::from(2u64), Scalar::ONE);
+        assert_eq!(invert_challenge::<Secp256k1>(&Scalar::ZERO), Err(VerifyError::ZeroChallenge));
+    }
+
+    #[test]
+    fn test_derive() {
+        let parts: [&[u8]; 2] = [b"incognito", b"challenge"];
+        let narrow = <NarrowReduce as ChallengeDerive<Secp256k1>>::hash_to_scalar::<Sha256>(&parts);
+        let wide = <WideReduce as ChallengeDerive<Secp256k1>>::hash_to_scalar::<Sha256>(&parts);
+        assert_ne!(narrow, wide);
+        assert_eq!(narrow, <NarrowReduce as ChallengeDerive<Secp256k1>>::hash_to_scalar::<Sha256>(&[b"incognitochallenge"]));
+        assert_eq!(wide, <WideReduce as ChallengeDerive<Secp256k1>>::hash_to_scalar::<Sha256>(&parts));
+        assert_ne!(wide, Scalar::ZERO);
+    }
+}
@@ -0,0 +1,79 @@
+use elliptic_curve::{CurveArithmetic, ProjectivePoint, Scalar};
+
+/// A commitment to a group element under a scalar blinder.
+///
+/// Signatures commit to the signer's key as `point_c_pk = commit(pk, beta)` and to
+/// amounts as `point_v = commit(g * amount, blind)`, and this trait is how those
+/// commitments are opened and checked outside of the proofs. It does not make the
+/// scheme swappable: the linkage, membership and range proofs are written for
+/// Pedersen over the params' own bases, so `IncognitoParams::key_commitment` and
+/// `amount_commitment` always return `Pedersen`, and another implementation only
+/// serves commitments made outside of `convert`.
+pub trait Commitment<C: CurveArithmetic> {
+    fn commit(&self, value: &ProjectivePoint<C>, blind: &Scalar<C>) -> ProjectivePoint<C>;
+
+    /// Checks that `commitment` opens to `value` under `blind`.
+    fn verify_opening(&self, commitment: &ProjectivePoint<C>, value: &ProjectivePoint<C>, blind: &Scalar<C>) -> bool {
+        self.commit(value, blind) == *commitment
+    }
+
+    /// Recovers the committed value from an opening, if the scheme allows it.
+    fn open(&self, commitment: &ProjectivePoint<C>, blind: &Scalar<C>) -> Option<ProjectivePoint<C>>;
+}
+
+/// Pedersen commitment `value + base * blind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pedersen<C: CurveArithmetic> {
+    base: ProjectivePoint<C>
+}
+
+impl <C: CurveArithmetic> Pedersen<C> {
+    pub fn new(base: ProjectivePoint<C>) -> Self {
+        Self {
+            base
+        }
+    }
+}
+
+impl <C: CurveArithmetic> Commitment<C> for Pedersen<C> {
+    fn commit(&self, value: &ProjectivePoint<C>, blind: &Scalar<C>) -> ProjectivePoint<C> {
+        *value + self.base * blind
+    }
+
+    fn open(&self, commitment: &ProjectivePoint<C>, blind: &Scalar<C>) -> Option<ProjectivePoint<C>> {
+        Some(*commitment - self.base * blind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::Commitment;
+
+    #[test]
+    fn test_pedersen_reproduces_signatures() {
+        let n = 8;
+        let index = 6;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let (incsig, beta) = params.convert_with_opening::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let pedersen = params.key_commitment();
+        assert!(pedersen.verify_opening(incsig.point_c_pk(), &pks[index], &beta));
+        assert!(!pedersen.verify_opening(incsig.point_c_pk(), &pks[index - 1], &beta));
+        assert_eq!(pedersen.open(incsig.point_c_pk(), &beta), Some(pks[index]));
+        assert!(incsig.could_be_signer::<Sha256, 8>(&params, &pks[index], &message, &beta));
+
+        let (_, commitment, blind) = params.convert_with_amount::<Sha256>(&pks, &message, &signature, index, 200, 8).unwrap();
+        assert!(params.amount_commitment().verify_opening(&commitment.point_v, &(params.value_base() * Scalar::from(200u64)), &blind));
+    }
+}
@@ -0,0 +1,91 @@
+use std::marker::PhantomData;
+
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, ProjectivePoint, Scalar};
+
+use crate::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+
+/// Bundles `IncognitoParams` with a fixed digest so application code does not
+/// have to repeat `::<D>` and pass `&params` at every call site.
+#[derive(Debug, Clone)]
+pub struct SigningContext<C: CurveArithmetic, D, const MAXN: usize> {
+    params: IncognitoParams<C, MAXN>,
+    _digest: PhantomData<D>
+}
+
+impl <C: CurveArithmetic, D, const MAXN: usize> SigningContext<C, D, MAXN>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    pub fn new(params: IncognitoParams<C, MAXN>) -> Self {
+        Self {
+            params,
+            _digest: PhantomData
+        }
+    }
+
+    pub fn params(&self) -> &IncognitoParams<C, MAXN> {
+        &self.params
+    }
+
+    pub fn sign(&self, sk: &Scalar<C>, message: &[u8]) -> SchnorrSignature<C> {
+        SchnorrSignature::<C>::sign::<D>(sk, message)
+    }
+
+    pub fn convert(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<IncognitoSignature<C>> {
+        self.params.convert::<D>(pks, message, signature, index)
+    }
+
+    pub fn verify(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()> {
+        self.params.verify::<D>(pks, message, signature)
+    }
+}
+
+impl <C: CurveArithmetic, D, const MAXN: usize> From<IncognitoParams<C, MAXN>> for SigningContext<C, D, MAXN>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    fn from(params: IncognitoParams<C, MAXN>) -> Self {
+        Self::new(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::incognito::IncognitoParams;
+
+    use super::SigningContext;
+
+    #[test]
+    fn test_round_trip() {
+        let n = 8;
+        let index = 5;
+        let mut rng = ThreadRng::default();
+        let ctx: SigningContext<Secp256k1, Sha256, 16> = IncognitoParams::new().into();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = ctx.sign(&sks[index], &message);
+        let incsig = ctx.convert(&pks, &message, &signature, index).unwrap();
+        ctx.verify(&pks, &message, &incsig).unwrap();
+        assert!(ctx.verify(&pks, &[0, 3, 6], &incsig).is_err());
+    }
+}
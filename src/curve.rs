@@ -0,0 +1,99 @@
+//! Compile-time check that a curve can be used with this crate.
+//!
+//! Calling `assert_curve_supported::<MyCurve>()` anywhere in a crate fails to compile,
+//! at that call, if `MyCurve` lacks a bound the signatures need, instead of surfacing
+//! as a trait error deep inside a generic function.
+//!
+//! `SchnorrSignature::to_bytes`/`from_bytes` additionally need a 32-byte field and
+//! `AffinePoint<C>: DecompressPoint<C>`; they are the only APIs with extra bounds
+//! besides the optional backends.
+
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, ProjectivePoint};
+#[cfg(feature = "serde")]
+use elliptic_curve::{AffinePoint, Scalar};
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Compiles only if `C` meets every bound needed by signing, conversion, verification
+/// and, with the `serde` feature, serialization.
+///
+/// ```
+/// anon::curve::assert_curve_supported::<k256::Secp256k1>();
+/// ```
+#[cfg(feature = "serde")]
+pub fn assert_curve_supported<C>()
+where
+    C: CurveArithmetic,
+    ProjectivePoint<C>: GroupEncoding,
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned
+{}
+
+/// Compiles only if `C` meets every bound needed by signing, conversion and verification.
+///
+/// ```
+/// anon::curve::assert_curve_supported::<k256::Secp256k1>();
+/// ```
+#[cfg(not(feature = "serde"))]
+pub fn assert_curve_supported<C>()
+where
+    C: CurveArithmetic,
+    ProjectivePoint<C>: GroupEncoding
+{}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::sha2::Sha256;
+    use p256::{NistP256, ProjectivePoint, Scalar};
+    use rand::rngs::ThreadRng;
+
+    use crate::{bulletproof::BulletProof, incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::assert_curve_supported;
+
+    #[test]
+    fn test_p256_schnorr() {
+        assert_curve_supported::<NistP256>();
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<NistP256>::sign::<Sha256>(&sk, &message);
+        signature.verify::<Sha256>(&pk, &message).unwrap();
+        assert!(signature.verify::<Sha256>(&pk, &[0, 3, 6]).is_err());
+        assert_eq!(SchnorrSignature::<NistP256>::from_bytes(&signature.to_bytes().unwrap()).unwrap(), signature);
+    }
+
+    #[test]
+    fn test_p256_bulletproof() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+
+        let proof = BulletProof::<NistP256>::prove::<Sha256>(&g, &h, &l, &r, &target);
+        proof.verify::<Sha256>(&g, &h).unwrap();
+        assert_eq!(proof.target, target);
+    }
+
+    #[test]
+    fn test_p256_incognito() {
+        let n = 8;
+        let index = 6;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<NistP256, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<NistP256>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        assert!(params.verify::<Sha256>(&pks, &[0, 3, 6], &incsig).is_err());
+    }
+}
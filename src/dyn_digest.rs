@@ -0,0 +1,72 @@
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, ProjectivePoint};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Keccak256, Sha3_256};
+
+use crate::incognito::{IncognitoParams, IncognitoSignature};
+
+/// Digest chosen at runtime, e.g. from a header a network peer sends along with
+/// its signatures.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DigestKind {
+    Sha256,
+    Sha3_256,
+    Keccak256
+}
+
+impl <C: CurveArithmetic, const MAXN: usize> IncognitoParams<C, MAXN>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Same as `verify::<D>` with the digest named by `kind`.
+    pub fn verify_dyn(
+        &self,
+        kind: DigestKind,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()> {
+        match kind {
+            DigestKind::Sha256 => self.verify::<Sha256>(pks, message, signature),
+            DigestKind::Sha3_256 => self.verify::<Sha3_256>(pks, message, signature),
+            DigestKind::Keccak256 => self.verify::<Keccak256>(pks, message, signature)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+    use sha3::Keccak256;
+
+    use crate::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::DigestKind;
+
+    #[test]
+    fn test_verify_dyn() {
+        let n = 4;
+        let index = 3;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        params.verify_dyn(DigestKind::Sha256, &pks, &message, &incsig).unwrap();
+        assert!(params.verify_dyn(DigestKind::Sha256, &pks, &[0, 3, 6], &incsig).is_err());
+        assert!(params.verify_dyn(DigestKind::Sha3_256, &pks, &message, &incsig).is_err());
+
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Keccak256>(&sks[index], &message);
+        let incsig = params.convert::<Keccak256>(&pks, &message, &signature, index).unwrap();
+        params.verify_dyn(DigestKind::Keccak256, &pks, &message, &incsig).unwrap();
+        assert!(params.verify_dyn(DigestKind::Sha256, &pks, &message, &incsig).is_err());
+    }
+}
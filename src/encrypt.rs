@@ -0,0 +1,103 @@
+//! ECIES on the signing curve, for settlement messages that must stay hidden from
+//! everyone but the recipient while still being signed.
+//!
+//! The sender draws an ephemeral key `e`, derives a ChaCha20-Poly1305 key from
+//! `H(E, pk, e * pk)` and sends `E = e * G` followed by the sealed message. Sign the
+//! ciphertext as a whole: the signature then also covers `E`, so the recipient knows
+//! the payload they decrypt is the one that was signed.
+
+use chacha20poly1305::{aead::{Aead, KeyInit}, ChaCha20Poly1305, Key, Nonce};
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, Field, Group, ProjectivePoint, Scalar};
+
+/// Encrypts `message` so only the holder of the secret key of `recipient_pk` can read it.
+pub fn encrypt_message<C: CurveArithmetic, D>(recipient_pk: &ProjectivePoint<C>, message: &[u8]) -> anyhow::Result<Vec<u8>>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    anyhow::ensure!(!bool::from(recipient_pk.is_identity()), "Recipient key is the identity");
+    let ephemeral = Scalar::<C>::random(&mut rand::rngs::ThreadRng::default());
+    let point_e = ProjectivePoint::<C>::generator() * ephemeral;
+    let cipher = derive_cipher::<C, D>(&point_e, recipient_pk, &(*recipient_pk * ephemeral))?;
+    let sealed = cipher.encrypt(&Nonce::default(), message).map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+    Ok([point_e.to_bytes().as_ref(), &sealed].concat())
+}
+
+/// Inverse of `encrypt_message`; fails if the ciphertext was altered or is for another key.
+pub fn decrypt_message<C: CurveArithmetic, D>(recipient_sk: &Scalar<C>, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    let mut repr = <ProjectivePoint<C> as GroupEncoding>::Repr::default();
+    let point_len = repr.as_ref().len();
+    anyhow::ensure!(ciphertext.len() >= point_len, "Ciphertext is missing its ephemeral key");
+    let (point_bytes, sealed) = ciphertext.split_at(point_len);
+    repr.as_mut().copy_from_slice(point_bytes);
+    let point_e = Option::<ProjectivePoint<C>>::from(ProjectivePoint::<C>::from_bytes(&repr))
+        .ok_or_else(|| anyhow::anyhow!("Invalid ephemeral key"))?;
+    anyhow::ensure!(!bool::from(point_e.is_identity()), "Ephemeral key is the identity");
+    let recipient_pk = ProjectivePoint::<C>::generator() * recipient_sk;
+    let cipher = derive_cipher::<C, D>(&point_e, &recipient_pk, &(point_e * recipient_sk))?;
+    cipher.decrypt(&Nonce::default(), sealed).map_err(|_| anyhow::anyhow!("Decryption failed"))
+}
+
+/// Each key encrypts a single message, under a fresh ephemeral key, so the nonce is fixed.
+fn derive_cipher<C: CurveArithmetic, D>(
+    point_e: &ProjectivePoint<C>,
+    recipient_pk: &ProjectivePoint<C>,
+    shared: &ProjectivePoint<C>
+) -> anyhow::Result<ChaCha20Poly1305>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    anyhow::ensure!(<D as Digest>::output_size() >= 32, "Digest output shorter than 32 bytes");
+    let digest = D::new()
+        .chain_update(b"incognito-ecies")
+        .chain_update(point_e.to_bytes())
+        .chain_update(recipient_pk.to_bytes())
+        .chain_update(shared.to_bytes())
+        .finalize();
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&digest[..32])))
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::{decrypt_message, encrypt_message};
+
+    #[test]
+    fn test_encrypt_then_sign() {
+        let n = 4;
+        let index = 1;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let recipient_sk = Scalar::random(&mut rng);
+        let recipient_pk = ProjectivePoint::GENERATOR * recipient_sk;
+
+        let message = b"transfer 100 to account 7";
+        let ciphertext = encrypt_message::<Secp256k1, Sha256>(&recipient_pk, message).unwrap();
+        assert_ne!(encrypt_message::<Secp256k1, Sha256>(&recipient_pk, message).unwrap(), ciphertext);
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &ciphertext);
+        let incsig = params.convert::<Sha256>(&pks, &ciphertext, &signature, index).unwrap();
+
+        params.verify::<Sha256>(&pks, &ciphertext, &incsig).unwrap();
+        assert_eq!(decrypt_message::<Secp256k1, Sha256>(&recipient_sk, &ciphertext).unwrap(), message);
+        assert!(decrypt_message::<Secp256k1, Sha256>(&sks[0], &ciphertext).is_err());
+
+        let mut tampered = ciphertext.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt_message::<Secp256k1, Sha256>(&recipient_sk, &tampered).is_err());
+        assert!(params.verify::<Sha256>(&pks, &tampered, &incsig).is_err());
+        assert!(decrypt_message::<Secp256k1, Sha256>(&recipient_sk, &ciphertext[..20]).is_err());
+    }
+}
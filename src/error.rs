@@ -0,0 +1,112 @@
+use std::fmt;
+
+/// Why a signature was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The verification context holds the wrong number of keys for the signature,
+    /// e.g. a Schnorr signature checked against a ring.
+    RingSize { expected: usize, actual: usize },
+    /// The ring holds no keys, so there is no signer to hide among.
+    EmptyRing,
+    /// The digest is shorter than a scalar, so challenges would not cover the group order.
+    DigestSize { expected: usize, actual: usize },
+    /// A Fiat–Shamir challenge hashed to zero and cannot be inverted.
+    ZeroChallenge,
+    /// A cryptographic check failed; carries the verifier's message.
+    Invalid(String)
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::RingSize { expected, actual } => write!(f, "Expected {} public keys, got {}", expected, actual),
+            VerifyError::EmptyRing => write!(f, "Ring is empty"),
+            VerifyError::DigestSize { expected, actual } => write!(f, "Digest output of {} bytes is shorter than the {}-byte scalar field", actual, expected),
+            VerifyError::ZeroChallenge => write!(f, "Challenge is zero"),
+            VerifyError::Invalid(reason) => write!(f, "{}", reason)
+        }
+    }
+}
+
+/// Also gives `VerifyError` anyhow's blanket `From`, so `?` lifts it into an
+/// `anyhow::Result` and `downcast_ref::<VerifyError>` recovers the variant.
+impl std::error::Error for VerifyError {}
+
+impl VerifyError {
+    /// Recovers the variant when `error` wraps a `VerifyError`; any other error becomes
+    /// `Invalid` with its message.
+    pub(crate) fn from_anyhow(error: anyhow::Error) -> Self {
+        match error.downcast::<VerifyError>() {
+            Ok(error) => error,
+            Err(error) => VerifyError::Invalid(error.to_string())
+        }
+    }
+}
+
+/// Lets callers of the `anyhow`-based API `?` its errors into a `VerifyError`.
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for VerifyError {
+    fn from(error: anyhow::Error) -> Self {
+        VerifyError::from_anyhow(error)
+    }
+}
+
+/// Malformed signature caught by `IncognitoSignature::structural_check` before any
+/// group operation. Scalars need no check here: decoding already rejects values
+/// outside the field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructuralError {
+    /// Rings must hold a power of two keys.
+    RingSize(usize),
+    /// A point that must not be the identity is.
+    IdentityPoint(&'static str),
+    /// The bulletproof does not have `log2(ring_size)` rounds of `L` and `R` points.
+    BulletproofRounds { expected: usize, left: usize, right: usize }
+}
+
+impl fmt::Display for StructuralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructuralError::RingSize(n) => write!(f, "Ring size {} is not a power of two", n),
+            StructuralError::IdentityPoint(name) => write!(f, "Point {} is the identity", name),
+            StructuralError::BulletproofRounds { expected, left, right } => write!(f, "Expected {} bulletproof rounds, got {} L and {} R points", expected, left, right)
+        }
+    }
+}
+
+impl std::error::Error for StructuralError {}
+
+#[cfg(all(test, feature = "anyhow"))]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::{Sha224, Sha256}, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::VerifyError;
+
+    #[test]
+    fn test_into_anyhow() {
+        let n = 4;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[1], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 1).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        let error = params.verify::<Sha256>(&[], &message, &incsig).unwrap_err();
+        assert_eq!(error.to_string(), "Ring is empty");
+        assert_eq!(error.downcast_ref::<VerifyError>(), Some(&VerifyError::EmptyRing));
+        assert_eq!(VerifyError::from(error), VerifyError::EmptyRing);
+
+        let error = params.verify::<Sha224>(&pks, &message, &incsig).unwrap_err();
+        assert_eq!(VerifyError::from(error), VerifyError::DigestSize { expected: 32, actual: 28 });
+
+        let error = params.verify::<Sha256>(&pks, &[0, 3, 6], &incsig).unwrap_err();
+        assert!(matches!(VerifyError::from(error), VerifyError::Invalid(_)));
+    }
+}
@@ -0,0 +1,54 @@
+//! Building blocks for the `arbitrary::Arbitrary` impls used by the fuzz targets.
+//! Generated values are well-formed group elements but otherwise unrelated, so
+//! they exercise the verifiers' rejection paths.
+
+use arbitrary::{Result, Unstructured};
+use elliptic_curve::{ops::Reduce, CurveArithmetic, FieldBytes, Group, ProjectivePoint, Scalar};
+
+/// Longest point vector generated for a bulletproof, well above any ring used in fuzzing.
+const MAX_VEC_LEN: usize = 10;
+
+pub(crate) fn scalar<C: CurveArithmetic>(u: &mut Unstructured) -> Result<Scalar<C>> {
+    let mut bytes = FieldBytes::<C>::default();
+    u.fill_buffer(&mut bytes)?;
+    Ok(<Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&bytes))
+}
+
+pub(crate) fn point<C: CurveArithmetic>(u: &mut Unstructured) -> Result<ProjectivePoint<C>> {
+    Ok(ProjectivePoint::<C>::generator() * scalar::<C>(u)?)
+}
+
+pub(crate) fn points<C: CurveArithmetic>(u: &mut Unstructured) -> Result<Vec<ProjectivePoint<C>>> {
+    let len = u.int_in_range(0..=MAX_VEC_LEN)?;
+    (0..len).map(|_| point::<C>(u)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::{rngs::ThreadRng, RngCore};
+
+    use crate::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+
+    #[test]
+    fn test_arbitrary_rejected() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let pks: Vec<_> = (0..8).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let mut data = vec![0u8; 4096];
+        for _ in 0..16 {
+            rng.fill_bytes(&mut data);
+            let mut u = Unstructured::new(&data);
+            let signature = SchnorrSignature::<Secp256k1>::arbitrary(&mut u).unwrap();
+            assert!(signature.verify::<Sha256>(&pks[0], b"fuzz").is_err());
+            let incsig = IncognitoSignature::<Secp256k1>::arbitrary(&mut u).unwrap();
+            assert!(params.verify::<Sha256>(&pks, b"fuzz", &incsig).is_err());
+
+            let bytes = bincode::serialize(&incsig).unwrap();
+            assert_eq!(bincode::deserialize::<IncognitoSignature<Secp256k1>>(&bytes).unwrap(), incsig);
+            let _ = bincode::deserialize::<IncognitoSignature<Secp256k1>>(&data);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! BIP32-style hierarchical derivation of secret keys.
+//!
+//! Follows BIP32 private derivation with HMAC-SHA512: indices from `HARDENED` up
+//! hash the parent secret, lower ones the parent public key, so the latter can also
+//! be derived by a watcher holding only public data. On secp256k1 the keys match
+//! BIP32 exactly. The scalar field must be 256 bits wide.
+
+use elliptic_curve::{generic_array::typenum::Unsigned, group::GroupEncoding, CurveArithmetic, Field, FieldBytes, FieldBytesSize, Group, PrimeField, ProjectivePoint, Scalar};
+#[cfg(feature = "zeroize")]
+use elliptic_curve::zeroize::{Zeroize, ZeroizeOnDrop};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+/// First hardened child index.
+pub const HARDENED: u32 = 1 << 31;
+
+/// A secret key with its public key, as accepted by `SchnorrSignature::sign`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPair<C: CurveArithmetic> {
+    pub sk: Scalar<C>,
+    pub pk: ProjectivePoint<C>
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> Drop for KeyPair<C> {
+    fn drop(&mut self) {
+        self.sk.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> ZeroizeOnDrop for KeyPair<C> {}
+
+impl <C: CurveArithmetic> KeyPair<C> {
+    pub fn from_secret(sk: Scalar<C>) -> Self {
+        Self {
+            sk,
+            pk: ProjectivePoint::<C>::generator() * sk
+        }
+    }
+}
+
+/// A secret key together with the chain code its children are derived from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedKey<C: CurveArithmetic> {
+    pub key_pair: KeyPair<C>,
+    pub chain_code: [u8; 32]
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> Drop for ExtendedKey<C> {
+    fn drop(&mut self) {
+        self.chain_code.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> ZeroizeOnDrop for ExtendedKey<C> {}
+
+impl <C: CurveArithmetic> ExtendedKey<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Master key of the hierarchy for `seed`.
+    pub fn master(seed: &[u8]) -> anyhow::Result<Self> {
+        let (sk, chain_code) = split_hmac::<C>(b"Bitcoin seed", &[seed])?;
+        anyhow::ensure!(!bool::from(sk.is_zero()), "Master key is zero");
+        Ok(Self {
+            key_pair: KeyPair::from_secret(sk),
+            chain_code
+        })
+    }
+
+    /// Child at `index`; hardened if `index >= HARDENED`.
+    pub fn derive_child(&self, index: u32) -> anyhow::Result<Self> {
+        let (sk, chain_code) = derive_child::<C>(&self.key_pair.sk, &self.chain_code, index)?;
+        Ok(Self {
+            key_pair: KeyPair::from_secret(sk),
+            chain_code
+        })
+    }
+
+    /// Follows `path` from this key, e.g. `[HARDENED, 1]` for `m/0'/1`.
+    pub fn derive_path(&self, path: &[u32]) -> anyhow::Result<Self> {
+        path.iter().try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+}
+
+/// Secret key and chain code of the child at `index` of `parent_sk`.
+///
+/// Fails with negligible probability, when the derived scalar is out of range or
+/// zero; as in BIP32, callers should then move on to the next index.
+pub fn derive_child<C: CurveArithmetic>(
+    parent_sk: &Scalar<C>,
+    chain_code: &[u8; 32],
+    index: u32
+) -> anyhow::Result<(Scalar<C>, [u8; 32])>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    let index_bytes = index.to_be_bytes();
+    let (tweak, child_chain_code) = if index >= HARDENED {
+        split_hmac::<C>(chain_code, &[&[0], parent_sk.to_repr().as_ref(), &index_bytes])?
+    } else {
+        let parent_pk = ProjectivePoint::<C>::generator() * parent_sk;
+        split_hmac::<C>(chain_code, &[parent_pk.to_bytes().as_ref(), &index_bytes])?
+    };
+    let child = tweak + parent_sk;
+    anyhow::ensure!(!bool::from(child.is_zero()), "Child key {} is zero", index);
+    Ok((child, child_chain_code))
+}
+
+/// `HMAC-SHA512(key, parts)` split into a scalar and a chain code.
+fn split_hmac<C: CurveArithmetic>(key: &[u8], parts: &[&[u8]]) -> anyhow::Result<(Scalar<C>, [u8; 32])> {
+    anyhow::ensure!(FieldBytesSize::<C>::USIZE == 32, "HD derivation needs a 256-bit scalar field");
+    let mut mac = Hmac::<Sha512>::new_from_slice(key)?;
+    for part in parts {
+        mac.update(part);
+    }
+    let output = mac.finalize().into_bytes();
+    let (left, right) = output.split_at(32);
+    let scalar = Option::<Scalar<C>>::from(Scalar::<C>::from_repr(FieldBytes::<C>::clone_from_slice(left)))
+        .ok_or_else(|| anyhow::anyhow!("Derived scalar out of range"))?;
+    Ok((scalar, right.try_into()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::PrimeField;
+    use k256::{sha2::Sha256, ProjectivePoint, Secp256k1};
+
+    use crate::schnorr::SchnorrSignature;
+
+    use super::{ExtendedKey, HARDENED};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_bip32_vector() {
+        // BIP32 test vector 1.
+        let master = ExtendedKey::<Secp256k1>::master(&hex("000102030405060708090a0b0c0d0e0f")).unwrap();
+        assert_eq!(master.key_pair.sk.to_repr().to_vec(), hex("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"));
+        assert_eq!(master.chain_code.to_vec(), hex("873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508"));
+
+        let child = master.derive_child(HARDENED).unwrap();
+        assert_eq!(child.key_pair.sk.to_repr().to_vec(), hex("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"));
+        assert_eq!(child.chain_code.to_vec(), hex("47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141"));
+
+        let grandchild = master.derive_path(&[HARDENED, 1]).unwrap();
+        assert_eq!(grandchild.key_pair.sk.to_repr().to_vec(), hex("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"));
+    }
+
+    #[test]
+    fn test_derive_child() {
+        let master = ExtendedKey::<Secp256k1>::master(b"incognito hd test seed").unwrap();
+        for index in [0, 1, HARDENED, HARDENED + 1] {
+            assert_eq!(master.derive_child(index).unwrap(), master.derive_child(index).unwrap());
+        }
+        assert_ne!(master.derive_child(0).unwrap().key_pair, master.derive_child(1).unwrap().key_pair);
+        assert_ne!(master.derive_child(0).unwrap().key_pair, master.derive_child(HARDENED).unwrap().key_pair);
+
+        let child = master.derive_child(7).unwrap().key_pair.clone();
+        assert_eq!(child.pk, ProjectivePoint::GENERATOR * child.sk);
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&child.sk, &message);
+        signature.verify::<Sha256>(&child.pk, &message).unwrap();
+    }
+}
@@ -1,90 +1,635 @@
-use std::array;
-
-use digest::{Digest, FixedOutput};
-use elliptic_curve::{group::{Curve, GroupEncoding}, ops::Reduce, AffinePoint, CurveArithmetic, Field, FieldBytes, FieldBytesSize, Group, PrimeField, ProjectivePoint, Scalar};
+use std::{array, ops::RangeInclusive};
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+
+use digest::{Digest, Output};
+use elliptic_curve::{group::GroupEncoding, subtle::{Choice, ConstantTimeEq}, CurveArithmetic, Field, Group, PrimeField, ProjectivePoint, Scalar};
+#[cfg(feature = "serde")]
+use elliptic_curve::{group::Curve, AffinePoint, ScalarPrimitive};
+#[cfg(feature = "zeroize")]
+use elliptic_curve::zeroize::Zeroize;
+#[cfg(feature = "transcript-log")]
+use crate::transcript::TranscriptLog;
+use anyhow::Context;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+#[cfg(feature = "serde")]
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::{bulletproof::BulletProof, schnorr::SchnorrSignature};
+use crate::{backend::{CurveBackend, GenericBackend}, bulletproof::{required_rounds, BulletProof}, commitment::{Commitment, Pedersen}, challenge::{check_digest_size, invert_challenge, reduce_digest}, error::StructuralError, limiter::ConversionLimiter, range::RangeProof, ring::{ensure_distinct, ensure_nonempty, ring_from_table, ring_hash, KeySource}, view::ViewKey, schnorr::{encode_messages, SchnorrSignature}};
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned", from = "IncognitoParamsSerde<C, MAXN>", into = "IncognitoParamsSerde<C, MAXN>")]
+/// Generators for rings of up to `MAXN` keys.
+///
+/// The bulletproof halves the ring in every round, so rings must have a power-of-two
+/// size; `MAXN` itself need not be one. Any power of two up to `MAXN` works, and the
+/// largest is `MAX_RING_SIZE`; the generators past it are only used by range proofs.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
 pub struct IncognitoParams<C: CurveArithmetic, const MAXN: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     g: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     h: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::array_point_serde"))]
     vec_g: [ProjectivePoint<C>; MAXN],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::array_point_serde"))]
     vec_h: [ProjectivePoint<C>; MAXN]
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned")]
-struct IncognitoParamsSerde<C: CurveArithmetic, const MAXN: usize> {
-    g: AffinePoint<C>,
-    h: AffinePoint<C>,
-    vec_g: Vec<AffinePoint<C>>,
-    vec_h: Vec<AffinePoint<C>>
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned", from = "IncognitoSignatureSerde<C>", into = "IncognitoSignatureSerde<C>")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
 pub struct IncognitoSignature<C: CurveArithmetic> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_r: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_c_pk: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_r_z: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     s_z: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     s_beta: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_a: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_s: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_s_pk: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_t1: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     point_t2: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     taux: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     mu: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     nu: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     tx: Scalar<C>,
     bulletproof: BulletProof<C>
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned")]
-struct IncognitoSignatureSerde<C: CurveArithmetic> {
-    point_r: AffinePoint<C>,
-    point_c_pk: AffinePoint<C>,
-    point_r_z: AffinePoint<C>,
+/// Everything in an `IncognitoSignature` except its bulletproof, so the two halves can be
+/// stored or sent separately and put back together with `IncognitoSignature::from_parts`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct LinkagePart<C: CurveArithmetic> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_r: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_c_pk: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_r_z: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     s_z: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     s_beta: Scalar<C>,
-    point_a: AffinePoint<C>,
-    point_s: AffinePoint<C>,
-    point_s_pk: AffinePoint<C>,
-    point_t1: AffinePoint<C>,
-    point_t2: AffinePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_a: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_s: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_s_pk: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_t1: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_t2: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     taux: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     mu: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     nu: Scalar<C>,
-    tx: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    tx: Scalar<C>
+}
+
+/// `IncognitoSignature` without the bulletproof's target, one point shorter on the wire.
+/// The target is fixed by the other commitments and the challenges, so the verifier
+/// recomputes it; see `IncognitoParams::verify_compact` and `IncognitoParams::expand`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct CompactSignature<C: CurveArithmetic> {
+    linkage: LinkagePart<C>,
+    /// Its `target` is left at the identity.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::untargeted_bulletproof_serde"))]
     bulletproof: BulletProof<C>
 }
 
+/// Opening of an incognito signature's key commitment, by which its signer shows that
+/// some other key did not sign. It reveals the signer's key to whoever checks it; see
+/// `IncognitoSignature::prove_not_signer`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned"))]
+pub struct Disavowal<C: CurveArithmetic> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    beta: Scalar<C>
+}
+
+/// Pedersen commitment `V = g^amount h^blind` to an amount carried with an incognito
+/// signature, together with a proof that the amount fits in the agreed number of bits.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct AmountCommitment<C: CurveArithmetic> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    pub point_v: ProjectivePoint<C>,
+    pub range_proof: RangeProof<C>
+}
+
+/// Incognito signature together with a deterministic commitment to its message, see
+/// `IncognitoParams::message_commitment`. The signature is made over the commitment
+/// rather than the message, so `verify_committed` checks it without the plaintext.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct CommittedSignature<C: CurveArithmetic> {
+    pub signature: IncognitoSignature<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    pub message_commitment: ProjectivePoint<C>
+}
+
+impl <C: CurveArithmetic> CommittedSignature<C> {
+    /// Whether both signatures commit to the same message. Only meaningful once
+    /// each has been accepted by `verify_committed`, which binds the commitment.
+    pub fn same_message(&self, other: &Self) -> bool {
+        self.message_commitment == other.message_commitment
+    }
+}
+
+/// Returned by `IncognitoParams::verify_with_ring`: digests of the ring and message a
+/// signature was accepted against, for deduplication and audit logs downstream.
+#[derive(Debug, Clone)]
+pub struct VerifiedSignature<D: Digest> {
+    ring_hash: Output<D>,
+    message_hash: Output<D>
+}
+
+impl <D: Digest> PartialEq for VerifiedSignature<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ring_hash == other.ring_hash && self.message_hash == other.message_hash
+    }
+}
+
+impl <D: Digest> Eq for VerifiedSignature<D> {}
+
+impl <D: Digest> VerifiedSignature<D> {
+    /// Digest of the ring's point encodings, in order.
+    pub fn ring_hash(&self) -> &Output<D> {
+        &self.ring_hash
+    }
+
+    pub fn message_hash(&self) -> &Output<D> {
+        &self.message_hash
+    }
+}
+
+/// One entry of `IncognitoParams::verify_batch`: ring, message and signature.
+pub type BatchItem<'a, C> = (&'a [ProjectivePoint<C>], &'a [u8], &'a IncognitoSignature<C>);
+
+/// Reusable buffers for `IncognitoParams::verify_with_scratch`. Each call grows
+/// them to the ring size if needed and leaves them allocated for the next one.
+#[derive(Debug, Clone, Default)]
+pub struct VerifyScratch<C: CurveArithmetic> {
+    vec_yn_inv: Vec<Scalar<C>>,
+    points: Vec<ProjectivePoint<C>>,
+    scalars: Vec<Scalar<C>>,
+    base_g: Vec<ProjectivePoint<C>>,
+    base_h: Vec<ProjectivePoint<C>>
+}
+
+impl <C: CurveArithmetic> VerifyScratch<C> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Preallocates for rings of up to `ring_size` keys.
+    pub fn with_capacity(ring_size: usize) -> Self {
+        Self {
+            vec_yn_inv: Vec::with_capacity(ring_size),
+            points: Vec::with_capacity(3 * ring_size),
+            scalars: Vec::with_capacity(3 * ring_size),
+            base_g: Vec::with_capacity(ring_size),
+            base_h: Vec::with_capacity(ring_size)
+        }
+    }
+
+    fn clear(&mut self) {
+        self.vec_yn_inv.clear();
+        self.points.clear();
+        self.scalars.clear();
+        self.base_g.clear();
+        self.base_h.clear();
+    }
+}
+
+/// A ring checked and hashed once by `IncognitoParams::prepare_ring`, for verifying
+/// many signatures over it with `verify_prepared`.
+///
+/// Caches what does not depend on the signature: the emptiness, size and duplicate key
+/// checks, the ring digest the `y` and `w` challenges bind, and the sums of `vec_g`, of
+/// the keys and of `vec_h` over the ring. The target then needs one multiplication per
+/// key instead of three. The bulletproof bases mix each key with the challenges `y` and
+/// `d`, so they are still built per signature. The sums are taken over the generators
+/// of the params that prepared the ring, so verify with those params.
+#[derive(Debug, Clone)]
+pub struct PreparedRing<C: CurveArithmetic, D: Digest> {
+    pks: Vec<ProjectivePoint<C>>,
+    ring_hash: Output<D>,
+    sums: RingSums<C>
+}
+
+/// `sum(vec_g[i])`, `sum(pks[i])` and `sum(vec_h[i])` over a ring: the parts of the
+/// bulletproof target that the challenges only scale.
+type RingSums<C> = [ProjectivePoint<C>; 3];
+
+impl <C: CurveArithmetic, D: Digest> PreparedRing<C, D> {
+    pub fn pks(&self) -> &[ProjectivePoint<C>] {
+        &self.pks
+    }
+
+    pub fn ring_hash(&self) -> &Output<D> {
+        &self.ring_hash
+    }
+}
+
+/// Fiat–Shamir challenges checked by `verify`: `c` of the Schnorr signature, `c_z`
+/// of the linkage proof, `y`, `w`, `x` of the polynomial and `d` of the target.
+#[derive(Debug, Clone, Copy)]
+pub struct Challenges<C: CurveArithmetic> {
+    pub c: Scalar<C>,
+    pub c_z: Scalar<C>,
+    pub y: Scalar<C>,
+    pub w: Scalar<C>,
+    pub x: Scalar<C>,
+    pub d: Scalar<C>
+}
+
+/// Points and scalars of a multi-scalar multiplication.
+type Terms<C> = (Vec<ProjectivePoint<C>>, Vec<Scalar<C>>);
+
+/// Receives every segment a challenge derivation feeds to the digest, tagged with the
+/// challenge's name; `TranscriptLog` is filled through it.
+type Tap<'a> = &'a mut dyn FnMut(&'static str, &[u8]);
+
+/// Secrets `convert_indices` draws or derives from the signer's position. Held in one
+/// place so that, with the `zeroize` feature, they are wiped on every return path,
+/// the early errors included.
+struct ProverSecrets<C: CurveArithmetic> {
+    beta: Scalar<C>,
+    r_z: Scalar<C>,
+    r_beta: Scalar<C>,
+    alpha: Scalar<C>,
+    rho: Scalar<C>,
+    zeta: Scalar<C>,
+    tau1: Scalar<C>,
+    tau2: Scalar<C>,
+    vec_b: Vec<Scalar<C>>,
+    vec_a: Vec<Scalar<C>>,
+    vec_s_a: Vec<Scalar<C>>,
+    vec_s_b: Vec<Scalar<C>>,
+    vec_l: Vec<Scalar<C>>,
+    vec_r: Vec<Scalar<C>>
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> Zeroize for ProverSecrets<C> {
+    fn zeroize(&mut self) {
+        for secret in [&mut self.beta, &mut self.r_z, &mut self.r_beta, &mut self.alpha, &mut self.rho, &mut self.zeta, &mut self.tau1, &mut self.tau2] {
+            secret.zeroize();
+        }
+        for secret in [&mut self.vec_b, &mut self.vec_a, &mut self.vec_s_a, &mut self.vec_s_b, &mut self.vec_l, &mut self.vec_r] {
+            secret.zeroize();
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> Drop for ProverSecrets<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+fn no_tap(_: &'static str, _: &[u8]) {}
+
+/// Result of `IncognitoParams::verify_prepare`: the two sides of the combined
+/// verification equation, which hold equal exactly when the signature is valid.
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyWork<C: CurveArithmetic> {
+    /// Terms over the ring and the signature's points.
+    pub left: ProjectivePoint<C>,
+    /// Terms over the fixed generators of `IncognitoParams`.
+    pub right: ProjectivePoint<C>
+}
+
+impl <C: CurveArithmetic> VerifyWork<C> {
+    pub fn finish(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(self.left == self.right, "Invalid incognito signature");
+        Ok(())
+    }
+}
+
+/// One claim `sum(scalars[i] * points[i]) == expected` returned by
+/// `IncognitoParams::verify_lazy`, left for an external engine to check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupEquation<C: CurveArithmetic> {
+    pub points: Vec<ProjectivePoint<C>>,
+    pub scalars: Vec<Scalar<C>>,
+    pub expected: ProjectivePoint<C>
+}
+
+impl <C: CurveArithmetic> GroupEquation<C> {
+    /// Checks the equation on its own, with one multi-scalar multiplication.
+    pub fn holds(&self) -> bool {
+        <GenericBackend as CurveBackend<C>>::msm(&self.points, &self.scalars) == self.expected
+    }
+}
+
+/// Counts signatures by outcome and records their ring sizes. A rejected batch counts
+/// every signature in it as rejected, since the batch does not tell which one failed.
+#[cfg(feature = "metrics")]
+fn record_verification<I: IntoIterator<Item = usize>>(ring_sizes: I, accepted: bool) {
+    let mut count = 0;
+    for size in ring_sizes {
+        metrics::histogram!("signature_ring_size").record(size as f64);
+        count += 1;
+    }
+    if accepted {
+        metrics::counter!("signatures_verified_total").increment(count);
+    } else {
+        metrics::counter!("signatures_rejected_total").increment(count);
+    }
+}
+
+/// Inverts every element with a single field inversion (Montgomery's trick).
+/// Returns `None` if any element is zero.
+fn batch_invert<F: Field>(values: &[F]) -> Option<Vec<F>> {
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = F::ONE;
+    for value in values {
+        prefix.push(acc);
+        acc *= value;
+    }
+    let mut acc_inv = Option::<F>::from(acc.invert())?;
+    let mut inverses = vec![F::ZERO; values.len()];
+    for i in (0..values.len()).rev() {
+        inverses[i] = acc_inv * prefix[i];
+        acc_inv *= values[i];
+    }
+    Some(inverses)
+}
+
+/// Group operations performed by `IncognitoParams::verify` with `GenericBackend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyCost {
+    pub point_mults: usize,
+    pub point_adds: usize
+}
+
+impl <C: CurveArithmetic, const MAXN: usize> Default for IncognitoParams<C, MAXN>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl <C: CurveArithmetic, const MAXN: usize> IncognitoParams<C, MAXN> 
 where
-    ProjectivePoint<C>: GroupEncoding,
-    Scalar<C>: Serialize + DeserializeOwned,
-    AffinePoint<C>: Serialize + DeserializeOwned
+    ProjectivePoint<C>: GroupEncoding
 {
+    /// Largest ring these params can sign for: the largest power of two up to `MAXN`.
+    pub const MAX_RING_SIZE: usize = if MAXN == 0 { 0 } else { 1 << MAXN.ilog2() };
+
     pub fn new() -> Self {
-        let mut rng = rand::rngs::ThreadRng::default();
+        Self::from_rng(&mut rand::rngs::ThreadRng::default())
+    }
+
+    /// Same as `new` with the generators drawn from `rng`, so a seeded RNG gives the same
+    /// params on every run. Curves may draw a random point as a random multiple of the
+    /// generator, so whoever knows the seed may know the discrete logs between the
+    /// generators: keep seeded RNGs to tests and use `from_seed` for shared params.
+    pub fn from_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
         Self {
-            g: ProjectivePoint::<C>::random(&mut rng),
-            h: ProjectivePoint::<C>::random(&mut rng),
+            g: ProjectivePoint::<C>::random(&mut *rng),
+            h: ProjectivePoint::<C>::random(&mut *rng),
             vec_g: array::from_fn(|_| {
-                ProjectivePoint::<C>::random(&mut rng)
+                ProjectivePoint::<C>::random(&mut *rng)
             }),
             vec_h: array::from_fn(|_| {
-                ProjectivePoint::<C>::random(&mut rng)
+                ProjectivePoint::<C>::random(&mut *rng)
             })
         }
     }
 
+    /// Derives every generator from `seed`, so that anyone can recompute the params and
+    /// none of their discrete logarithms are known. Each generator depends only on its
+    /// own position, which lets `extend_to` grow the params later.
+    pub fn from_seed<D>(seed: &[u8]) -> Self
+    where
+        D: Digest
+    {
+        Self {
+            g: Self::hash_to_point::<D>(seed, b"g", 0),
+            h: Self::hash_to_point::<D>(seed, b"h", 0),
+            vec_g: array::from_fn(|i| Self::hash_to_point::<D>(seed, b"vec_g", i)),
+            vec_h: array::from_fn(|i| Self::hash_to_point::<D>(seed, b"vec_h", i))
+        }
+    }
+
+    /// Grows params made by `from_seed` to `NEWN` generators. The first `MAXN` are kept,
+    /// so signatures over the old params still verify; the rest are derived from `seed`.
+    pub fn extend_to<D, const NEWN: usize>(&self, seed: &[u8]) -> anyhow::Result<IncognitoParams<C, NEWN>>
+    where
+        D: Digest
+    {
+        anyhow::ensure!(NEWN >= MAXN, "Cannot shrink params from {} to {}", MAXN, NEWN);
+        anyhow::ensure!(
+            self.g == Self::hash_to_point::<D>(seed, b"g", 0) && self.h == Self::hash_to_point::<D>(seed, b"h", 0),
+            "Params were not derived from this seed"
+        );
+        Ok(IncognitoParams {
+            g: self.g,
+            h: self.h,
+            vec_g: array::from_fn(|i| self.vec_g.get(i).copied().unwrap_or_else(|| Self::hash_to_point::<D>(seed, b"vec_g", i))),
+            vec_h: array::from_fn(|i| self.vec_h.get(i).copied().unwrap_or_else(|| Self::hash_to_point::<D>(seed, b"vec_h", i)))
+        })
+    }
+
+    /// Keeps the first `NEWN` generators, e.g. for a verifier that only accepts smaller
+    /// rings than the signer's params allow.
+    pub fn truncate<const NEWN: usize>(&self) -> anyhow::Result<IncognitoParams<C, NEWN>> {
+        anyhow::ensure!(NEWN <= MAXN, "Cannot truncate params from {} to {}", MAXN, NEWN);
+        Ok(IncognitoParams {
+            g: self.g,
+            h: self.h,
+            vec_g: array::from_fn(|i| self.vec_g[i]),
+            vec_h: array::from_fn(|i| self.vec_h[i])
+        })
+    }
+
+    /// Whether signatures over rings of up to `ring_size` keys made with `other` verify
+    /// with `self`, and the other way round: `g`, `h` and the first `ring_size`
+    /// generators must agree, whatever the two `MAXN`.
+    pub fn compatible_with<const OTHERN: usize>(&self, other: &IncognitoParams<C, OTHERN>, ring_size: usize) -> bool {
+        ring_size <= MAXN.min(OTHERN)
+            && self.g == other.g
+            && self.h == other.h
+            && self.vec_g[..ring_size] == other.vec_g[..ring_size]
+            && self.vec_h[..ring_size] == other.vec_h[..ring_size]
+    }
+
+    /// Derives the generators from a caller-chosen `domain_tag` alone: `vec_g[i]` and
+    /// `vec_h[i]` hash the length-prefixed tag, the label `"G"` (resp. `"H"`) and the
+    /// index `i` to a point; `g` and `h` use the labels `"g"` and `"h"`.
+    ///
+    /// The hashing is this crate's try-and-increment, not RFC 9380 hash-to-curve, so the
+    /// generators do not match other bulletproof libraries even for the same tag.
+    pub fn standard_generators<D>(domain_tag: &[u8]) -> Self
+    where
+        D: Digest
+    {
+        let point = |label: &[u8], index: usize| Self::try_and_increment::<D>(|digest| {
+            digest
+                .chain_update((domain_tag.len() as u64).to_le_bytes())
+                .chain_update(domain_tag)
+                .chain_update(label)
+                .chain_update((index as u64).to_le_bytes())
+        });
+        Self {
+            g: point(b"g", 0),
+            h: point(b"h", 0),
+            vec_g: array::from_fn(|i| point(b"G", i)),
+            vec_h: array::from_fn(|i| point(b"H", i))
+        }
+    }
+
+    /// Try-and-increment: hashes `(seed, label, index, counter)` into a point encoding
+    /// until it decodes to a non-identity point.
+    fn hash_to_point<D>(seed: &[u8], label: &[u8], index: usize) -> ProjectivePoint<C>
+    where
+        D: Digest
+    {
+        Self::try_and_increment::<D>(|digest| {
+            digest
+                .chain_update(b"incognito-params")
+                .chain_update((seed.len() as u64).to_le_bytes())
+                .chain_update(seed)
+                .chain_update(label)
+                .chain_update((index as u64).to_le_bytes())
+        })
+    }
+
+    /// Fills a point encoding with `input || counter || block` digests, bumping the
+    /// counter until the encoding decodes to a non-identity point.
+    fn try_and_increment<D>(input: impl Fn(D) -> D) -> ProjectivePoint<C>
+    where
+        D: Digest
+    {
+        let mut repr = <ProjectivePoint<C> as GroupEncoding>::Repr::default();
+        for counter in 0u64.. {
+            for (block, chunk) in repr.as_mut().chunks_mut(<D as Digest>::output_size()).enumerate() {
+                let digest = input(D::new())
+                    .chain_update(counter.to_le_bytes())
+                    .chain_update((block as u64).to_le_bytes())
+                    .finalize();
+                chunk.copy_from_slice(&digest[..chunk.len()]);
+            }
+            if let Some(point) = Option::<ProjectivePoint<C>>::from(ProjectivePoint::<C>::from_bytes(&repr)) {
+                if !bool::from(point.is_identity()) {
+                    return point;
+                }
+            }
+        }
+        unreachable!("counter space exhausted")
+    }
+
+    /// Startup sanity check for release builds. Rejects generators that are the identity
+    /// or repeated, then converts and verifies a signature over a small ring. Conversion
+    /// always enforces `tx = t(x)`, and verification re-checks every bulletproof fold,
+    /// which covers the invariants that are otherwise only asserted in debug builds.
+    pub fn self_test<D>(&self) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let generators: Vec<_> = [self.g, self.h].into_iter().chain(self.vec_g.iter().copied()).chain(self.vec_h.iter().copied()).collect();
+        anyhow::ensure!(
+            generators.iter().all(|point| !bool::from(point.is_identity())),
+            "Corrupted generators: identity point"
+        );
+        ensure_distinct::<C>(&generators).context("Corrupted generators")?;
+
+        let n = if MAXN >= 8 { 8 } else { 1 << MAXN.max(1).ilog2() };
+        let index = n / 2;
+        let mut rng = rand::rngs::ThreadRng::default();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::<C>::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|sk| ProjectivePoint::<C>::generator() * sk).collect();
+        let message = b"incognito self-test";
+        let signature = SchnorrSignature::<C>::sign::<D>(&sks[index], message);
+        let incsig = self.convert::<D>(&pks, message, &signature, index)?;
+        self.verify::<D>(&pks, message, &incsig)?;
+        anyhow::ensure!(self.verify::<D>(&pks, b"incognito self-test!", &incsig).is_err(), "Signature verified for the wrong message");
+        Ok(())
+    }
+
+    /// Digest of `MAXN` and every generator in a fixed order. Parties can compare
+    /// fingerprints up front, since verifying against mismatched params only fails
+    /// later, with an opaque equation failure.
+    pub fn fingerprint<D>(&self) -> Output<D>
+    where
+        D: Digest
+    {
+        let mut digest = D::new()
+            .chain_update(b"incognito-params-fingerprint")
+            .chain_update((MAXN as u64).to_le_bytes());
+        for point in [&self.g, &self.h].into_iter().chain(self.vec_g.iter()).chain(self.vec_h.iter()) {
+            digest.update(point.to_bytes());
+        }
+        digest.finalize()
+    }
+
+    /// Scheme committing to the signer's key in `point_c_pk`, blinded by `beta` over `g`.
+    /// Fixed to Pedersen, which the linkage equation is written for.
+    pub fn key_commitment(&self) -> Pedersen<C> {
+        Pedersen::new(self.g)
+    }
+
+    /// Scheme committing to `value_base() * amount` in amount commitments, blinded over `h`.
+    /// Fixed to Pedersen, which the range proof is written for.
+    pub fn amount_commitment(&self) -> Pedersen<C> {
+        Pedersen::new(self.h)
+    }
+
+    /// Base the committed amounts are multiplied with.
+    pub fn value_base(&self) -> ProjectivePoint<C> {
+        self.g
+    }
+
     #[inline]
+    /// Constant term of `t(X) = <l(X), r(X)>` for a ring of `n` keys with `signers`
+    /// of them signing: `w^2 signers - w^3 n + (w - w^2) sum(y^i)`.
+    fn compute_t0(n: usize, signers: usize, w: &Scalar<C>, sum_yn: &Scalar<C>) -> Scalar<C> {
+        let w2 = *w * w;
+        w2 * Scalar::<C>::from(signers as u64) - w2 * w * Scalar::<C>::from(n as u64) + (*w - w2) * sum_yn
+    }
+
+    /// Debug-build check on the bases `g_i pk_i^d` the bulletproof folds over. Repeated
+    /// keys are rejected before any base is built, since a key listed twice would let one
+    /// signer count as two towards a threshold. Because the `g_i` are independent, even a
+    /// repeated key gives distinct bases, and distinct keys only collide if `d` solves
+    /// `g_i - g_j = (pk_j - pk_i) d`, which needs a discrete log between the generators.
+    fn debug_assert_distinct_bases(bases: &[ProjectivePoint<C>]) {
+        debug_assert!(ensure_distinct::<C>(bases).is_ok(), "Repeated bulletproof base");
+    }
+
     fn build_vec_yn(n: usize, y: &Scalar<C>) -> Vec<Scalar<C>> {
         let mut vec_yn = Vec::new();
         let mut cur = Scalar::<C>::ONE;
@@ -95,17 +640,80 @@ where
         vec_yn
     }
 
+    /// Hashes the segments `transcript` feeds, in order, to a challenge.
+    ///
+    /// A field-width digest is reduced once, which skews the challenge by at most
+    /// `(2^256 - n) / 2^256` for a 256-bit order `n`: about `2^-128` on secp256k1 and
+    /// `2^-32` on P-256. Neither shrinks the challenge space enough to matter for
+    /// soundness, so the narrow reduction stays for transcript compatibility. A digest
+    /// twice the field width, such as SHA-512, is reduced wide by `reduce_digest`, which
+    /// makes the bias negligible (about `2^-256`) rather than zero.
+    #[inline]
+    fn hash_transcript<D>(transcript: impl FnOnce(&mut dyn FnMut(&[u8]))) -> Scalar<C>
+    where
+        D: Digest
+    {
+        let mut digest = D::new();
+        transcript(&mut |bytes| Digest::update(&mut digest, bytes));
+        reduce_digest::<C>(&digest.finalize())
+    }
+
+    /// `hash_transcript` for the challenge `name`, also passing each segment to `tap`.
+    fn hash_tapped<D>(name: &'static str, tap: Tap, transcript: impl FnOnce(&mut dyn FnMut(&[u8]))) -> Scalar<C>
+    where
+        D: Digest
+    {
+        Self::hash_transcript::<D>(|absorb| transcript(&mut |bytes| {
+            tap(name, bytes);
+            absorb(bytes);
+        }))
+    }
+
+    fn transcript_cz(commitment_pk_mask: &ProjectivePoint<C>, commitment_pk: &ProjectivePoint<C>, absorb: &mut dyn FnMut(&[u8])) {
+        absorb(commitment_pk_mask.to_bytes().as_ref());
+        absorb(commitment_pk.to_bytes().as_ref());
+    }
+
+    /// `g`, `A`, `S`, `S_pk`, `C_pk`, the ring digest, then a single byte separating `y`
+    /// (0) from `w` (1). Binding the ring makes a signature made over one ring fail
+    /// against any other, even one of the same size.
+    #[allow(clippy::too_many_arguments)]
+    fn transcript_yw(
+        point_g: &ProjectivePoint<C>,
+        point_a: &ProjectivePoint<C>,
+        point_s: &ProjectivePoint<C>,
+        point_s_pk: &ProjectivePoint<C>,
+        point_c_pk: &ProjectivePoint<C>,
+        ring: &[u8],
+        separator: u8,
+        absorb: &mut dyn FnMut(&[u8])
+    ) {
+        for point in [point_g, point_a, point_s, point_s_pk, point_c_pk] {
+            absorb(point.to_bytes().as_ref());
+        }
+        absorb(ring);
+        absorb(&[separator]);
+    }
+
+    fn transcript_x(point_t1: &ProjectivePoint<C>, point_t2: &ProjectivePoint<C>, y: &Scalar<C>, w: &Scalar<C>, absorb: &mut dyn FnMut(&[u8])) {
+        absorb(point_t1.to_bytes().as_ref());
+        absorb(point_t2.to_bytes().as_ref());
+        absorb(&y.to_repr());
+        absorb(&w.to_repr());
+    }
+
+    fn transcript_d(x: &Scalar<C>, taux: &Scalar<C>, mu: &Scalar<C>, nu: &Scalar<C>, tx: &Scalar<C>, absorb: &mut dyn FnMut(&[u8])) {
+        for scalar in [x, taux, mu, nu, tx] {
+            absorb(&scalar.to_repr());
+        }
+    }
+
     #[inline]
-    fn challenge_cz<D>(commitment_pk_mask: &ProjectivePoint<C>, commitment_pk: &ProjectivePoint<C>) -> Scalar<C>
+    fn challenge_cz<D>(commitment_pk_mask: &ProjectivePoint<C>, commitment_pk: &ProjectivePoint<C>, tap: Tap) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let digest = D::new()
-            .chain_update(commitment_pk_mask.to_bytes())
-            .chain_update(commitment_pk.to_bytes())
-            .finalize_fixed();
-        let fieldbytes = digest as FieldBytes<C>;
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&fieldbytes)
+        Self::hash_tapped::<D>("c_z", tap, |absorb| Self::transcript_cz(commitment_pk_mask, commitment_pk, absorb))
     }
 
     #[inline]
@@ -114,21 +722,14 @@ where
         point_a: &ProjectivePoint<C>,
         point_s: &ProjectivePoint<C>,
         point_s_pk: &ProjectivePoint<C>,
-        point_c_pk: &ProjectivePoint<C>
+        point_c_pk: &ProjectivePoint<C>,
+        ring: &[u8],
+        tap: Tap
     ) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let digest = D::new()
-            .chain_update(point_g.to_bytes())
-            .chain_update(point_a.to_bytes())
-            .chain_update(point_s.to_bytes())
-            .chain_update(point_s_pk.to_bytes())
-            .chain_update(point_c_pk.to_bytes())
-            .chain_update(&[0])
-            .finalize_fixed();
-        let fieldbytes = digest as FieldBytes<C>;
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&fieldbytes)
+        Self::hash_tapped::<D>("y", tap, |absorb| Self::transcript_yw(point_g, point_a, point_s, point_s_pk, point_c_pk, ring, 0, absorb))
     }
 
     #[inline]
@@ -137,21 +738,14 @@ where
         point_a: &ProjectivePoint<C>,
         point_s: &ProjectivePoint<C>,
         point_s_pk: &ProjectivePoint<C>,
-        point_c_pk: &ProjectivePoint<C>
+        point_c_pk: &ProjectivePoint<C>,
+        ring: &[u8],
+        tap: Tap
     ) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let digest = D::new()
-            .chain_update(point_g.to_bytes())
-            .chain_update(point_a.to_bytes())
-            .chain_update(point_s.to_bytes())
-            .chain_update(point_s_pk.to_bytes())
-            .chain_update(point_c_pk.to_bytes())
-            .chain_update(&[1])
-            .finalize_fixed();
-        let fieldbytes = digest as FieldBytes<C>;
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&fieldbytes)
+        Self::hash_tapped::<D>("w", tap, |absorb| Self::transcript_yw(point_g, point_a, point_s, point_s_pk, point_c_pk, ring, 1, absorb))
     }
 
     #[inline]
@@ -160,18 +754,12 @@ where
         point_t2: &ProjectivePoint<C>,
         y: &Scalar<C>,
         w: &Scalar<C>,
+        tap: Tap
     ) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let digest = D::new()
-            .chain_update(point_t1.to_bytes())
-            .chain_update(point_t2.to_bytes())
-            .chain_update(y.to_repr())
-            .chain_update(w.to_repr())
-            .finalize_fixed();
-        let fieldbytes = digest as FieldBytes<C>;
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&fieldbytes)
+        Self::hash_tapped::<D>("x", tap, |absorb| Self::transcript_x(point_t1, point_t2, y, w, absorb))
     }
 
     #[inline]
@@ -181,21 +769,20 @@ where
         mu: &Scalar<C>,
         nu: &Scalar<C>,
         tx: &Scalar<C>,
+        tap: Tap
     ) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let digest = D::new()
-            .chain_update(x.to_repr())
-            .chain_update(taux.to_repr())
-            .chain_update(mu.to_repr())
-            .chain_update(nu.to_repr())
-            .chain_update(tx.to_repr())
-            .finalize_fixed();
-        let fieldbytes = digest as FieldBytes<C>;
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(&fieldbytes)
+        Self::hash_tapped::<D>("d", tap, |absorb| Self::transcript_d(x, taux, mu, nu, tx, absorb))
     }
 
+    /// Converts a Schnorr signature by `pks[index]` into an incognito signature over the ring `pks`.
+    ///
+    /// Rings holding the same key twice are rejected here and by `verify`.
+    ///
+    /// A ring of a single key is accepted: the bulletproof then has no folding rounds and
+    /// the signature verifies, but it obviously does not hide the signer.
     pub fn convert<D>(
         &self,
         pks: &[ProjectivePoint<C>],
@@ -204,101 +791,389 @@ where
         index: usize
     ) -> anyhow::Result<IncognitoSignature<C>>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        anyhow::ensure!(pks.len() <= MAXN);
-        anyhow::ensure!(index < pks.len());
-
-        let mut rng = rand::rngs::ThreadRng::default();
-        let beta = Scalar::<C>::random(&mut rng);
-        let point_c_pk = self.g * &beta + pks[index];
-
-        let r_z = Scalar::<C>::random(&mut rng);
-        let r_beta = Scalar::<C>::random(&mut rng);
-        let c = SchnorrSignature::<C>::challenge::<D>(&signature.point_r, message);
+        self.convert_with_opening::<D>(pks, message, signature, index).map(|(incsig, _)| incsig)
+    }
 
-        let point_r_z = ProjectivePoint::<C>::generator() * r_z + self.g * r_beta * c;
-        let c_z = Self::challenge_cz::<D>(&point_r_z, &point_c_pk);
+    /// Same as `convert` for the ring `ids` names in a shared `key_table`, as in the
+    /// settlement protocol. `ring_index` is the signer's position within `ids`. The table
+    /// may be a slice, a `Vec` or any other `KeySource`, including a `&dyn KeySource`.
+    pub fn convert_indexed<D>(
+        &self,
+        key_table: &(impl KeySource<C> + ?Sized),
+        ids: &[usize],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        ring_index: usize
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        self.convert::<D>(&ring_from_table::<C, _>(key_table, ids)?, message, signature, ring_index)
+    }
 
-        let s_z = r_z + c_z * signature.z;
-        let s_beta = r_beta + c_z * beta;
+    /// Same as `convert` for a signature from `SchnorrSignature::sign_messages`.
+    pub fn convert_messages<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        messages: &[&[u8]],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        self.convert::<D>(pks, &encode_messages(messages), signature, index)
+    }
 
-        let alpha = Scalar::<C>::random(&mut rng);
-        let rho = Scalar::<C>::random(&mut rng);
-        let zeta = Scalar::<C>::random(&mut rng);
+    /// Same as `convert`, taking the ring from an iterator, e.g. keys streamed from
+    /// storage. The proof passes over the ring several times, so up to `MAXN` keys
+    /// are buffered internally; a longer ring is rejected without draining it.
+    pub fn convert_iter<D, I>(
+        &self,
+        pks: I,
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest,
+        I: IntoIterator<Item = ProjectivePoint<C>>
+    {
+        self.convert::<D>(&Self::buffer_ring(pks)?, message, signature, index)
+    }
 
-        let n = pks.len();
-        let vec_s_a = (0..n).map(|_| Scalar::<C>::random(&mut rng)).collect::<Vec<_>>();
-        let vec_s_b = (0..n).map(|_| Scalar::<C>::random(&mut rng)).collect::<Vec<_>>();
+    /// Same as `verify`, taking the ring from an iterator; buffered as in `convert_iter`.
+    pub fn verify_iter<D, I>(
+        &self,
+        pks: I,
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest,
+        I: IntoIterator<Item = ProjectivePoint<C>>
+    {
+        self.verify::<D>(&Self::buffer_ring(pks)?, message, signature)
+    }
 
-        let vec_b: Vec<_> = (0..n).map(|i| if i == index { Scalar::<C>::ONE } else { Scalar::<C>::ZERO }).collect();
-        let vec_a: Vec<_> = vec_b.iter().map(|bi| *bi - Scalar::<C>::ONE).collect();
+    fn buffer_ring<I: IntoIterator<Item = ProjectivePoint<C>>>(pks: I) -> anyhow::Result<Vec<ProjectivePoint<C>>> {
+        let pks: Vec<_> = pks.into_iter().take(MAXN + 1).collect();
+        anyhow::ensure!(pks.len() <= MAXN, "Ring has more than MAXN {} keys", MAXN);
+        Ok(pks)
+    }
 
-        let mut point_a = self.h * alpha;
-        for i in 0..n {
-            point_a += self.vec_g[i] * vec_b[i];
-            point_a += self.vec_h[i] * vec_a[i];
-        }
-        let mut point_s = self.h * rho;
-        for i in 0..n {
-            point_s += self.vec_g[i] * vec_s_b[i];
-            point_s += self.vec_h[i] * vec_s_a[i];
-        }
-        let mut point_s_pk = self.g * zeta;
-        for i in 0..n {
-            point_s_pk += pks[i] * vec_s_b[i];
+    /// Same as `convert`, but first charges the conversion to the linkability tag of
+    /// the signer at `index` in `limiter`, failing once the signer has used up its
+    /// current window. A conversion that fails is refunded.
+    pub fn convert_limited<D>(
+        &self,
+        limiter: &mut ConversionLimiter,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        let pk = pks.get(index).ok_or_else(|| anyhow::anyhow!("Index {} out of range", index))?;
+        let tag = self.linkability_tag::<D>(pk);
+        limiter.acquire(&tag)?;
+        let result = self.convert::<D>(pks, message, signature, index);
+        if result.is_err() {
+            limiter.release(&tag);
         }
+        result
+    }
 
-        let y = Self::challenge_y::<D>(&self.g, &point_a, &point_s, &point_s_pk, &point_c_pk);
-        let w = Self::challenge_w::<D>(&self.g, &point_a, &point_s, &point_s_pk, &point_c_pk);
-
-        let vec_yn: Vec<<C as CurveArithmetic>::Scalar> = Self::build_vec_yn(n, &y);
-        let mut t1 = Scalar::<C>::ZERO;
-        let mut t2 = Scalar::<C>::ZERO;
-        for i in 0..n {
-            t1 += vec_s_b[i] * (vec_yn[i] * (vec_a[i] + w) + w * w);
-            t1 += (vec_b[i] - w) * (vec_yn[i] * vec_s_a[i]);
-            t2 += vec_s_b[i] * vec_yn[i] * vec_s_a[i];
-        }
+    /// Tag under which `convert_limited` counts the conversions of `pk`: a digest of
+    /// `g`, `h` and `pk`. It is the same for every conversion by one signer under these
+    /// params, and differs between params, so limiters shared across deployments do
+    /// not mix their counts.
+    pub fn linkability_tag<D>(&self, pk: &ProjectivePoint<C>) -> Output<D>
+    where
+        D: Digest
+    {
+        D::new()
+            .chain_update(b"incognito-link-tag")
+            .chain_update(self.g.to_bytes())
+            .chain_update(self.h.to_bytes())
+            .chain_update(pk.to_bytes())
+            .finalize()
+    }
 
-        let tau1 = Scalar::<C>::random(&mut rng);
-        let tau2 = Scalar::<C>::random(&mut rng);
-        let point_t1 = ProjectivePoint::<C>::generator() * t1 + self.h * tau1;
-        let point_t2 = ProjectivePoint::<C>::generator() * t2 + self.h * tau2;
+    /// Same as `convert`, additionally returning the blinding `beta` of `point_c_pk`.
+    /// Revealing `beta` later lets an auditor confirm the signer via `could_be_signer`.
+    pub fn convert_with_opening<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<(IncognitoSignature<C>, Scalar<C>)>
+    where
+        D: Digest
+    {
+        self.convert_indices::<D, _>(pks, message, signature, &[index], None, &mut rand::rngs::ThreadRng::default(), &mut no_tap)
+    }
 
-        let x = Self::challenge_x::<D>(&point_t1, &point_t2, &y, &w);
-        let taux = tau2 * x * x + tau1 * x;
-        let mu = alpha + rho * x;
-        let nu = beta + zeta * x;
+    /// Same as `convert`, with `beta` derived from `view_key` so that the holder of the
+    /// view key can later recognize the signature with `ViewKey::recognizes`.
+    pub fn convert_with_view_key<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize,
+        view_key: &ViewKey<C>
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        anyhow::ensure!(pks.get(index) == Some(view_key.pk()), "View key does not belong to the signer at index {}", index);
+        let beta = view_key.beta::<D>(&signature.point_r, message);
+        self.convert_indices::<D, _>(pks, message, signature, &[index], Some(beta), &mut rand::rngs::ThreadRng::default(), &mut no_tap)
+            .map(|(incsig, _)| incsig)
+    }
 
-        let vec_l: Vec<_> = (0..n).map(|i| (vec_b[i] - w) + vec_s_b[i] * x).collect();
-        let vec_r: Vec<_> = (0..n).map(|i| vec_yn[i] * (vec_a[i] + w + vec_s_a[i] * x) + w * w).collect();
-        let tx = (0..n).map(|i| vec_l[i] * vec_r[i]).sum();
+    /// Commitment to `message` carried by `CommittedSignature`. It is deterministic so
+    /// that equal messages can be recognized, which also means a guessed message can be
+    /// checked against it: it hides only messages with enough entropy.
+    pub fn message_commitment<D>(message: &[u8]) -> ProjectivePoint<C>
+    where
+        D: Digest
+    {
+        let digest = D::new().chain_update(b"incognito-message").chain_update(message).finalize();
+        ProjectivePoint::<C>::generator() * reduce_digest::<C>(&digest)
+    }
 
-        #[cfg(debug_assertions)]
-        {
-            let mut scalar_n = Scalar::<C>::ZERO;
-            let mut scalar_sum_yn = Scalar::<C>::ZERO;
-            for i in 0..n {
-                scalar_n += Scalar::<C>::ONE;
-                scalar_sum_yn += vec_yn[i];
-            }
-            let t0 = w * w - w * w * w * scalar_n + (w - w * w) * scalar_sum_yn;
-            debug_assert_eq!(tx, t0 + t1 * x + t2 * x * x);
+    /// Bytes signed in place of the message under `message_commitment`: a domain tag
+    /// followed by the commitment's encoding. Feeding them to every challenge as the
+    /// message binds the commitment into the transcript.
+    pub fn committed_message(message_commitment: &ProjectivePoint<C>) -> Vec<u8> {
+        [b"incognito-committed".as_slice(), message_commitment.to_bytes().as_ref()].concat()
+    }
+
+    /// Same as `convert` for a Schnorr signature over `committed_message(message_commitment)`,
+    /// so that the result can be verified from the commitment alone.
+    pub fn convert_with_message_commitment<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message_commitment: &ProjectivePoint<C>,
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<CommittedSignature<C>>
+    where
+        D: Digest
+    {
+        Ok(CommittedSignature {
+            signature: self.convert::<D>(pks, &Self::committed_message(message_commitment), signature, index)?,
+            message_commitment: *message_commitment
+        })
+    }
+
+    /// Same as `convert`, drawing every blinder from `rng`.
+    pub fn convert_with_rng<D, R>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize,
+        rng: &mut R
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest,
+        R: RngCore + CryptoRng
+    {
+        self.convert_indices::<D, R>(pks, message, signature, &[index], None, rng, &mut no_tap).map(|(incsig, _)| incsig)
+    }
+
+    /// Same as `convert`, with the blinders drawn from a `ChaCha20Rng` seeded by `seed`,
+    /// so that a failing conversion can be replayed exactly.
+    ///
+    /// For tests only: converting two different messages with the same seed reuses the
+    /// blinders and reveals the signer.
+    pub fn convert_deterministic<D>(
+        &self,
+        seed: [u8; 32],
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        self.convert_with_rng::<D, _>(pks, message, signature, index, &mut ChaCha20Rng::from_seed(seed))
+    }
+
+    /// Produces a fresh, unlinkable incognito signature for the same ring and message.
+    ///
+    /// An existing signature cannot be re-randomized from its public data alone: changing
+    /// `beta` or any blinder changes the Fiat–Shamir challenges, and answering them needs
+    /// `z` from the Schnorr signature. Whoever kept the Schnorr signature and the opening
+    /// `beta` of `previous` can instead convert again; the signer's index is recovered
+    /// from the opening.
+    pub fn reconvert<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        previous: &IncognitoSignature<C>,
+        beta: &Scalar<C>
+    ) -> anyhow::Result<(IncognitoSignature<C>, Scalar<C>)>
+    where
+        D: Digest
+    {
+        anyhow::ensure!(previous.point_r == signature.point_r, "Schnorr signature does not match the incognito signature");
+        let pk = self.key_commitment().open(&previous.point_c_pk, beta)
+            .ok_or_else(|| anyhow::anyhow!("Key commitment cannot be opened"))?;
+        let index = pks.iter().position(|each| *each == pk).ok_or_else(|| anyhow::anyhow!("Opening does not match any ring member"))?;
+        self.convert_with_opening::<D>(pks, message, signature, index)
+    }
+
+    /// Converts a signature made jointly by `t` distinct ring members into one that
+    /// proves `t` members of `pks` signed, without revealing which. `verify_threshold`
+    /// accepts it for any threshold up to `t`. The count `t` itself is not hidden: a
+    /// verifier can find it by trying thresholds until one fails.
+    ///
+    /// The joint signature is a Schnorr signature under the sum of the signers' keys,
+    /// so the keys in `pks` must not be chosen adversarially (e.g. registered with a
+    /// proof of possession) to rule out rogue-key combinations.
+    pub fn convert_threshold<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signers: &[(Scalar<C>, usize)]
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        let mut indices: Vec<_> = signers.iter().map(|(_, index)| *index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        anyhow::ensure!(indices.len() == signers.len(), "Signers must be distinct");
+        for (sk, index) in signers {
+            anyhow::ensure!(pks.get(*index) == Some(&(ProjectivePoint::<C>::generator() * sk)), "Signer key does not match index {}", index);
+        }
+        let sk: Scalar<C> = signers.iter().map(|(sk, _)| *sk).sum();
+        let signature = SchnorrSignature::<C>::sign::<D>(&sk, message);
+        self.convert_indices::<D, _>(pks, message, &signature, &indices, None, &mut rand::rngs::ThreadRng::default(), &mut no_tap).map(|(incsig, _)| incsig)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn convert_indices<D, R>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        indices: &[usize],
+        beta: Option<Scalar<C>>,
+        rng: &mut R,
+        tap: Tap
+    ) -> anyhow::Result<(IncognitoSignature<C>, Scalar<C>)>
+    where
+        D: Digest,
+        R: RngCore + CryptoRng
+    {
+        ensure_nonempty::<C>(pks)?;
+        anyhow::ensure!(pks.len() <= MAXN, "Ring size {} exceeds {}", pks.len(), MAXN);
+        required_rounds(pks.len()).ok_or(StructuralError::RingSize(pks.len()))?;
+        anyhow::ensure!(!indices.is_empty(), "No signers");
+        ensure_distinct::<C>(pks)?;
+        for index in indices {
+            anyhow::ensure!(*index < pks.len(), "Index {} out of range", index);
+        }
+
+        let n = pks.len();
+        let vec_b: Vec<_> = (0..n).map(|i| if indices.contains(&i) { Scalar::<C>::ONE } else { Scalar::<C>::ZERO }).collect();
+        let mut secrets = ProverSecrets::<C> {
+            beta: beta.unwrap_or_else(|| Scalar::<C>::random(&mut *rng)),
+            r_z: Scalar::<C>::random(&mut *rng),
+            r_beta: Scalar::<C>::random(&mut *rng),
+            alpha: Scalar::<C>::random(&mut *rng),
+            rho: Scalar::<C>::random(&mut *rng),
+            zeta: Scalar::<C>::random(&mut *rng),
+            vec_s_a: (0..n).map(|_| Scalar::<C>::random(&mut *rng)).collect(),
+            vec_s_b: (0..n).map(|_| Scalar::<C>::random(&mut *rng)).collect(),
+            tau1: Scalar::<C>::random(&mut *rng),
+            tau2: Scalar::<C>::random(&mut *rng),
+            vec_a: vec_b.iter().map(|bi| *bi - Scalar::<C>::ONE).collect(),
+            vec_b,
+            vec_l: Vec::new(),
+            vec_r: Vec::new()
+        };
+        let s = &mut secrets;
+        let point_c_pk = self.key_commitment().commit(&indices.iter().map(|index| pks[*index]).sum::<ProjectivePoint<C>>(), &s.beta);
+
+        let c = SchnorrSignature::<C>::challenge::<D>(&signature.point_r, message, &mut |bytes| tap("c", bytes));
+
+        let point_r_z = ProjectivePoint::<C>::generator() * s.r_z + self.g * s.r_beta * c;
+        let c_z = Self::challenge_cz::<D>(&point_r_z, &point_c_pk, tap);
+
+        let s_z = s.r_z + c_z * signature.z;
+        let s_beta = s.r_beta + c_z * s.beta;
+
+        let mut point_a = self.h * s.alpha;
+        for i in 0..n {
+            point_a += self.vec_g[i] * s.vec_b[i];
+            point_a += self.vec_h[i] * s.vec_a[i];
+        }
+        let mut point_s = self.h * s.rho;
+        for i in 0..n {
+            point_s += self.vec_g[i] * s.vec_s_b[i];
+            point_s += self.vec_h[i] * s.vec_s_a[i];
         }
+        let mut point_s_pk = self.g * s.zeta;
+        for (pk, s_b) in pks.iter().zip(&s.vec_s_b) {
+            point_s_pk += *pk * s_b;
+        }
+
+        let ring = ring_hash::<C, D>(pks);
+        let y = Self::challenge_y::<D>(&self.g, &point_a, &point_s, &point_s_pk, &point_c_pk, &ring, tap);
+        let w = Self::challenge_w::<D>(&self.g, &point_a, &point_s, &point_s_pk, &point_c_pk, &ring, tap);
+
+        let vec_yn: Vec<<C as CurveArithmetic>::Scalar> = Self::build_vec_yn(n, &y);
+        let mut t1 = Scalar::<C>::ZERO;
+        let mut t2 = Scalar::<C>::ZERO;
+        for (i, yn) in vec_yn.iter().enumerate() {
+            t1 += s.vec_s_b[i] * (*yn * (s.vec_a[i] + w) + w * w);
+            t1 += (s.vec_b[i] - w) * (*yn * s.vec_s_a[i]);
+            t2 += s.vec_s_b[i] * yn * s.vec_s_a[i];
+        }
+
+        let point_t1 = ProjectivePoint::<C>::generator() * t1 + self.h * s.tau1;
+        let point_t2 = ProjectivePoint::<C>::generator() * t2 + self.h * s.tau2;
 
-        let d = Self::challenge_d::<D>(&x, &taux, &mu, &nu, &tx);
+        let x = Self::challenge_x::<D>(&point_t1, &point_t2, &y, &w, tap);
+        let taux = s.tau2 * x * x + s.tau1 * x;
+        let mu = s.alpha + s.rho * x;
+        let nu = s.beta + s.zeta * x;
 
-        let vec_yn_inv: Vec<<C as CurveArithmetic>::Scalar> = Self::build_vec_yn(n, &y.invert().unwrap());
+        s.vec_l = (0..n).map(|i| (s.vec_b[i] - w) + s.vec_s_b[i] * x).collect();
+        s.vec_r = (0..n).map(|i| vec_yn[i] * (s.vec_a[i] + w + s.vec_s_a[i] * x) + w * w).collect();
+        let tx = (0..n).map(|i| s.vec_l[i] * s.vec_r[i]).sum();
+
+        // Cheap next to the commitments above, so it stays on in release builds too.
+        let t0 = Self::compute_t0(n, indices.len(), &w, &vec_yn.iter().sum());
+        anyhow::ensure!(tx == t0 + t1 * x + t2 * x * x, "Prover invariant tx = t(x) violated");
+
+        let d = Self::challenge_d::<D>(&x, &taux, &mu, &nu, &tx, tap);
+
+        let vec_yn_inv: Vec<<C as CurveArithmetic>::Scalar> = Self::build_vec_yn(n, &invert_challenge::<C>(&y)?);
         let bulletproof_base1: Vec<_> = (0..n).map(|i| self.vec_g[i] + pks[i] * d). collect();
+        Self::debug_assert_distinct_bases(&bulletproof_base1);
         let bulletproof_base2: Vec<_> = (0..n).map(|i| self.vec_h[i] * vec_yn_inv[i]). collect();
         // Proving exists l and r such that P = g ^ l h ^ r and c = <l, r>
         let bulletproof_target: ProjectivePoint::<C> = (0..n).map(|i| {
-            (self.vec_g[i] + pks[i] * d) * vec_l[i] + self.vec_h[i] * vec_yn_inv[i] * vec_r[i]
+            (self.vec_g[i] + pks[i] * d) * s.vec_l[i] + self.vec_h[i] * vec_yn_inv[i] * s.vec_r[i]
         }).sum();
-        let bulletproof = BulletProof::<C>::prove::<D>(&bulletproof_base1, &bulletproof_base2, &vec_l, &vec_r, &bulletproof_target);
+        let bulletproof = BulletProof::<C>::prove::<D>(&bulletproof_base1, &bulletproof_base2, &s.vec_l, &s.vec_r, &bulletproof_target);
+        let beta = s.beta;
 
-        Ok(IncognitoSignature {
+        Ok((IncognitoSignature {
             point_c_pk,
             point_r: signature.point_r,
             point_r_z,
@@ -314,9 +1189,11 @@ where
             nu,
             tx,
             bulletproof
-        })
+        }, beta))
     }
 
+    /// Only `g`, `h` and the first `pks.len()` generators take part, so the signer's
+    /// params may have a different `MAXN` as long as those agree, see `compatible_with`.
     pub fn verify<D>(
         &self,
         pks: &[ProjectivePoint<C>],
@@ -324,333 +1201,2669 @@ where
         signature: &IncognitoSignature<C>
     ) -> anyhow::Result<()>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let n = pks.len();
-
-        let IncognitoSignature {
-            point_c_pk,
-            point_r,
-            point_r_z,
-            s_z,
-            s_beta,
-            point_a,
-            point_s,
-            point_s_pk,
-            point_t1,
-            point_t2,
-            taux,
-            mu,
-            nu,
-            tx,
-            bulletproof
-        } = signature;
+        self.verify_with_backend::<D, GenericBackend>(pks, message, signature)
+    }
 
-        let c = SchnorrSignature::<C>::challenge::<D>(&signature.point_r, message);
+    /// Verifies a signature from `convert_indexed` against the same `ids` and `key_table`.
+    pub fn verify_indexed<D>(
+        &self,
+        key_table: &(impl KeySource<C> + ?Sized),
+        ids: &[usize],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify::<D>(&ring_from_table::<C, _>(key_table, ids)?, message, signature)
+    }
 
-        let c_z = Self::challenge_cz::<D>(&point_r_z, &point_c_pk);
-        let y = Self::challenge_y::<D>(&self.g, &point_a, &point_s, &point_s_pk, &point_c_pk);
-        let w = Self::challenge_w::<D>(&self.g, &point_a, &point_s, &point_s_pk, &point_c_pk);
+    /// Verifies a signature over the first `ring_len` keys of a larger committed set,
+    /// e.g. a ledger's key table. Borrows the prefix, nothing is copied.
+    pub fn verify_prefix<D>(
+        &self,
+        full_keys: &[ProjectivePoint<C>],
+        ring_len: usize,
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        anyhow::ensure!(ring_len <= full_keys.len(), "Ring length {} exceeds {} keys", ring_len, full_keys.len());
+        self.verify::<D>(&full_keys[..ring_len], message, signature)
+    }
 
+    /// Verifies a signature from `convert_messages`, requiring the identical message list.
+    pub fn verify_messages<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        messages: &[&[u8]],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify::<D>(pks, &encode_messages(messages), signature)
+    }
 
+    /// Same as `verify`, returning digests of the ring and message on success.
+    pub fn verify_with_ring<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<VerifiedSignature<D>>
+    where
+        D: Digest
+    {
+        self.verify::<D>(pks, message, signature)?;
+        Ok(VerifiedSignature {
+            ring_hash: ring_hash::<C, D>(pks),
+            message_hash: D::new().chain_update(b"message").chain_update(message).finalize()
+        })
+    }
 
-        anyhow::ensure!(
-            ProjectivePoint::<C>::generator() * s_z + self.g * s_beta * c == *point_r_z + *point_r * c_z + *point_c_pk * c_z * c
-        );
+    /// Same as `verify`, with the multi-scalar multiplication delegated to `B`.
+    pub fn verify_with_backend<D, B>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        self.verify_threshold_with_backend::<D, B>(pks, message, signature, 1..=1, &mut VerifyScratch::new(), true)
+    }
 
-        let x = Self::challenge_x::<D>(&point_t1, &point_t2, &y, &w);
+    /// Same as `verify`, reusing the buffers in `scratch` instead of allocating.
+    pub fn verify_with_scratch<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>,
+        scratch: &mut VerifyScratch<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify_threshold_with_backend::<D, GenericBackend>(pks, message, signature, 1..=1, scratch, true)
+    }
 
-        let mut scalar_n = Scalar::<C>::ZERO;
-        let mut scalar_sum_yn = Scalar::<C>::ZERO;
-        let mut yn = Scalar::<C>::ONE;
-        for _ in 0..n {
-            scalar_n += Scalar::<C>::ONE;
-            scalar_sum_yn += yn;
+    /// Runs the checks `verify` makes on the ring alone and hashes it, so signatures
+    /// over the same ring can skip them with `verify_prepared`.
+    pub fn prepare_ring<D>(&self, pks: &[ProjectivePoint<C>]) -> anyhow::Result<PreparedRing<C, D>>
+    where
+        D: Digest
+    {
+        ensure_nonempty::<C>(pks)?;
+        anyhow::ensure!(pks.len() <= MAXN, "Ring size {} out of range", pks.len());
+        required_rounds(pks.len()).ok_or(StructuralError::RingSize(pks.len()))?;
+        check_digest_size::<C, D>()?;
+        ensure_distinct::<C>(pks)?;
+        let n = pks.len();
+        Ok(PreparedRing {
+            pks: pks.to_vec(),
+            ring_hash: ring_hash::<C, D>(pks),
+            sums: [self.vec_g[..n].iter().sum(), pks.iter().sum(), self.vec_h[..n].iter().sum()]
+        })
+    }
 
-            yn *= y;
-        }
-        let t0 = w * w - w * w * w * scalar_n + (w - w * w) * scalar_sum_yn;
-        anyhow::ensure!(
-            ProjectivePoint::<C>::generator() * tx + self.h * taux == ProjectivePoint::<C>::generator() * t0 + *point_t1 * x + *point_t2 * x * x
-        );
+    /// Same as `verify` over `ring.pks()`.
+    pub fn verify_prepared<D>(
+        &self,
+        ring: &PreparedRing<C, D>,
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let result = self.check_prepared::<D>(ring, message, signature);
+        #[cfg(feature = "metrics")]
+        record_verification([ring.pks.len()], result.is_ok());
+        result
+    }
 
-        let vec_yn: Vec<<C as CurveArithmetic>::Scalar> = Self::build_vec_yn(n, &y);
-        let vec_yn_inv: Vec<<C as CurveArithmetic>::Scalar> = Self::build_vec_yn(n, &y.invert().unwrap());
-        let d = Self::challenge_d::<D>(&x, &taux, &mu, &nu, &tx);
-        let point_1 = self.g * d * nu + self.h * mu;
-        let mut point_2 = *point_a + *point_s * x + *point_c_pk * d + *point_s_pk * x * d;
-        for i in 0..n {
-            point_2 += (self.vec_g[i] + pks[i] * d) * (-w);
-            point_2 += (self.vec_h[i] * vec_yn_inv[i]) * (w * vec_yn[i] + w * w);
-        }
+    fn check_prepared<D>(&self, ring: &PreparedRing<C, D>, message: &[u8], signature: &IncognitoSignature<C>) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let pks = ring.pks();
+        anyhow::ensure!(pks.len() <= MAXN, "Ring size {} out of range", pks.len());
+        signature.structural_check(pks.len())?;
+        let challenges = self.bound_challenges::<D>(&ring.ring_hash, message, signature, &mut no_tap);
+        self.check_equations::<D, GenericBackend>(pks, signature, 1..=1, &challenges, &mut VerifyScratch::new(), true, true, Some(&ring.sums))
+    }
 
-        // let bullet_target: ProjectivePoint::<C> = (0..n).map(|i| {
-        //     (self.vec_g[i] + pks[i] * d) * vec_l[i] + self.vec_h[i] * vec_yn_inv[i] * vec_r[i]
-        // }).sum();
-        let bulletproof_base1: Vec<_> = (0..n).map(|i| self.vec_g[i] + pks[i] * d). collect();
-        let bulletproof_base2: Vec<_> = (0..n).map(|i| self.vec_h[i] * vec_yn_inv[i]). collect();
-        bulletproof.verify::<D>(&bulletproof_base1, &bulletproof_base2)?;
+    /// Same as `verify` for a signature sent without its bulletproof target. The target
+    /// is recomputed and the bulletproof checked against it, so this costs no more than
+    /// `verify`.
+    pub fn verify_compact<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        compact: &CompactSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let signature = IncognitoSignature::from_parts(compact.linkage.clone(), compact.bulletproof.clone());
+        let result = self.precheck::<D>(pks, &signature, 1).and_then(|_| {
+            let challenges = self.challenges::<D>(pks, message, &signature);
+            self.check_equations::<D, GenericBackend>(pks, &signature, 1..=1, &challenges, &mut VerifyScratch::new(), true, false, None)
+        });
+        #[cfg(feature = "metrics")]
+        record_verification([pks.len()], result.is_ok());
+        result
+    }
 
-        anyhow::ensure!(
-            point_1 + bulletproof.target == point_2
-        );
+    /// Restores the full signature `compact` was made from, for storage or for APIs that
+    /// take an `IncognitoSignature`. Does not verify it.
+    pub fn expand<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        compact: CompactSignature<C>
+    ) -> anyhow::Result<IncognitoSignature<C>>
+    where
+        D: Digest
+    {
+        let mut signature = IncognitoSignature::from_parts(compact.linkage, compact.bulletproof);
+        self.precheck::<D>(pks, &signature, 1)?;
+        let challenges = self.challenges::<D>(pks, message, &signature);
+        signature.bulletproof.target = self.recompute_target::<GenericBackend>(pks, &signature, &challenges, &mut VerifyScratch::new(), None)?;
+        Ok(signature)
+    }
 
+    /// Cheap rejection of forgeries ahead of `verify`, checking only the linkage and
+    /// polynomial equations. Their cost does not grow with the ring beyond hashing it.
+    ///
+    /// Passing proves nothing about ring membership, which only the inner-product
+    /// argument establishes; a subset of the ring cannot stand in for it, since the
+    /// bulletproof commits to all keys at once. Signatures that pass must still go
+    /// through `verify`.
+    pub fn prescreen<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.precheck::<D>(pks, signature, 1)?;
+        let challenges = self.challenges::<D>(pks, message, signature);
+        anyhow::ensure!(self.check_linkage(signature, &challenges), "Linkage equation failed");
+        anyhow::ensure!(self.check_polynomial(signature, &challenges, pks.len(), 1), "Polynomial equation failed");
         Ok(())
     }
-}
 
-impl <C: CurveArithmetic, const MAXN: usize> From<IncognitoParamsSerde<C, MAXN>> for IncognitoParams<C, MAXN> {
-    fn from(value: IncognitoParamsSerde<C, MAXN>) -> Self {
-        Self {
-            g: ProjectivePoint::<C>::from(value.g),
-            h: ProjectivePoint::<C>::from(value.h),
-            vec_g: value.vec_g.into_iter().map(|each| ProjectivePoint::<C>::from(each)).collect::<Vec<_>>().try_into().unwrap(),
-            vec_h: value.vec_h.into_iter().map(|each| ProjectivePoint::<C>::from(each)).collect::<Vec<_>>().try_into().unwrap(),
-        }
+    /// Same as `verify`, but evaluates every verification equation before deciding, so
+    /// a rejection takes as long whichever equation failed. Only the ring size, the
+    /// signature's shape and duplicate keys, all public, are still checked up front.
+    pub fn verify_constant_time<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify_threshold_with_backend::<D, GenericBackend>(pks, message, signature, 1..=1, &mut VerifyScratch::new(), false)
     }
-}
 
-impl <C: CurveArithmetic, const MAXN: usize> From<IncognitoParams<C, MAXN>> for IncognitoParamsSerde<C, MAXN> {
-    fn from(value: IncognitoParams<C, MAXN>) -> Self {
-        Self {
-            g: value.g.to_affine(),
-            h: value.h.to_affine(),
-            vec_g: value.vec_g.into_iter().map(|each| each.to_affine()).collect(),
-            vec_h: value.vec_h.into_iter().map(|each| each.to_affine()).collect(),
-        }
+    /// Verifies a signature from `convert_threshold`, accepting if at least `threshold`
+    /// distinct members of `pks` signed.
+    pub fn verify_threshold<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>,
+        threshold: usize
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify_threshold_with_backend::<D, GenericBackend>(pks, message, signature, threshold..=pks.len(), &mut VerifyScratch::new(), true)
     }
-}
 
-impl <C: CurveArithmetic> From<IncognitoSignatureSerde<C>> for IncognitoSignature<C> {
-    fn from(value: IncognitoSignatureSerde<C>) -> Self {
-        Self {
-            point_r: ProjectivePoint::<C>::from(value.point_r),
-            point_c_pk: ProjectivePoint::<C>::from(value.point_c_pk),
-            point_r_z: ProjectivePoint::<C>::from(value.point_r_z),
-            s_z: value.s_z,
-            s_beta: value.s_beta,
-            point_a: ProjectivePoint::<C>::from(value.point_a),
-            point_s: ProjectivePoint::<C>::from(value.point_s),
-            point_s_pk: ProjectivePoint::<C>::from(value.point_s_pk),
-            point_t1: ProjectivePoint::<C>::from(value.point_t1),
-            point_t2: ProjectivePoint::<C>::from(value.point_t2),
-            taux: value.taux,
-            mu: value.mu,
-            nu: value.nu,
-            tx: value.tx,
-            bulletproof: value.bulletproof,
-        }
-    }
-}
-
-impl <C: CurveArithmetic> From<IncognitoSignature<C>> for IncognitoSignatureSerde<C> {
-    fn from(value: IncognitoSignature<C>) -> Self {
-        Self {
-            point_r: value.point_r.to_affine(),
-            point_c_pk: value.point_c_pk.to_affine(),
-            point_r_z: value.point_r_z.to_affine(),
-            s_z: value.s_z,
-            s_beta: value.s_beta,
-            point_a: value.point_a.to_affine(),
-            point_s: value.point_s.to_affine(),
-            point_s_pk: value.point_s_pk.to_affine(),
-            point_t1: value.point_t1.to_affine(),
-            point_t2: value.point_t2.to_affine(),
-            taux: value.taux,
-            mu: value.mu,
-            nu: value.nu,
-            tx: value.tx,
-            bulletproof: value.bulletproof,
-        }
+    /// Same as `convert`, additionally committing to `amount` and proving it is below
+    /// `2^bits`. `bits` must be a power of two no larger than 64 or `MAXN`.
+    /// Returns the commitment and its blinding, which opens `point_v` to `amount`.
+    /// The range proof is bound to the returned signature and to `message`.
+    pub fn convert_with_amount<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize,
+        amount: u64,
+        bits: usize
+    ) -> anyhow::Result<(IncognitoSignature<C>, AmountCommitment<C>, Scalar<C>)>
+    where
+        D: Digest
+    {
+        self.check_amount_bits(bits)?;
+        anyhow::ensure!(bits == 64 || amount >> bits == 0, "Amount {} does not fit in {} bits", amount, bits);
+        let incsig = self.convert::<D>(pks, message, signature, index)?;
+        let blind = Scalar::<C>::random(&mut rand::rngs::ThreadRng::default());
+        let point_v = self.amount_commitment().commit(&(self.value_base() * Scalar::<C>::from(amount)), &blind);
+        let range_proof = RangeProof::<C>::prove::<D>(
+            (&self.g, &self.h, &self.vec_g[..bits], &self.vec_h[..bits]),
+            &point_v,
+            amount,
+            &blind,
+            &Self::amount_context(&incsig, message)
+        );
+        Ok((incsig, AmountCommitment { point_v, range_proof }, blind))
     }
-}
 
-// impl <C: CurveArithmetic, const MAXN: usize> Serialize for IncognitoParams<C, MAXN> 
-// where
-//     AffinePoint<C>: Serialize + DeserializeOwned
-// {
-//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-//     where
-//         S: serde::Serializer {
-//         let mut state = serializer.serialize_struct("IncognitoParams", 4)?;
-//         state.serialize_field("g", &self.g.to_affine())?;
-//         state.serialize_field("h", &self.h.to_affine())?;
-//         state.serialize_field("vec_g", &self.vec_g.iter().map(|each| each.to_affine()).collect::<Vec<_>>())?;
-//         state.serialize_field("vec_h", &self.vec_g.iter().map(|each| each.to_affine()).collect::<Vec<_>>())?;
-//         state.end()
-//     }
-// }
+    /// Verifies `signature` as `verify` does, then checks that `commitment` holds an
+    /// amount below `2^bits` and was made for this signature and message.
+    pub fn verify_with_amount<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>,
+        commitment: &AmountCommitment<C>,
+        bits: usize
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.check_amount_bits(bits)?;
+        self.verify::<D>(pks, message, signature)?;
+        commitment.range_proof.verify::<D>(
+            (&self.g, &self.h, &self.vec_g[..bits], &self.vec_h[..bits]),
+            &commitment.point_v,
+            &Self::amount_context(signature, message)
+        )
+    }
 
-// impl<'de, C: CurveArithmetic, const MAXN: usize> Deserialize<'de> for IncognitoParams<C, MAXN>
-// where
-//     AffinePoint<C>: Deserialize<'de>
-// {
-//     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-//     where
-//         D: serde::Deserializer<'de> {
-//         #[derive(Deserialize)]
-//         #[serde(field_identifier, rename_all = "snake_case")]
+    /// Verifies a signature from `convert_with_message_commitment` against its own
+    /// commitment, without the message. A signature moved onto another commitment fails.
+    pub fn verify_committed<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        committed: &CommittedSignature<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify::<D>(pks, &Self::committed_message(&committed.message_commitment), &committed.signature)
+    }
+
+    fn check_amount_bits(&self, bits: usize) -> anyhow::Result<()> {
+        anyhow::ensure!(bits.is_power_of_two() && bits <= 64, "Range proof bits {} must be a power of two up to 64", bits);
+        anyhow::ensure!(bits <= MAXN, "Range proof bits {} exceed MAXN {}", bits, MAXN);
+        Ok(())
+    }
+
+    fn amount_context(signature: &IncognitoSignature<C>, message: &[u8]) -> Vec<u8> {
+        let mut context = Vec::new();
+        context.extend_from_slice(signature.point_c_pk.to_bytes().as_ref());
+        context.extend_from_slice(signature.point_r.to_bytes().as_ref());
+        context.extend_from_slice(message);
+        context
+    }
+
+    fn verify_threshold_with_backend<D, B>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>,
+        signers: RangeInclusive<usize>,
+        scratch: &mut VerifyScratch<C>,
+        short_circuit: bool
+    ) -> anyhow::Result<()>
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        let result = self.check_threshold_with_backend::<D, B>(pks, message, signature, signers, scratch, short_circuit);
+        #[cfg(feature = "metrics")]
+        record_verification([pks.len()], result.is_ok());
+        result
+    }
+
+    fn check_threshold_with_backend<D, B>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>,
+        signers: RangeInclusive<usize>,
+        scratch: &mut VerifyScratch<C>,
+        short_circuit: bool
+    ) -> anyhow::Result<()>
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        self.precheck::<D>(pks, signature, *signers.start())?;
+        let challenges = self.challenges::<D>(pks, message, signature);
+        self.check_equations::<D, B>(pks, signature, signers, &challenges, scratch, short_circuit, true, None)
+    }
+
+    /// Same as `verify`, with the Fiat–Shamir challenges taken from `challenges` instead
+    /// of derived from the transcript, so tests can probe single equations at chosen
+    /// challenge values. Never use it to accept signatures.
+    #[cfg(feature = "test-internals")]
+    pub fn verify_with_challenges<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        signature: &IncognitoSignature<C>,
+        challenges: &Challenges<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.precheck::<D>(pks, signature, 1)?;
+        self.check_equations::<D, GenericBackend>(pks, signature, 1..=1, challenges, &mut VerifyScratch::new(), true, true, None)
+    }
+
+    fn precheck<D>(&self, pks: &[ProjectivePoint<C>], signature: &IncognitoSignature<C>, threshold: usize) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let n = pks.len();
+        ensure_nonempty::<C>(pks)?;
+        anyhow::ensure!(n <= MAXN, "Ring size {} out of range", n);
+        signature.structural_check(n)?;
+        anyhow::ensure!(threshold >= 1 && threshold <= n, "Threshold {} out of range", threshold);
+        check_digest_size::<C, D>()?;
+        ensure_distinct::<C>(pks)
+    }
+
+    /// Fiat–Shamir challenges `verify` derives for `signature` on `message`.
+    pub fn challenges<D>(&self, pks: &[ProjectivePoint<C>], message: &[u8], signature: &IncognitoSignature<C>) -> Challenges<C>
+    where
+        D: Digest
+    {
+        self.bound_challenges::<D>(&ring_hash::<C, D>(pks), message, signature, &mut no_tap)
+    }
+
+    /// Same as `challenges`, with the ring digest already computed. Challenges are
+    /// derived in the order `convert` derives them, each passing its segments to `tap`.
+    fn bound_challenges<D>(&self, ring: &[u8], message: &[u8], signature: &IncognitoSignature<C>, tap: Tap) -> Challenges<C>
+    where
+        D: Digest
+    {
+        let IncognitoSignature { point_c_pk, point_r, point_r_z, point_a, point_s, point_s_pk, point_t1, point_t2, taux, mu, nu, tx, .. } = signature;
+        let c = SchnorrSignature::<C>::challenge::<D>(point_r, message, &mut |bytes| tap("c", bytes));
+        let c_z = Self::challenge_cz::<D>(point_r_z, point_c_pk, tap);
+        let y = Self::challenge_y::<D>(&self.g, point_a, point_s, point_s_pk, point_c_pk, ring, tap);
+        let w = Self::challenge_w::<D>(&self.g, point_a, point_s, point_s_pk, point_c_pk, ring, tap);
+        let x = Self::challenge_x::<D>(point_t1, point_t2, &y, &w, tap);
+        let d = Self::challenge_d::<D>(&x, taux, mu, nu, tx, tap);
+        Challenges { c, c_z, y, w, x, d }
+    }
+
+    /// Same as `convert`, also returning the bytes hashed for each challenge, recorded
+    /// as the conversion derives them.
+    #[cfg(feature = "transcript-log")]
+    pub fn convert_with_log<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &SchnorrSignature<C>,
+        index: usize
+    ) -> anyhow::Result<(IncognitoSignature<C>, TranscriptLog)>
+    where
+        D: Digest
+    {
+        let mut log = TranscriptLog::new();
+        let (incsig, _) = self.convert_indices::<D, _>(pks, message, signature, &[index], None, &mut rand::rngs::ThreadRng::default(), &mut |name, bytes| log.absorb(name, bytes))?;
+        Ok((incsig, log))
+    }
+
+    /// Same as `verify`, also returning the bytes hashed for each challenge. The log is
+    /// returned even if verification fails, as long as the signature is well formed.
+    #[cfg(feature = "transcript-log")]
+    pub fn verify_with_log<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<(anyhow::Result<()>, TranscriptLog)>
+    where
+        D: Digest
+    {
+        self.precheck::<D>(pks, signature, 1)?;
+        let mut log = TranscriptLog::new();
+        let challenges = self.bound_challenges::<D>(&ring_hash::<C, D>(pks), message, signature, &mut |name, bytes| log.absorb(name, bytes));
+        let result = self.check_equations::<D, GenericBackend>(pks, signature, 1..=1, &challenges, &mut VerifyScratch::new(), true, true, None);
+        Ok((result, log))
+    }
+
+    /// Linkage equation `G * s_z + g * s_beta * c == R_z + (R + C_pk * c) * c_z`, binding
+    /// the commitment `point_c_pk` to the Schnorr signature.
+    pub fn check_linkage(&self, signature: &IncognitoSignature<C>, challenges: &Challenges<C>) -> bool {
+        self.linkage_equation::<GenericBackend>(signature, challenges).into()
+    }
+
+    /// Polynomial equation `G * tx + h * taux == G * t0 + T1 * x + T2 * x^2` for a ring of
+    /// `ring_size` keys with `signers` signers.
+    pub fn check_polynomial(&self, signature: &IncognitoSignature<C>, challenges: &Challenges<C>, ring_size: usize, signers: usize) -> bool {
+        self.polynomial_equation::<GenericBackend>(signature, challenges, ring_size, signers..=signers).into()
+    }
+
+    /// Inner-product argument over `pks`: the bulletproof folds to its final scalars and
+    /// its target matches the one rebuilt from the signature's commitments.
+    pub fn check_inner_product<D>(&self, pks: &[ProjectivePoint<C>], signature: &IncognitoSignature<C>, challenges: &Challenges<C>) -> bool
+    where
+        D: Digest
+    {
+        self.inner_product_equations::<D, GenericBackend>(pks, signature, challenges, &mut VerifyScratch::new(), true, None)
+            .is_ok_and(|(folding, target)| (folding & target).into())
+    }
+
+    fn linkage_equation<B>(&self, signature: &IncognitoSignature<C>, challenges: &Challenges<C>) -> Choice
+    where
+        B: CurveBackend<C>
+    {
+        let IncognitoSignature { point_c_pk, point_r, point_r_z, s_z, s_beta, .. } = signature;
+        let Challenges { c, c_z, .. } = *challenges;
+        B::msm(&[ProjectivePoint::<C>::generator(), self.g, *point_r, *point_c_pk], &[*s_z, *s_beta * c, -c_z, -c_z * c]).ct_eq(point_r_z)
+    }
+
+    /// Holds if the polynomial equation does for some signer count in `signers`.
+    fn polynomial_equation<B>(&self, signature: &IncognitoSignature<C>, challenges: &Challenges<C>, ring_size: usize, signers: RangeInclusive<usize>) -> Choice
+    where
+        B: CurveBackend<C>
+    {
+        let IncognitoSignature { point_t1, point_t2, taux, tx, .. } = signature;
+        let Challenges { y, w, x, .. } = *challenges;
+        let mut scalar_sum_yn = Scalar::<C>::ZERO;
+        let mut yn = Scalar::<C>::ONE;
+        for _ in 0..ring_size {
+            scalar_sum_yn += yn;
+            yn *= y;
+        }
+        let t0 = Self::compute_t0(ring_size, *signers.start(), &w, &scalar_sum_yn);
+        let residue = B::msm(&[ProjectivePoint::<C>::generator(), self.h, *point_t1, *point_t2], &[*tx - t0, *taux, -x, -x * x]);
+        let mut holds = residue.is_identity();
+        // Each further signer adds `w^2` to `t0`, so the residue grows by `G * w^2`.
+        if signers.end() > signers.start() {
+            let step = B::mul(&ProjectivePoint::<C>::generator(), &(w * w));
+            let mut expected = step;
+            for _ in *signers.start()..*signers.end() {
+                holds |= residue.ct_eq(&expected);
+                expected = B::add(&expected, &step);
+            }
+        }
+        holds
+    }
+
+    /// Outcomes of the bulletproof folding and of the target equation. Without
+    /// `stored_target` the signature's own target is ignored, as in a `CompactSignature`,
+    /// and the folding is checked against the recomputed one instead.
+    fn inner_product_equations<D, B>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        signature: &IncognitoSignature<C>,
+        challenges: &Challenges<C>,
+        scratch: &mut VerifyScratch<C>,
+        stored_target: bool,
+        sums: Option<&RingSums<C>>
+    ) -> anyhow::Result<(Choice, Choice)>
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        let n = pks.len();
+        let Challenges { d, .. } = *challenges;
+        let target = self.recompute_target::<B>(pks, signature, challenges, scratch, sums)?;
+
+        // let bullet_target: ProjectivePoint::<C> = (0..n).map(|i| {
+        //     (self.vec_g[i] + pks[i] * d) * vec_l[i] + self.vec_h[i] * vec_yn_inv[i] * vec_r[i]
+        // }).sum();
+        let VerifyScratch { vec_yn_inv, base_g, base_h, .. } = scratch;
+        base_g.extend((0..n).map(|i| B::add(&self.vec_g[i], &B::mul(&pks[i], &d))));
+        Self::debug_assert_distinct_bases(base_g);
+        base_h.extend((0..n).map(|i| B::mul(&self.vec_h[i], &vec_yn_inv[i])));
+        let bulletproof = &signature.bulletproof;
+        if stored_target {
+            let folding = bulletproof.check_in_place::<D, B>(&bulletproof.target, base_g, base_h)?;
+            Ok((folding, bulletproof.target.ct_eq(&target)))
+        } else {
+            Ok((bulletproof.check_in_place::<D, B>(&target, base_g, base_h)?, Choice::from(1)))
+        }
+    }
+
+    /// Bulletproof target `P_2 - P_1` determined by the signature's commitments, leaving
+    /// the powers of `y^-1` in `scratch`. With the ring's `sums`, the `w` and `w d` terms
+    /// of every key are taken from them instead of multiplied out.
+    fn recompute_target<B>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        signature: &IncognitoSignature<C>,
+        challenges: &Challenges<C>,
+        scratch: &mut VerifyScratch<C>,
+        sums: Option<&RingSums<C>>
+    ) -> anyhow::Result<ProjectivePoint<C>>
+    where
+        B: CurveBackend<C>
+    {
+        let n = pks.len();
+        let IncognitoSignature { point_c_pk, point_a, point_s, point_s_pk, mu, nu, .. } = signature;
+        let Challenges { y, w, x, d, .. } = *challenges;
+
+        scratch.clear();
+        let VerifyScratch { vec_yn_inv, points, scalars, .. } = scratch;
+        let y_inv = invert_challenge::<C>(&y)?;
+        let mut yn_inv = Scalar::<C>::ONE;
+        for _ in 0..n {
+            vec_yn_inv.push(yn_inv);
+            yn_inv *= y_inv;
+        }
+        // A S^x C_pk^d S_pk^(x d) g^(-d nu) h^-mu <base1, -w> <vec_h, w + w^2 y^-n>
+        points.extend([*point_s, *point_c_pk, *point_s_pk, self.g, self.h]);
+        scalars.extend([x, d, x * d, -d * nu, -*mu]);
+        match sums {
+            Some([sum_g, sum_pk, sum_h]) => {
+                points.extend([*sum_g, *sum_pk, *sum_h]);
+                scalars.extend([-w, -w * d, w]);
+                points.extend_from_slice(&self.vec_h[..n]);
+                scalars.extend(vec_yn_inv.iter().map(|yn_inv| w * w * yn_inv));
+            }
+            None => for i in 0..n {
+                points.extend([self.vec_g[i], pks[i], self.vec_h[i]]);
+                scalars.extend([-w, -w * d, w + w * w * vec_yn_inv[i]]);
+            }
+        }
+        Ok(B::add(point_a, &B::msm(points, scalars)))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_equations<D, B>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        signature: &IncognitoSignature<C>,
+        signers: RangeInclusive<usize>,
+        challenges: &Challenges<C>,
+        scratch: &mut VerifyScratch<C>,
+        short_circuit: bool,
+        stored_target: bool,
+        sums: Option<&RingSums<C>>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest,
+        B: CurveBackend<C>
+    {
+        // Without `short_circuit` every equation is evaluated and only the conjunction
+        // is reported, so the time taken does not tell which one failed.
+        let mut valid = Choice::from(1);
+        let mut check = |equation: Choice, name: &str| -> anyhow::Result<()> {
+            anyhow::ensure!(!short_circuit || bool::from(equation), "{} equation failed", name);
+            valid &= equation;
+            Ok(())
+        };
+
+        check(self.linkage_equation::<B>(signature, challenges), "Linkage")?;
+        check(self.polynomial_equation::<B>(signature, challenges, pks.len(), signers), "Polynomial")?;
+        let (folding, target) = self.inner_product_equations::<D, B>(pks, signature, challenges, scratch, stored_target, sums)?;
+        check(folding, "Bulletproof")?;
+        check(target, "Target")?;
+
+        anyhow::ensure!(bool::from(valid), "Invalid incognito signature");
+        Ok(())
+    }
+
+    /// Counts the group operations `verify` spends on a ring of `ring_size` keys, with
+    /// an `msm` of `k` terms costing `k` multiplications and `k` additions as in
+    /// `GenericBackend`.
+    ///
+    /// Per ring member: three target MSM terms, one multiplication for each bulletproof
+    /// base and four from folding the generators; plus two multiplications per folding
+    /// round and a constant for the linkage, polynomial and final equations.
+    pub fn verify_cost(ring_size: usize) -> VerifyCost {
+        let n = ring_size.max(1).next_power_of_two();
+        let rounds = n.trailing_zeros() as usize;
+        VerifyCost {
+            // 4 linkage + 4 polynomial + (5 + 3n) target + 2n bases + 4(n - 1) folds
+            // + 2 per round + 2 final bulletproof check
+            point_mults: 11 + 9 * n + 2 * rounds,
+            // 4 linkage + 4 polynomial + (6 + 3n) target + n bases + 4(n - 1) folds
+            // + 3 per round + 2 final bulletproof check
+            point_adds: 12 + 8 * n + 3 * rounds
+        }
+    }
+
+    /// Verifies several signatures at once, accepting only if all of them are valid.
+    ///
+    /// Every verification equation is weighted by a fresh random scalar and all of
+    /// them are summed into a single multi-scalar multiplication. Rings may have
+    /// different sizes: they all draw from the prefix of `vec_g`/`vec_h`, so the
+    /// coefficients of shared generators are merged per index.
+    pub fn verify_batch<D>(
+        &self,
+        batch: &[BatchItem<C>]
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let result = self.check_batch::<D>(batch);
+        #[cfg(feature = "metrics")]
+        record_verification(batch.iter().map(|(pks, _, _)| pks.len()), result.is_ok());
+        result
+    }
+
+    /// Runs every multi-scalar multiplication of `verify`, leaving only a point
+    /// comparison to `VerifyWork::finish`, so the expensive part can be moved to
+    /// another thread or device. Uses the randomized equation of `verify_batch`.
+    pub fn verify_prepare<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<VerifyWork<C>>
+    where
+        D: Digest
+    {
+        let ((points, scalars), (gen_points, gen_scalars)) = self.batch_terms::<D>(&[(pks, message, signature)])?;
+        Ok(VerifyWork {
+            left: <GenericBackend as CurveBackend<C>>::msm(&points, &scalars),
+            right: -<GenericBackend as CurveBackend<C>>::msm(&gen_points, &gen_scalars)
+        })
+    }
+
+    /// The equations `verify` checks, without checking them: the signature is valid
+    /// exactly when every returned `GroupEquation` holds. The shape checks and the
+    /// Fiat–Shamir challenges are done here, so only group arithmetic is deferred, and
+    /// an engine can fold the equations of many signatures into one batch.
+    pub fn verify_lazy<D>(
+        &self,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        signature: &IncognitoSignature<C>
+    ) -> anyhow::Result<Vec<GroupEquation<C>>>
+    where
+        D: Digest
+    {
+        self.precheck::<D>(pks, signature, 1)?;
+        let n = pks.len();
+        let Challenges { c, c_z, y, w, x, d } = self.challenges::<D>(pks, message, signature);
+        let IncognitoSignature { point_c_pk, point_r, point_r_z, s_z, s_beta, point_a, point_s, point_s_pk, point_t1, point_t2, taux, mu, nu, tx, bulletproof, .. } = signature;
+        let generator = ProjectivePoint::<C>::generator();
+
+        let vec_yn_inv = Self::build_vec_yn(n, &invert_challenge::<C>(&y)?);
+        let bulletproof_base1: Vec<_> = (0..n).map(|i| self.vec_g[i] + pks[i] * d).collect();
+        let bulletproof_base2: Vec<_> = (0..n).map(|i| self.vec_h[i] * vec_yn_inv[i]).collect();
+        let vec_x = bulletproof.round_challenges::<D>(&bulletproof_base1, &bulletproof_base2)?;
+        let vec_x_inv = batch_invert(&vec_x).ok_or_else(|| anyhow::anyhow!("Zero challenge"))?;
+        // Coefficient of the i-th base in the fully folded generator.
+        let mut vec_s = vec![Scalar::<C>::ONE];
+        for (x, x_inv) in vec_x.iter().zip(vec_x_inv.iter()) {
+            vec_s = vec_s.iter().flat_map(|s| [*s * x_inv, *s * x]).collect();
+        }
+        let t0 = Self::compute_t0(n, 1, &w, &Self::build_vec_yn(n, &y).iter().sum());
+
+        // Linkage: G^s_z g^(s_beta c) R^-c_z C_pk^(-c_z c) = R_z
+        let linkage = GroupEquation {
+            points: vec![generator, self.g, *point_r, *point_c_pk],
+            scalars: vec![*s_z, *s_beta * c, -c_z, -c_z * c],
+            expected: *point_r_z
+        };
+
+        // Polynomial: G^(tx - t0) h^taux T1^-x T2^(-x^2) = 1
+        let polynomial = GroupEquation {
+            points: vec![generator, self.h, *point_t1, *point_t2],
+            scalars: vec![*tx - t0, *taux, -x, -x * x],
+            expected: ProjectivePoint::<C>::identity()
+        };
+
+        // Target: A S^x C_pk^d S_pk^(x d) g^(-d nu) h^-mu <base1, -w> <vec_h, w + w^2 y^-n> = P
+        let mut target = GroupEquation {
+            points: vec![*point_a, *point_s, *point_c_pk, *point_s_pk, self.g, self.h],
+            scalars: vec![Scalar::<C>::ONE, x, d, x * d, -d * nu, -*mu],
+            expected: bulletproof.target
+        };
+
+        // Inner product: base1^(l s) base2^(r s^-1) prod(L^(-x^2) R^(-x^-2)) = P
+        let mut inner_product = GroupEquation {
+            points: Vec::with_capacity(3 * n + 2 * vec_x.len()),
+            scalars: Vec::with_capacity(3 * n + 2 * vec_x.len()),
+            expected: bulletproof.target
+        };
+        for (j, (x, x_inv)) in vec_x.iter().zip(vec_x_inv.iter()).enumerate() {
+            inner_product.points.extend([bulletproof.vec_point_l[j], bulletproof.vec_point_r[j]]);
+            inner_product.scalars.extend([-*x * x, -*x_inv * x_inv]);
+        }
+
+        for i in 0..n {
+            target.points.extend([self.vec_g[i], pks[i], self.vec_h[i]]);
+            target.scalars.extend([-w, -w * d, w + w * w * vec_yn_inv[i]]);
+
+            let s_l = bulletproof.l * vec_s[i];
+            inner_product.points.extend([self.vec_g[i], pks[i], self.vec_h[i]]);
+            inner_product.scalars.extend([s_l, s_l * d, bulletproof.r * vec_s[n - 1 - i] * vec_yn_inv[i]]);
+        }
+
+        Ok(vec![linkage, polynomial, target, inner_product])
+    }
+
+    fn check_batch<D>(
+        &self,
+        batch: &[BatchItem<C>]
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let ((mut points, mut scalars), (gen_points, gen_scalars)) = self.batch_terms::<D>(batch)?;
+        points.extend(gen_points);
+        scalars.extend(gen_scalars);
+        anyhow::ensure!(
+            bool::from(<GenericBackend as CurveBackend<C>>::msm(&points, &scalars).is_identity()),
+            "Invalid incognito signature in batch"
+        );
+        Ok(())
+    }
+
+    /// Randomly weighted terms of all verification equations in `batch`, split into
+    /// those over signatures and rings and those over the fixed generators.
+    fn batch_terms<D>(
+        &self,
+        batch: &[BatchItem<C>]
+    ) -> anyhow::Result<(Terms<C>, Terms<C>)>
+    where
+        D: Digest
+    {
+        check_digest_size::<C, D>()?;
+        let mut rng = rand::rngs::ThreadRng::default();
+
+        let mut coef_gen = Scalar::<C>::ZERO;
+        let mut coef_g = Scalar::<C>::ZERO;
+        let mut coef_h = Scalar::<C>::ZERO;
+        let mut coef_vec_g = vec![Scalar::<C>::ZERO; MAXN];
+        let mut coef_vec_h = vec![Scalar::<C>::ZERO; MAXN];
+        let mut points = Vec::new();
+        let mut scalars = Vec::new();
+
+        // Challenges (c, c_z, y, w, x, d) of every signature, so that all `y` and all
+        // bulletproof round challenges can each be inverted with a single inversion.
+        let mut vec_challenges = Vec::with_capacity(batch.len());
+        for (pks, message, signature) in batch {
+            let n = pks.len();
+            ensure_nonempty::<C>(pks)?;
+            anyhow::ensure!(n <= MAXN, "Ring size {} out of range", n);
+            signature.structural_check(n)?;
+            ensure_distinct::<C>(pks)?;
+
+            let Challenges { c, c_z, y, w, x, d } = self.challenges::<D>(pks, message, signature);
+            vec_challenges.push((c, c_z, y, w, x, d));
+        }
+        let vec_y: Vec<_> = vec_challenges.iter().map(|(_, _, y, ..)| *y).collect();
+        let vec_y_inv = batch_invert(&vec_y).ok_or_else(|| anyhow::anyhow!("Zero challenge in batch"))?;
+
+        let mut vec_rounds = Vec::with_capacity(batch.len());
+        for ((pks, _, signature), (y_inv, (.., d))) in batch.iter().zip(vec_y_inv.iter().zip(vec_challenges.iter())) {
+            let n = pks.len();
+            let vec_yn_inv = Self::build_vec_yn(n, y_inv);
+            let bulletproof_base1: Vec<_> = (0..n).map(|i| self.vec_g[i] + pks[i] * d).collect();
+            Self::debug_assert_distinct_bases(&bulletproof_base1);
+            let bulletproof_base2: Vec<_> = (0..n).map(|i| self.vec_h[i] * vec_yn_inv[i]).collect();
+            let vec_x = signature.bulletproof.round_challenges::<D>(&bulletproof_base1, &bulletproof_base2)?;
+            vec_rounds.push((vec_yn_inv, vec_x));
+        }
+        let all_x: Vec<_> = vec_rounds.iter().flat_map(|(_, vec_x)| vec_x.iter().copied()).collect();
+        let mut all_x_inv = batch_invert(&all_x).ok_or_else(|| anyhow::anyhow!("Zero challenge in batch"))?.into_iter();
+
+        for ((pks, _, signature), ((c, c_z, y, w, x, d), (vec_yn_inv, vec_x))) in batch.iter().zip(vec_challenges.into_iter().zip(vec_rounds)) {
+            let n = pks.len();
+            let IncognitoSignature {
+                point_c_pk,
+                point_r,
+                point_r_z,
+                s_z,
+                s_beta,
+                point_a,
+                point_s,
+                point_s_pk,
+                point_t1,
+                point_t2,
+                taux,
+                mu,
+                nu,
+                tx,
+                bulletproof
+            } = signature;
+
+            let t0 = Self::compute_t0(n, 1, &w, &Self::build_vec_yn(n, &y).iter().sum());
+
+            let vec_x_inv: Vec<_> = all_x_inv.by_ref().take(vec_x.len()).collect();
+
+            // Coefficient of the i-th base in the fully folded generator.
+            let mut vec_s = vec![Scalar::<C>::ONE];
+            for (x, x_inv) in vec_x.iter().zip(vec_x_inv.iter()) {
+                vec_s = vec_s.iter().flat_map(|s| [*s * x_inv, *s * x]).collect();
+            }
+
+            // Linkage: g^s_z g^(s_beta c) = R_z R^c_z C_pk^(c_z c)
+            let a1 = Scalar::<C>::random(&mut rng);
+            coef_gen += a1 * s_z;
+            coef_g += a1 * s_beta * c;
+            points.extend([*point_r_z, *point_r, *point_c_pk]);
+            scalars.extend([-a1, -a1 * c_z, -a1 * c_z * c]);
+
+            // Polynomial: g^tx h^taux = g^t0 T1^x T2^(x^2)
+            let a2 = Scalar::<C>::random(&mut rng);
+            coef_gen += a2 * (*tx - t0);
+            coef_h += a2 * taux;
+            points.extend([*point_t1, *point_t2]);
+            scalars.extend([-a2 * x, -a2 * x * x]);
+
+            // Target: P = A S^x C_pk^d S_pk^(x d) <base1, -w> <vec_h, w + w^2 y^-n> - g^(d nu) h^mu
+            let a3 = Scalar::<C>::random(&mut rng);
+            coef_g += a3 * d * nu;
+            coef_h += a3 * mu;
+            points.extend([bulletproof.target, *point_a, *point_s, *point_c_pk, *point_s_pk]);
+            scalars.extend([a3, -a3, -a3 * x, -a3 * d, -a3 * x * d]);
+
+            // Inner product: P prod(L^(x^2) R^(x^-2)) = base1^(l s) base2^(r s^-1)
+            let a4 = Scalar::<C>::random(&mut rng);
+            points.push(bulletproof.target);
+            scalars.push(a4);
+            for j in 0..vec_x.len() {
+                points.extend([bulletproof.vec_point_l[j], bulletproof.vec_point_r[j]]);
+                scalars.extend([a4 * vec_x[j] * vec_x[j], a4 * vec_x_inv[j] * vec_x_inv[j]]);
+            }
+
+            let vec_s_inv: Vec<_> = vec_s.iter().rev().copied().collect();
+            for i in 0..n {
+                let s_l = a4 * bulletproof.l * vec_s[i];
+                coef_vec_g[i] += a3 * w - s_l;
+                points.push(pks[i]);
+                scalars.push(a3 * w * d - s_l * d);
+                coef_vec_h[i] += -a3 * (w + w * w * vec_yn_inv[i]) - a4 * bulletproof.r * vec_s_inv[i] * vec_yn_inv[i];
+            }
+        }
+
+        let mut gen_points = vec![ProjectivePoint::<C>::generator(), self.g, self.h];
+        let mut gen_scalars = vec![coef_gen, coef_g, coef_h];
+        gen_points.extend(self.vec_g.iter().chain(self.vec_h.iter()).copied());
+        gen_scalars.extend(coef_vec_g.into_iter().chain(coef_vec_h));
+
+        Ok(((points, scalars), (gen_points, gen_scalars)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <C: CurveArithmetic, const MAXN: usize> IncognitoParams<C, MAXN>
+where
+    AffinePoint<C>: Serialize + DeserializeOwned,
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Writes the same bytes as `bincode::serialize`, one generator at a time, so large
+    /// params are never held in memory a second time as affine points.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> anyhow::Result<()> {
+        bincode::serialize_into(&mut writer, &self.g.to_affine())?;
+        bincode::serialize_into(&mut writer, &self.h.to_affine())?;
+        for points in [&self.vec_g, &self.vec_h] {
+            bincode::serialize_into(&mut writer, &(MAXN as u64))?;
+            for point in points {
+                bincode::serialize_into(&mut writer, &point.to_affine())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inverse of `write_to`, also accepting the output of `bincode::serialize`.
+    pub fn read_from<R: Read>(mut reader: R) -> anyhow::Result<Self> {
+        let g = Self::read_point(&mut reader)?;
+        let h = Self::read_point(&mut reader)?;
+        let mut read_array = || -> anyhow::Result<[ProjectivePoint<C>; MAXN]> {
+            let len: u64 = bincode::deserialize_from(&mut reader)?;
+            anyhow::ensure!(len == MAXN as u64, "Params hold {} generators, expected {}", len, MAXN);
+            let points = (0..MAXN).map(|_| Self::read_point(&mut reader)).collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(points.try_into().unwrap_or_else(|_| unreachable!("exactly MAXN points were read")))
+        };
+        let vec_g = read_array()?;
+        let vec_h = read_array()?;
+        Ok(Self {
+            g,
+            h,
+            vec_g,
+            vec_h
+        })
+    }
+
+    fn read_point<R: Read>(reader: &mut R) -> anyhow::Result<ProjectivePoint<C>> {
+        let point: AffinePoint<C> = bincode::deserialize_from(reader)?;
+        Ok(crate::serde_util::point_serde::validate::<_, bincode::Error>(ProjectivePoint::<C>::from(point))?)
+    }
+}
+
+impl <C: CurveArithmetic> IncognitoSignature<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Commitment `point_c_pk` to the signer's key, see `IncognitoParams::key_commitment`.
+    pub fn point_c_pk(&self) -> &ProjectivePoint<C> {
+        &self.point_c_pk
+    }
+
+    /// Nonce commitment of the converted Schnorr signature.
+    pub fn point_r(&self) -> &ProjectivePoint<C> {
+        &self.point_r
+    }
+
+    /// Cheap shape checks that `verify` runs before any group operation.
+    pub fn structural_check(&self, ring_size: usize) -> Result<(), StructuralError> {
+        let expected = required_rounds(ring_size).ok_or(StructuralError::RingSize(ring_size))? as usize;
+        if bool::from(self.point_r.is_identity()) {
+            return Err(StructuralError::IdentityPoint("R"));
+        }
+        let (left, right) = (self.bulletproof.vec_point_l.len(), self.bulletproof.vec_point_r.len());
+        if left != expected || right != expected {
+            return Err(StructuralError::BulletproofRounds { expected, left, right });
+        }
+        Ok(())
+    }
+
+    /// Checks whether `pk` is the key hidden in `point_c_pk` under the opening `beta`,
+    /// and that the commitment is bound to the Schnorr signature on `message`.
+    /// This does not check ring membership; use `IncognitoParams::verify` for that.
+    pub fn could_be_signer<D, const MAXN: usize>(
+        &self,
+        params: &IncognitoParams<C, MAXN>,
+        pk: &ProjectivePoint<C>,
+        message: &[u8],
+        beta: &Scalar<C>
+    ) -> bool
+    where
+        D: Digest
+    {
+        let c = SchnorrSignature::<C>::challenge::<D>(&self.point_r, message, &mut |_| ());
+        let c_z = IncognitoParams::<C, MAXN>::challenge_cz::<D>(&self.point_r_z, &self.point_c_pk, &mut no_tap);
+        let linked = ProjectivePoint::<C>::generator() * self.s_z + params.g * self.s_beta * c
+            == self.point_r_z + self.point_r * c_z + self.point_c_pk * c_z * c;
+        linked && params.key_commitment().verify_opening(&self.point_c_pk, pk, beta)
+    }
+
+    /// Disavowal showing `blacklisted_pk` did not make this signature, from the opening
+    /// `beta` returned by `convert_with_opening`. Fails if `blacklisted_pk` is the signer.
+    pub fn prove_not_signer<const MAXN: usize>(
+        &self,
+        params: &IncognitoParams<C, MAXN>,
+        beta: &Scalar<C>,
+        blacklisted_pk: &ProjectivePoint<C>
+    ) -> anyhow::Result<Disavowal<C>> {
+        anyhow::ensure!(!params.key_commitment().verify_opening(&self.point_c_pk, blacklisted_pk, beta), "Blacklisted key is the signer");
+        Ok(Disavowal { beta: *beta })
+    }
+
+    /// Checks that `disavowal` opens `point_c_pk` to a member of `pks` other than
+    /// `blacklisted_pk`. Any `beta` opens the commitment to some key; opening it to another
+    /// ring member than the signer takes the discrete log of their difference to `g`.
+    /// This does not verify the signature; use `IncognitoParams::verify` for that.
+    pub fn verify_not_signer<D, const MAXN: usize>(
+        &self,
+        params: &IncognitoParams<C, MAXN>,
+        pks: &[ProjectivePoint<C>],
+        message: &[u8],
+        disavowal: &Disavowal<C>,
+        blacklisted_pk: &ProjectivePoint<C>
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let signer = params.key_commitment().open(&self.point_c_pk, &disavowal.beta).ok_or_else(|| anyhow::anyhow!("Cannot open key commitment"))?;
+        anyhow::ensure!(self.could_be_signer::<D, MAXN>(params, &signer, message, &disavowal.beta), "Linkage equation failed");
+        anyhow::ensure!(pks.contains(&signer), "Opened key is not in the ring");
+        anyhow::ensure!(signer != *blacklisted_pk, "Blacklisted key is the signer");
+        Ok(())
+    }
+}
+
+impl <C: CurveArithmetic> IncognitoSignature<C> {
+    /// Points in every signature whatever the ring size: eight commitments and the
+    /// bulletproof's target.
+    pub const NUM_POINTS: usize = 9;
+    /// Scalars in every signature: six responses and the bulletproof's final `l` and `r`.
+    pub const NUM_SCALARS: usize = 8;
+
+    /// `L` and `R` points of the bulletproof over a ring of `ring_size` keys, rounded up
+    /// to a power of two: `2 log2(n)`.
+    pub fn bulletproof_points(ring_size: usize) -> usize {
+        2 * ring_size.next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// Separates the bulletproof from the rest of the signature.
+    pub fn split(self) -> (LinkagePart<C>, BulletProof<C>) {
+        (
+            LinkagePart {
+                point_r: self.point_r,
+                point_c_pk: self.point_c_pk,
+                point_r_z: self.point_r_z,
+                s_z: self.s_z,
+                s_beta: self.s_beta,
+                point_a: self.point_a,
+                point_s: self.point_s,
+                point_s_pk: self.point_s_pk,
+                point_t1: self.point_t1,
+                point_t2: self.point_t2,
+                taux: self.taux,
+                mu: self.mu,
+                nu: self.nu,
+                tx: self.tx
+            },
+            self.bulletproof
+        )
+    }
+
+    /// Drops the bulletproof's target, see `CompactSignature`.
+    pub fn compact(self) -> CompactSignature<C> {
+        let (linkage, mut bulletproof) = self.split();
+        bulletproof.target = ProjectivePoint::<C>::identity();
+        CompactSignature { linkage, bulletproof }
+    }
+
+    /// Inverse of `split`.
+    pub fn from_parts(linkage: LinkagePart<C>, bulletproof: BulletProof<C>) -> Self {
+        Self {
+            point_r: linkage.point_r,
+            point_c_pk: linkage.point_c_pk,
+            point_r_z: linkage.point_r_z,
+            s_z: linkage.s_z,
+            s_beta: linkage.s_beta,
+            point_a: linkage.point_a,
+            point_s: linkage.point_s,
+            point_s_pk: linkage.point_s_pk,
+            point_t1: linkage.point_t1,
+            point_t2: linkage.point_t2,
+            taux: linkage.taux,
+            mu: linkage.mu,
+            nu: linkage.nu,
+            tx: linkage.tx,
+            bulletproof
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <C: CurveArithmetic> IncognitoSignature<C>
+where
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned,
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Bincode with varint lengths, see `serde_util::to_bytes_compact`.
+    pub fn to_bytes_compact(&self) -> anyhow::Result<Vec<u8>> {
+        crate::serde_util::to_bytes_compact(self)
+    }
+
+    pub fn from_bytes_compact(bytes: &[u8]) -> anyhow::Result<Self> {
+        crate::serde_util::from_bytes_compact(bytes)
+    }
+
+    /// Length under `bincode::serialize` of a signature over a ring of `ring_size` keys,
+    /// rounded up to a power of two, e.g. to cap the size of network frames.
+    pub fn serialized_len(ring_size: usize) -> usize {
+        let point = bincode::serialized_size(&ProjectivePoint::<C>::generator().to_affine()).expect("points serialize") as usize;
+        let scalar = bincode::serialized_size(&Into::<ScalarPrimitive<C>>::into(Scalar::<C>::ONE)).expect("scalars serialize") as usize;
+        let points = Self::NUM_POINTS + Self::bulletproof_points(ring_size);
+        // Lengths of `L` and `R`.
+        points * point + Self::NUM_SCALARS * scalar + 2 * 8
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl <'a, C: CurveArithmetic> arbitrary::Arbitrary<'a> for IncognitoSignature<C> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            point_r: crate::fuzzing::point::<C>(u)?,
+            point_c_pk: crate::fuzzing::point::<C>(u)?,
+            point_r_z: crate::fuzzing::point::<C>(u)?,
+            s_z: crate::fuzzing::scalar::<C>(u)?,
+            s_beta: crate::fuzzing::scalar::<C>(u)?,
+            point_a: crate::fuzzing::point::<C>(u)?,
+            point_s: crate::fuzzing::point::<C>(u)?,
+            point_s_pk: crate::fuzzing::point::<C>(u)?,
+            point_t1: crate::fuzzing::point::<C>(u)?,
+            point_t2: crate::fuzzing::point::<C>(u)?,
+            taux: crate::fuzzing::scalar::<C>(u)?,
+            mu: crate::fuzzing::scalar::<C>(u)?,
+            nu: crate::fuzzing::scalar::<C>(u)?,
+            tx: crate::fuzzing::scalar::<C>(u)?,
+            bulletproof: u.arbitrary()?
+        })
+    }
+}
+
+// impl <C: CurveArithmetic, const MAXN: usize> Serialize for IncognitoParams<C, MAXN> 
+// where
+//     AffinePoint<C>: Serialize + DeserializeOwned
+// {
+//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+//     where
+//         S: serde::Serializer {
+//         let mut state = serializer.serialize_struct("IncognitoParams", 4)?;
+//         state.serialize_field("g", &self.g.to_affine())?;
+//         state.serialize_field("h", &self.h.to_affine())?;
+//         state.serialize_field("vec_g", &self.vec_g.iter().map(|each| each.to_affine()).collect::<Vec<_>>())?;
+//         state.serialize_field("vec_h", &self.vec_g.iter().map(|each| each.to_affine()).collect::<Vec<_>>())?;
+//         state.end()
+//     }
+// }
+
+// impl<'de, C: CurveArithmetic, const MAXN: usize> Deserialize<'de> for IncognitoParams<C, MAXN>
+// where
+//     AffinePoint<C>: Deserialize<'de>
+// {
+//     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+//     where
+//         D: serde::Deserializer<'de> {
+//         #[derive(Deserialize)]
+//         #[serde(field_identifier, rename_all = "snake_case")]
 //         enum Field { G, H, VecG, VecH }
 
-//         struct SignatureVisitor<C: CurveArithmetic, const MAXN: usize> {
-//             _phantom1: PhantomData<C>,
-//         }
+//         struct SignatureVisitor<C: CurveArithmetic, const MAXN: usize> {
+//             _phantom1: PhantomData<C>,
+//         }
+
+//         impl <'de, C: CurveArithmetic, const MAXN: usize> Visitor<'de> for SignatureVisitor<C, MAXN> 
+//         where
+//             AffinePoint<C>: Deserialize<'de>
+//         {
+//             type Value = IncognitoParams::<C, MAXN>;
+        
+//             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+//                 formatter.write_str("Incognito Params")
+//             }
+
+//             fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+//                 where
+//                     A: serde::de::SeqAccess<'de>, {
+                
+//                 let g = ProjectivePoint::<C>::from(seq.next_element::<AffinePoint<C>>()?.ok_or_else(|| de::Error::invalid_length(0, &self))?);
+//                 let h = ProjectivePoint::<C>::from(seq.next_element::<AffinePoint<C>>()?.ok_or_else(|| de::Error::invalid_length(1, &self))?);
+//                 let vec_g = seq
+//                     .next_element::<Vec<AffinePoint<C>>>()?
+//                     .ok_or_else(|| de::Error::invalid_length(2, &self))?
+//                     .into_iter()
+//                     .map(|each| ProjectivePoint::<C>::from(each))
+//                     .collect::<Vec<_>>()
+//                     .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?;
+//                 let vec_h = seq
+//                     .next_element::<Vec<AffinePoint<C>>>()?
+//                     .ok_or_else(|| de::Error::invalid_length(3, &self))?
+//                     .into_iter()
+//                     .map(|each| ProjectivePoint::<C>::from(each))
+//                     .collect::<Vec<_>>()
+//                     .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?;
+//                 Ok(IncognitoParams{
+//                     g,
+//                     h,
+//                     vec_g,
+//                     vec_h
+//                 })
+                
+//             }
+
+//             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+//                 where
+//                     A: serde::de::MapAccess<'de>, {
+//                 let mut g = None;
+//                 let mut h = None;
+//                 let mut vec_g = None;
+//                 let mut vec_h = None;
+
+//                 while let Some(key) = map.next_key::<Field>()? {
+//                     match key {
+//                         Field::G => {
+//                             if g.is_some() {
+//                                 return Err(de::Error::duplicate_field("g"))
+//                             }
+//                             g = Some(ProjectivePoint::<C>::from(map.next_value::<AffinePoint<C>>()?));
+//                         },
+//                         Field::H => {
+//                             if h.is_some() {
+//                                 return Err(de::Error::duplicate_field("h"))
+//                             }
+//                             h = Some(ProjectivePoint::<C>::from(map.next_value::<AffinePoint<C>>()?));
+//                         },
+//                         Field::VecG => {
+//                             if vec_g.is_some() {
+//                                 return Err(de::Error::duplicate_field("vec_g"))
+//                             }
+//                             vec_g = Some(
+//                                 map.next_value::<Vec<AffinePoint<C>>>()?.into_iter()
+//                                 .map(|each| ProjectivePoint::<C>::from(each))
+//                                 .collect::<Vec<_>>()
+//                                 .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?
+//                             );
+//                         },
+//                         Field::VecH => {
+//                             if vec_h.is_some() {
+//                                 return Err(de::Error::duplicate_field("vec_h"))
+//                             }
+//                             vec_h = Some(
+//                                 map.next_value::<Vec<AffinePoint<C>>>()?.into_iter()
+//                                 .map(|each| ProjectivePoint::<C>::from(each))
+//                                 .collect::<Vec<_>>()
+//                                 .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?
+//                             );
+//                         }
+//                     }
+//                 }
+
+//                 Ok(IncognitoParams{
+//                     g: g.ok_or_else(|| de::Error::missing_field("g"))?,
+//                     h: h.ok_or_else(|| de::Error::missing_field("h"))?,
+//                     vec_g: vec_g.ok_or_else(|| de::Error::missing_field("vec_g"))?,
+//                     vec_h: vec_h.ok_or_else(|| de::Error::missing_field("vec_h"))?,
+//                 })
+//             }
+
+//         }
+
+//         const FIELDS: &'static [&'static str] = &["g", "h", "vec_g", "vec_h"];
+//         deserializer.deserialize_struct("IncognitoParams", FIELDS, SignatureVisitor::<C, MAXN>{_phantom1: PhantomData::default()})
+//     }
+// }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use elliptic_curve::Field;
+    use k256::{sha2::{Sha256, Sha512}, ProjectivePoint, Scalar, Secp256k1};
+    use rand::{rngs::ThreadRng, Rng};
+
+    use crate::{backend::GenericBackend, bulletproof::required_rounds, error::{StructuralError, VerifyError}, limiter::ConversionLimiter, range::RangeProof, schnorr::SchnorrSignature};
+
+    #[cfg(feature = "test-internals")]
+    use super::Challenges;
+    #[cfg(feature = "serde")]
+    use super::CompactSignature;
+    use super::{AmountCommitment, CommittedSignature, Disavowal, IncognitoParams, IncognitoSignature, VerifyScratch};
+
+    /// Digest of the signature produced by `test_convert_deterministic`.
+    #[cfg(feature = "serde")]
+    const PINNED: [u8; 32] = [
+        0x37, 0x63, 0xd0, 0x39, 0x9d, 0xb1, 0x52, 0x1e, 0xef, 0xfe, 0x84, 0xb5, 0xe9, 0x2f, 0xf2, 0x75,
+        0x02, 0x80, 0x33, 0x35, 0x37, 0x66, 0xc5, 0x28, 0xbd, 0xb2, 0xa3, 0xd0, 0x80, 0x39, 0x87, 0xa4
+    ];
+
+    #[test]
+
+    fn test_correctness() {
+        for n in [1, 2, 4, 8, 16] {
+            let i = ThreadRng::default().gen_range(0..n);
+            test_correctness_n(n, i);
+        }
+    }
+
+    fn test_correctness_n(n: usize, index: usize) {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 256>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+
+        signature.verify::<Sha256>(&pks[index], &message).unwrap();
+
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_convert_deterministic() {
+        use k256::sha2::Digest;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        use crate::schnorr::SchnorrSigner;
+
+        let n = 8;
+        let index = 3;
+        let mut rng = ChaCha20Rng::from_seed([7; 32]);
+        let params = IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(b"deterministic");
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let mut signer = SchnorrSigner::<Secp256k1, Sha256>::with_nonce(Scalar::random(&mut rng));
+        signer.update(&message);
+        let signature = signer.finalize(&sks[index]);
+
+        let incsig = params.convert_deterministic::<Sha256>([9; 32], &pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        let replayed = params.convert_deterministic::<Sha256>([9; 32], &pks, &message, &signature, index).unwrap();
+        let bytes = bincode::serialize(&incsig).unwrap();
+        assert_eq!(bytes, bincode::serialize(&replayed).unwrap());
+        assert_ne!(incsig, params.convert_deterministic::<Sha256>([10; 32], &pks, &message, &signature, index).unwrap());
+
+        let pinned: [u8; 32] = Sha256::digest(&bytes).into();
+        assert_eq!(pinned, PINNED);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let n = 4;
+        let index = 1;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+            assert!(params.verify::<Sha256>(&pks, &[0, 3, 6], &incsig).is_err());
+            params.verify_batch::<Sha256>(&[(&pks, &message, &incsig), (&pks, &message, &incsig)]).unwrap();
+        });
+
+        let snapshot: Vec<_> = snapshotter.snapshot().into_vec().into_iter()
+            .map(|(key, _, _, value)| (key.key().name().to_string(), value))
+            .collect();
+        let value = |name: &str| snapshot.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+        assert_eq!(value("signatures_verified_total"), Some(&DebugValue::Counter(3)));
+        assert_eq!(value("signatures_rejected_total"), Some(&DebugValue::Counter(1)));
+        match value("signature_ring_size") {
+            Some(DebugValue::Histogram(sizes)) => assert_eq!(sizes.len(), 4),
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_self_test() {
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        params.self_test::<Sha256>().unwrap();
+        IncognitoParams::<Secp256k1, 1>::new().self_test::<Sha256>().unwrap();
+        IncognitoParams::<Secp256k1, 5>::new().self_test::<Sha256>().unwrap();
+
+        let mut corrupted = params.clone();
+        corrupted.vec_h[3] = corrupted.vec_g[1];
+        assert!(corrupted.self_test::<Sha256>().is_err());
+
+        let mut corrupted = params.clone();
+        corrupted.g = ProjectivePoint::IDENTITY;
+        assert!(corrupted.self_test::<Sha256>().is_err());
+    }
+
+    #[test]
+    fn test_extend_to() {
+        let n = 4;
+        let index = 2;
+        let seed = b"incognito test params";
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::from_seed::<Sha256>(seed);
+        assert_eq!(params, IncognitoParams::<Secp256k1, 4>::from_seed::<Sha256>(seed));
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        let extended = params.extend_to::<Sha256, 8>(seed).unwrap();
+        assert_eq!(extended.vec_g[..4], params.vec_g);
+        assert_eq!(extended.vec_h[..4], params.vec_h);
+        assert_eq!(extended, IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(seed));
+        extended.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        assert!(params.extend_to::<Sha256, 8>(b"another seed").is_err());
+        assert!(extended.extend_to::<Sha256, 4>(seed).is_err());
+        assert!(IncognitoParams::<Secp256k1, 4>::new().extend_to::<Sha256, 8>(seed).is_err());
+    }
+
+    #[test]
+    fn test_different_maxn() {
+        let n = 64;
+        let seed = b"incognito test params";
+        let mut rng = ThreadRng::default();
+        let signer = IncognitoParams::<Secp256k1, 1024>::from_seed::<Sha256>(seed);
+        let verifier = IncognitoParams::<Secp256k1, 256>::from_seed::<Sha256>(seed);
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[17], &message);
+        let incsig = signer.convert::<Sha256>(&pks, &message, &signature, 17).unwrap();
+        assert!(verifier.compatible_with(&signer, n));
+        assert!(signer.compatible_with(&verifier, 256));
+        assert!(!signer.compatible_with(&verifier, 512));
+        verifier.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        assert_eq!(signer.truncate::<256>().unwrap(), verifier);
+        assert!(verifier.truncate::<1024>().is_err());
+
+        let unrelated = IncognitoParams::<Secp256k1, 256>::from_seed::<Sha256>(b"another seed");
+        assert!(!unrelated.compatible_with(&signer, n));
+        assert!(unrelated.verify::<Sha256>(&pks, &message, &incsig).is_err());
+    }
+
+    #[test]
+    fn test_prepared_ring() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let ring = params.prepare_ring::<Sha256>(&pks).unwrap();
+        assert_eq!(ring.pks(), &pks[..]);
+
+        let message = [0, 3, 6, 9];
+        for index in [0, 5] {
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+            params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+            params.verify_prepared::<Sha256>(&ring, &message, &incsig).unwrap();
+            assert!(params.verify::<Sha256>(&pks, &[0, 3, 6], &incsig).is_err());
+            assert!(params.verify_prepared::<Sha256>(&ring, &[0, 3, 6], &incsig).is_err());
+        }
+
+        let mut reordered = pks.clone();
+        reordered.swap(0, 1);
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[2], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 2).unwrap();
+        assert!(params.verify_prepared::<Sha256>(&params.prepare_ring(&reordered).unwrap(), &message, &incsig).is_err());
+        assert!(params.prepare_ring::<Sha256>(&pks[..6]).is_err());
+        assert!(params.prepare_ring::<Sha256>(&[pks[0], pks[0]]).is_err());
+
+        // The cached sums give the same target as multiplying out every key, for a
+        // tampered signature too.
+        let mut tampered = incsig.clone();
+        tampered.point_s += ProjectivePoint::GENERATOR;
+        assert!(params.verify_prepared::<Sha256>(&ring, &message, &tampered).is_err());
+        for incsig in [incsig, tampered] {
+            let challenges = params.challenges::<Sha256>(&pks, &message, &incsig);
+            let mut scratch = VerifyScratch::new();
+            assert_eq!(
+                params.recompute_target::<GenericBackend>(&pks, &incsig, &challenges, &mut scratch, Some(&ring.sums)).unwrap(),
+                params.recompute_target::<GenericBackend>(&pks, &incsig, &challenges, &mut scratch, None).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_prefix() {
+        let n = 12;
+        let ring_len = 8;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 16>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let full_keys: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[6], &message);
+        let incsig = params.convert::<Sha256>(&full_keys[..ring_len], &message, &signature, 6).unwrap();
+        params.verify::<Sha256>(&full_keys[..ring_len], &message, &incsig).unwrap();
+        params.verify_prefix::<Sha256>(&full_keys, ring_len, &message, &incsig).unwrap();
+        assert!(params.verify_prefix::<Sha256>(&full_keys, ring_len, &[0, 3, 6], &incsig).is_err());
+        assert!(params.verify_prefix::<Sha256>(&full_keys, 4, &message, &incsig).is_err());
+        assert!(params.verify_prefix::<Sha256>(&full_keys, 16, &message, &incsig).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_compact_signature() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[3], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 3).unwrap();
+        let compact = incsig.clone().compact();
+        let bytes = bincode::serialize(&compact).unwrap();
+        assert_eq!(bytes.len() + 41, bincode::serialize(&incsig).unwrap().len());
+
+        let compact: CompactSignature<Secp256k1> = bincode::deserialize(&bytes).unwrap();
+        params.verify_compact::<Sha256>(&pks, &message, &compact).unwrap();
+        assert!(params.verify_compact::<Sha256>(&pks, &[0, 3, 6], &compact).is_err());
+        assert_eq!(params.expand::<Sha256>(&pks, &message, compact.clone()).unwrap(), incsig);
+
+        let mut tampered = compact.clone();
+        tampered.linkage.point_a += ProjectivePoint::GENERATOR;
+        assert!(params.verify_compact::<Sha256>(&pks, &message, &tampered).is_err());
+        let mut tampered = compact.clone();
+        tampered.bulletproof.l += Scalar::ONE;
+        assert!(params.verify_compact::<Sha256>(&pks, &message, &tampered).is_err());
+        let mut tampered = compact;
+        tampered.bulletproof.vec_point_l[0] += ProjectivePoint::GENERATOR;
+        assert!(params.verify_compact::<Sha256>(&pks, &message, &tampered).is_err());
+
+        // A full signature still has to carry the right target.
+        let mut tampered = incsig;
+        tampered.bulletproof.target += ProjectivePoint::GENERATOR;
+        assert!(params.verify::<Sha256>(&pks, &message, &tampered).is_err());
+    }
+
+    #[test]
+    fn test_standard_generators() {
+        let params = IncognitoParams::<Secp256k1, 8>::standard_generators::<Sha256>(b"Bulletproof");
+        assert_eq!(params, IncognitoParams::<Secp256k1, 8>::standard_generators::<Sha256>(b"Bulletproof"));
+        assert_ne!(params, IncognitoParams::<Secp256k1, 8>::standard_generators::<Sha256>(b"Other"));
+        assert_ne!(params, IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(b"Bulletproof"));
+        for point in [params.g, params.h] {
+            assert!(!params.vec_g.contains(&point) && !params.vec_h.contains(&point));
+        }
+        assert_ne!(params.g, params.h);
+        params.self_test::<Sha256>().unwrap();
+    }
+
+    #[test]
+    fn test_verify_with_ring() {
+        let n = 8;
+        let index = 1;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let first = params.verify_with_ring::<Sha256>(&pks, &message, &incsig).unwrap();
+        let second = params.verify_with_ring::<Sha256>(&pks, &message, &incsig).unwrap();
+        assert_eq!(first.ring_hash(), second.ring_hash());
+        assert_eq!(first, second);
+
+        let other = params.convert::<Sha256>(&pks[..4], &message, &signature, index).unwrap();
+        let verified = params.verify_with_ring::<Sha256>(&pks[..4], &message, &other).unwrap();
+        assert_ne!(verified.ring_hash(), first.ring_hash());
+        assert_eq!(verified.message_hash(), first.message_hash());
+        assert!(params.verify_with_ring::<Sha256>(&pks[..4], &message, &incsig).is_err());
+    }
+
+    #[test]
+    fn test_digest_sizes() {
+        let n = 8;
+        let index = 4;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        assert!(params.verify::<Sha512>(&pks, &message, &incsig).is_err());
+
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha512>(&sks[index], &message);
+        signature.verify::<Sha512>(&pks[index], &message).unwrap();
+        assert!(signature.verify::<Sha256>(&pks[index], &message).is_err());
+        let incsig = params.convert::<Sha512>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha512>(&pks, &message, &incsig).unwrap();
+        assert!(params.verify::<Sha256>(&pks, &message, &incsig).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+
+    fn test_serialization() {
+        use crate::incognito::IncognitoSignature;
+
+        let n = 256;
+        let index = 128;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 256>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+
+        signature.verify::<Sha256>(&pks[index], &message).unwrap();
+
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+
+        let params_new: IncognitoParams::<Secp256k1, 256> = bincode::deserialize(&bincode::serialize(&params).unwrap()).unwrap();
+        let incsig_new: IncognitoSignature::<Secp256k1> = bincode::deserialize(&bincode::serialize(&incsig).unwrap()).unwrap();
+        params_new.verify::<Sha256>(&pks, &message, &incsig_new).unwrap();
+        assert_eq!(params, params_new);
+    }
+
+    #[test]
+    fn test_from_rng() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let params = IncognitoParams::<Secp256k1, 16>::from_rng(&mut ChaCha20Rng::from_seed([7; 32]));
+        assert_eq!(params, IncognitoParams::<Secp256k1, 16>::from_rng(&mut ChaCha20Rng::from_seed([7; 32])));
+        assert_ne!(params, IncognitoParams::<Secp256k1, 16>::from_rng(&mut ChaCha20Rng::from_seed([8; 32])));
+        assert_ne!(params, IncognitoParams::<Secp256k1, 16>::new());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_malformed_inputs() {
+        use crate::incognito::IncognitoSignature;
+
+        let n = 8;
+        let index = 2;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+
+        // Params of another MAXN.
+        let bytes = bincode::serialize(&IncognitoParams::<Secp256k1, 4>::new()).unwrap();
+        assert!(bincode::deserialize::<IncognitoParams<Secp256k1, 8>>(&bytes).is_err());
+
+        // Truncated signature.
+        let bytes = bincode::serialize(&incsig).unwrap();
+        for len in [0, 1, bytes.len() / 2, bytes.len() - 1] {
+            assert!(bincode::deserialize::<IncognitoSignature<Secp256k1>>(&bytes[..len]).is_err());
+        }
+
+        // Bulletproofs whose rounds do not match the ring.
+        let mut uneven = incsig.clone();
+        uneven.bulletproof.vec_point_r.push(ProjectivePoint::GENERATOR);
+        let mut long = incsig.clone();
+        long.bulletproof.vec_point_l.extend([ProjectivePoint::GENERATOR; 40]);
+        long.bulletproof.vec_point_r.extend([ProjectivePoint::GENERATOR; 40]);
+        for malformed in [uneven, long] {
+            let decoded: IncognitoSignature<Secp256k1> = bincode::deserialize(&bincode::serialize(&malformed).unwrap()).unwrap();
+            assert!(params.verify::<Sha256>(&pks, &message, &decoded).is_err());
+            assert!(params.verify_batch::<Sha256>(&[(&pks, &message, &decoded)]).is_err());
+        }
+
+        // Rings the params cannot hold.
+        assert!(params.verify::<Sha256>(&[], &message, &incsig).is_err());
+        let long_ring: Vec<_> = (0..16).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        assert!(params.verify::<Sha256>(&long_ring, &message, &incsig).is_err());
+        assert!(params.convert::<Sha256>(&long_ring, &message, &signature, index).is_err());
+        assert!(params.convert::<Sha256>(&pks, &message, &signature, n).is_err());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_prover_secrets_zeroize() {
+        use elliptic_curve::zeroize::Zeroize;
+
+        // `Drop` wipes the secrets through the same `zeroize`.
+        let mut secrets = super::ProverSecrets::<Secp256k1> {
+            beta: Scalar::ONE,
+            r_z: Scalar::ONE,
+            r_beta: Scalar::ONE,
+            alpha: Scalar::ONE,
+            rho: Scalar::ONE,
+            zeta: Scalar::ONE,
+            tau1: Scalar::ONE,
+            tau2: Scalar::ONE,
+            vec_b: vec![Scalar::ONE; 4],
+            vec_a: vec![Scalar::ONE; 4],
+            vec_s_a: vec![Scalar::ONE; 4],
+            vec_s_b: vec![Scalar::ONE; 4],
+            vec_l: vec![Scalar::ONE; 4],
+            vec_r: vec![Scalar::ONE; 4]
+        };
+        Zeroize::zeroize(&mut secrets);
+        assert!([secrets.beta, secrets.r_z, secrets.r_beta, secrets.alpha, secrets.rho, secrets.zeta, secrets.tau1, secrets.tau2].iter().all(|secret| *secret == Scalar::ZERO));
+        assert!([&secrets.vec_b, &secrets.vec_a, &secrets.vec_s_a, &secrets.vec_s_b, &secrets.vec_l, &secrets.vec_r].iter().all(|secret| secret.is_empty()));
+    }
+
+    #[test]
+    fn test_convert_limited() {
+        let n = 8;
+        let index = 3;
+        let limit = 2;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let mut limiter = ConversionLimiter::new(limit, Duration::from_secs(3600));
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        // Failed conversions are refunded.
+        let mut duplicated = pks.clone();
+        duplicated[0] = duplicated[1];
+        for _ in 0..=limit {
+            assert!(params.convert_limited::<Sha256>(&mut limiter, &duplicated, &message, &signature, index).is_err());
+        }
+        for _ in 0..limit {
+            let incsig = params.convert_limited::<Sha256>(&mut limiter, &pks, &message, &signature, index).unwrap();
+            params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        }
+        assert!(params.convert_limited::<Sha256>(&mut limiter, &pks, &message, &signature, index).is_err());
+
+        let other = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index + 1], &message);
+        params.convert_limited::<Sha256>(&mut limiter, &pks, &message, &other, index + 1).unwrap();
+
+        // The limiter counts tags, which are bound to the params.
+        let tag = params.linkability_tag::<Sha256>(&pks[index]);
+        assert!(limiter.acquire(&tag).is_err());
+        assert_ne!(tag, IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(b"other").linkability_tag::<Sha256>(&pks[index]));
+        limiter.acquire(&params.linkability_tag::<Sha256>(&pks[index + 2])).unwrap();
+    }
+
+    #[test]
+    fn test_single_member_ring() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sk = Scalar::random(&mut rng);
+        let pks = [ProjectivePoint::GENERATOR * sk];
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 0).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        params.verify_batch::<Sha256>(&[(&pks, &message, &incsig)]).unwrap();
+        assert!(incsig.bulletproof.vec_point_l.is_empty());
+        assert!(params.verify::<Sha256>(&pks, &[0, 3, 6], &incsig).is_err());
+        assert!(params.verify::<Sha256>(&[ProjectivePoint::GENERATOR], &message, &incsig).is_err());
+        assert!(params.verify::<Sha256>(&[], &message, &incsig).is_err());
+        assert!(params.convert::<Sha256>(&[], &message, &signature, 0).is_err());
+    }
+
+    #[test]
+    fn test_ring_binding() {
+        let n = 4;
+        let index = 0;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..2 * n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        // Ring B keeps the signer and replaces everyone else.
+        let ring_a = &pks[..n];
+        let ring_b: Vec<_> = [pks[index]].into_iter().chain(pks[n + 1..].iter().copied()).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(ring_a, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(ring_a, &message, &incsig).unwrap();
+        assert!(params.verify::<Sha256>(&ring_b, &message, &incsig).is_err());
+        assert!(params.verify_batch::<Sha256>(&[(&ring_b, &message, &incsig)]).is_err());
+        assert!(params.verify_prepared::<Sha256>(&params.prepare_ring(&ring_b).unwrap(), &message, &incsig).is_err());
+        assert_ne!(
+            params.challenges::<Sha256>(ring_a, &message, &incsig).y,
+            params.challenges::<Sha256>(&ring_b, &message, &incsig).y
+        );
+    }
+
+    #[test]
+    fn test_empty_ring() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sk = Scalar::random(&mut rng);
+        let pks = [ProjectivePoint::GENERATOR * sk];
+
+        // A single-member signature has no folding rounds, like one over an empty ring would.
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 0).unwrap();
+        let errors = [
+            params.verify::<Sha256>(&[], &message, &incsig).unwrap_err(),
+            params.verify_constant_time::<Sha256>(&[], &message, &incsig).unwrap_err(),
+            params.verify_batch::<Sha256>(&[(&[], &message, &incsig)]).unwrap_err(),
+            params.convert::<Sha256>(&[], &message, &signature, 0).unwrap_err()
+        ];
+        for error in errors {
+            assert_eq!(error.downcast_ref::<VerifyError>(), Some(&VerifyError::EmptyRing));
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        let n = 5;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        // 2-of-5, with the ring padded to a power of two by keys nobody controls here.
+        let padding: Vec<_> = (0..3).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let ring = [pks.as_slice(), &padding].concat();
+
+        let message = [0, 3, 6, 9];
+        let incsig = params.convert_threshold::<Sha256>(&ring, &message, &[(sks[1], 1), (sks[4], 4)]).unwrap();
+        params.verify_threshold::<Sha256>(&ring, &message, &incsig, 2).unwrap();
+        params.verify_threshold::<Sha256>(&ring, &message, &incsig, 1).unwrap();
+        assert!(params.verify_threshold::<Sha256>(&ring, &message, &incsig, 3).is_err());
+        // `verify` still means a single signer.
+        assert!(params.verify::<Sha256>(&ring, &message, &incsig).is_err());
+
+        // Three signers meet a threshold of two, but not of four.
+        let triple = params.convert_threshold::<Sha256>(&ring, &message, &[(sks[0], 0), (sks[2], 2), (sks[3], 3)]).unwrap();
+        params.verify_threshold::<Sha256>(&ring, &message, &triple, 2).unwrap();
+        params.verify_threshold::<Sha256>(&ring, &message, &triple, 3).unwrap();
+        assert!(params.verify_threshold::<Sha256>(&ring, &message, &triple, 4).is_err());
+        assert!(params.verify_threshold::<Sha256>(&ring, &[0, 3, 6], &triple, 2).is_err());
+
+        let single = params.convert_threshold::<Sha256>(&ring, &message, &[(sks[1], 1)]).unwrap();
+        assert!(params.verify_threshold::<Sha256>(&ring, &message, &single, 2).is_err());
+        params.verify::<Sha256>(&ring, &message, &single).unwrap();
+
+        assert!(params.convert_threshold::<Sha256>(&ring, &message, &[(sks[1], 1), (sks[1], 1)]).is_err());
+        assert!(params.convert_threshold::<Sha256>(&ring, &message, &[(sks[1], 1), (sks[2], 4)]).is_err());
+    }
+
+    #[test]
+    fn test_reconvert() {
+        let n = 8;
+        let index = 6;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let (incsig, beta) = params.convert_with_opening::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let (fresh, fresh_beta) = params.reconvert::<Sha256>(&pks, &message, &signature, &incsig, &beta).unwrap();
+        params.verify::<Sha256>(&pks, &message, &fresh).unwrap();
+        assert_ne!(fresh, incsig);
+        assert_ne!(fresh.point_c_pk, incsig.point_c_pk);
+        assert!(fresh.could_be_signer::<Sha256, 8>(&params, &pks[index], &message, &fresh_beta));
+
+        assert!(params.reconvert::<Sha256>(&pks, &message, &signature, &incsig, &fresh_beta).is_err());
+    }
+
+    #[test]
+    fn test_amount_commitment() {
+        let n = 8;
+        let index = 3;
+        let bits = 16;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 16>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let (incsig, commitment, blind) = params.convert_with_amount::<Sha256>(&pks, &message, &signature, index, 40000, bits).unwrap();
+        params.verify_with_amount::<Sha256>(&pks, &message, &incsig, &commitment, bits).unwrap();
+        assert_eq!(commitment.point_v, params.g * Scalar::from(40000u64) + params.h * blind);
+        assert!(params.verify_with_amount::<Sha256>(&pks, &message, &incsig, &commitment, 8).is_err());
+        assert!(params.verify_with_amount::<Sha256>(&pks, &[0, 3, 6], &incsig, &commitment, bits).is_err());
+
+        assert!(params.convert_with_amount::<Sha256>(&pks, &message, &signature, index, 1 << bits, bits).is_err());
+        assert!(params.convert_with_amount::<Sha256>(&pks, &message, &signature, index, 1, 12).is_err());
+
+        // An honest prover for an out-of-range amount cannot produce a valid proof.
+        let amount = (1u64 << bits) + 5;
+        let point_v = params.g * Scalar::from(amount) + params.h * blind;
+        let context = IncognitoParams::<Secp256k1, 16>::amount_context(&incsig, &message);
+        let generators = (&params.g, &params.h, &params.vec_g[..bits], &params.vec_h[..bits]);
+        let range_proof = RangeProof::<Secp256k1>::prove::<Sha256>(generators, &point_v, amount, &blind, &context);
+        assert!(range_proof.verify::<Sha256>(generators, &point_v, &context).is_err());
+        let forged = AmountCommitment { point_v, range_proof };
+        assert!(params.verify_with_amount::<Sha256>(&pks, &message, &incsig, &forged, bits).is_err());
+
+        // The commitment does not carry over to another signature.
+        let other = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        assert!(params.verify_with_amount::<Sha256>(&pks, &message, &other, &commitment, bits).is_err());
+    }
+
+    #[test]
+    fn test_could_be_signer() {
+        let n = 8;
+        let index = 2;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let (incsig, beta) = params.convert_with_opening::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        assert!(incsig.could_be_signer::<Sha256, 8>(&params, &pks[index], &message, &beta));
+        assert!(!incsig.could_be_signer::<Sha256, 8>(&params, &pks[index + 1], &message, &beta));
+        assert!(!incsig.could_be_signer::<Sha256, 8>(&params, &pks[index], &message, &Scalar::random(&mut rng)));
+        assert!(!incsig.could_be_signer::<Sha256, 8>(&params, &pks[index], &[0, 3, 6], &beta));
+    }
+
+    #[test]
+    fn test_not_signer() {
+        let n = 8;
+        let index = 2;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let (incsig, beta) = params.convert_with_opening::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let disavowal = incsig.prove_not_signer(&params, &beta, &pks[5]).unwrap();
+        incsig.verify_not_signer::<Sha256, 8>(&params, &pks, &message, &disavowal, &pks[5]).unwrap();
+        assert!(incsig.verify_not_signer::<Sha256, 8>(&params, &pks, &[0, 3, 6], &disavowal, &pks[5]).is_err());
+
+        // The signer cannot disavow themselves, nor open the commitment to another key.
+        assert!(incsig.prove_not_signer(&params, &beta, &pks[index]).is_err());
+        assert!(incsig.verify_not_signer::<Sha256, 8>(&params, &pks, &message, &disavowal, &pks[index]).is_err());
+        let forged = Disavowal { beta: Scalar::random(&mut rng) };
+        assert!(incsig.verify_not_signer::<Sha256, 8>(&params, &pks, &message, &forged, &pks[5]).is_err());
+    }
+
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn test_without_serde() {
+        // Built with `--no-default-features`: the core flow must not depend on serde.
+        test_correctness_n(4, 1);
+    }
+
+    #[cfg(feature = "lincomb")]
+    #[test]
+    fn test_backends_agree() {
+        use crate::backend::{GenericBackend, LinCombBackend};
+
+        let n = 16;
+        let index = 7;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 16>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+
+        params.verify_with_backend::<Sha256, GenericBackend>(&pks, &message, &incsig).unwrap();
+        params.verify_with_backend::<Sha256, LinCombBackend>(&pks, &message, &incsig).unwrap();
+        assert!(params.verify_with_backend::<Sha256, GenericBackend>(&pks, &[0, 3, 6], &incsig).is_err());
+        assert!(params.verify_with_backend::<Sha256, LinCombBackend>(&pks, &[0, 3, 6], &incsig).is_err());
+    }
+
+    #[test]
+    fn test_verify_cost() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use crate::backend::{CurveBackend, GenericBackend};
+
+        static MULTS: AtomicUsize = AtomicUsize::new(0);
+        static ADDS: AtomicUsize = AtomicUsize::new(0);
+        // Counts every operation `verify_with_backend` makes, its MSMs evaluated term by
+        // term as `GenericBackend` does.
+        struct CountingBackend;
+        impl CurveBackend<Secp256k1> for CountingBackend {
+            fn msm(points: &[ProjectivePoint], scalars: &[Scalar]) -> ProjectivePoint {
+                points.iter().zip(scalars.iter()).fold(ProjectivePoint::IDENTITY, |sum, (point, scalar)| Self::add(&sum, &Self::mul(point, scalar)))
+            }
+
+            fn mul(point: &ProjectivePoint, scalar: &Scalar) -> ProjectivePoint {
+                MULTS.fetch_add(1, Ordering::SeqCst);
+                <GenericBackend as CurveBackend<Secp256k1>>::mul(point, scalar)
+            }
+
+            fn add(a: &ProjectivePoint, b: &ProjectivePoint) -> ProjectivePoint {
+                ADDS.fetch_add(1, Ordering::SeqCst);
+                <GenericBackend as CurveBackend<Secp256k1>>::add(a, b)
+            }
+        }
+
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[0], &message);
+
+        for n in [1, 2, 8] {
+            let incsig = params.convert::<Sha256>(&pks[..n], &message, &signature, 0).unwrap();
+            MULTS.store(0, Ordering::SeqCst);
+            ADDS.store(0, Ordering::SeqCst);
+            params.verify_with_backend::<Sha256, CountingBackend>(&pks[..n], &message, &incsig).unwrap();
+            let counted = super::VerifyCost { point_mults: MULTS.load(Ordering::SeqCst), point_adds: ADDS.load(Ordering::SeqCst) };
+            assert_eq!(IncognitoParams::<Secp256k1, 8>::verify_cost(n), counted, "ring of {}", n);
+        }
+        assert_eq!(IncognitoParams::<Secp256k1, 8>::verify_cost(8), super::VerifyCost { point_mults: 89, point_adds: 85 });
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let mut rng = ThreadRng::default();
+        let values: Vec<_> = (0..9).map(|_| Scalar::random(&mut rng)).collect();
+        let inverses = super::batch_invert(&values).unwrap();
+        for (value, inverse) in values.iter().zip(inverses.iter()) {
+            assert_eq!(*inverse, value.invert().unwrap());
+        }
+        assert!(super::batch_invert::<Scalar>(&[]).unwrap().is_empty());
+        assert!(super::batch_invert(&[values[0], Scalar::ZERO]).is_none());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 64>::new();
+        let sks: Vec<_> = (0..64).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let mut messages = Vec::new();
+        let mut incsigs = Vec::new();
+        for (k, n) in [4, 16, 64, 16].into_iter().enumerate() {
+            let index = rng.gen_range(0..n);
+            let message = vec![k as u8, 3, 6, 9];
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            incsigs.push(params.convert::<Sha256>(&pks[..n], &message, &signature, index).unwrap());
+            messages.push(message);
+        }
+        let sizes = [4, 16, 64, 16];
+        let batch: Vec<_> = (0..4).map(|k| (&pks[..sizes[k]], &messages[k][..], &incsigs[k])).collect();
+        params.verify_batch::<Sha256>(&batch).unwrap();
+
+        let mut batch_invalid = batch.clone();
+        batch_invalid[1].1 = &messages[0];
+        assert!(params.verify_batch::<Sha256>(&batch_invalid).is_err());
+
+        let mut batch_invalid = batch.clone();
+        batch_invalid[2].0 = &pks[..32];
+        assert!(params.verify_batch::<Sha256>(&batch_invalid).is_err());
+    }
+
+    #[test]
+    fn test_verify_prepare() {
+        let n = 16;
+        let index = 9;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 16>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+        params.verify_prepare::<Sha256>(&pks, &message, &incsig).unwrap().finish().unwrap();
+
+        let wrong_message = [0, 3, 6];
+        assert!(params.verify::<Sha256>(&pks, &wrong_message, &incsig).is_err());
+        let work = params.verify_prepare::<Sha256>(&pks, &wrong_message, &incsig).unwrap();
+        assert!(work.finish().is_err());
+
+        let mut pks_swapped = pks.clone();
+        pks_swapped.swap(0, 1);
+        assert!(params.verify::<Sha256>(&pks_swapped, &message, &incsig).is_err());
+        assert!(params.verify_prepare::<Sha256>(&pks_swapped, &message, &incsig).and_then(|work| work.finish()).is_err());
+
+        assert!(params.verify::<Sha256>(&pks[..8], &message, &incsig).is_err());
+        assert!(params.verify_prepare::<Sha256>(&pks[..8], &message, &incsig).and_then(|work| work.finish()).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_scratch() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 16>::new();
+        let sks: Vec<_> = (0..16).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let mut scratch = VerifyScratch::with_capacity(4);
+        for (k, n) in [4, 16, 1, 8, 16, 2].into_iter().enumerate() {
+            let index = rng.gen_range(0..n);
+            let message = [k as u8, 3, 6, 9];
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            let incsig = params.convert::<Sha256>(&pks[..n], &message, &signature, index).unwrap();
 
-//         impl <'de, C: CurveArithmetic, const MAXN: usize> Visitor<'de> for SignatureVisitor<C, MAXN> 
-//         where
-//             AffinePoint<C>: Deserialize<'de>
-//         {
-//             type Value = IncognitoParams::<C, MAXN>;
-        
-//             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-//                 formatter.write_str("Incognito Params")
-//             }
+            params.verify::<Sha256>(&pks[..n], &message, &incsig).unwrap();
+            params.verify_with_scratch::<Sha256>(&pks[..n], &message, &incsig, &mut scratch).unwrap();
 
-//             fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-//                 where
-//                     A: serde::de::SeqAccess<'de>, {
-                
-//                 let g = ProjectivePoint::<C>::from(seq.next_element::<AffinePoint<C>>()?.ok_or_else(|| de::Error::invalid_length(0, &self))?);
-//                 let h = ProjectivePoint::<C>::from(seq.next_element::<AffinePoint<C>>()?.ok_or_else(|| de::Error::invalid_length(1, &self))?);
-//                 let vec_g = seq
-//                     .next_element::<Vec<AffinePoint<C>>>()?
-//                     .ok_or_else(|| de::Error::invalid_length(2, &self))?
-//                     .into_iter()
-//                     .map(|each| ProjectivePoint::<C>::from(each))
-//                     .collect::<Vec<_>>()
-//                     .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?;
-//                 let vec_h = seq
-//                     .next_element::<Vec<AffinePoint<C>>>()?
-//                     .ok_or_else(|| de::Error::invalid_length(3, &self))?
-//                     .into_iter()
-//                     .map(|each| ProjectivePoint::<C>::from(each))
-//                     .collect::<Vec<_>>()
-//                     .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?;
-//                 Ok(IncognitoParams{
-//                     g,
-//                     h,
-//                     vec_g,
-//                     vec_h
-//                 })
-                
-//             }
+            let wrong_message = [k as u8, 3, 6];
+            assert!(params.verify::<Sha256>(&pks[..n], &wrong_message, &incsig).is_err());
+            assert!(params.verify_with_scratch::<Sha256>(&pks[..n], &wrong_message, &incsig, &mut scratch).is_err());
+        }
+    }
 
-//             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-//                 where
-//                     A: serde::de::MapAccess<'de>, {
-//                 let mut g = None;
-//                 let mut h = None;
-//                 let mut vec_g = None;
-//                 let mut vec_h = None;
+    #[test]
+    fn test_same_message() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
 
-//                 while let Some(key) = map.next_key::<Field>()? {
-//                     match key {
-//                         Field::G => {
-//                             if g.is_some() {
-//                                 return Err(de::Error::duplicate_field("g"))
-//                             }
-//                             g = Some(ProjectivePoint::<C>::from(map.next_value::<AffinePoint<C>>()?));
-//                         },
-//                         Field::H => {
-//                             if h.is_some() {
-//                                 return Err(de::Error::duplicate_field("h"))
-//                             }
-//                             h = Some(ProjectivePoint::<C>::from(map.next_value::<AffinePoint<C>>()?));
-//                         },
-//                         Field::VecG => {
-//                             if vec_g.is_some() {
-//                                 return Err(de::Error::duplicate_field("vec_g"))
-//                             }
-//                             vec_g = Some(
-//                                 map.next_value::<Vec<AffinePoint<C>>>()?.into_iter()
-//                                 .map(|each| ProjectivePoint::<C>::from(each))
-//                                 .collect::<Vec<_>>()
-//                                 .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?
-//                             );
-//                         },
-//                         Field::VecH => {
-//                             if vec_h.is_some() {
-//                                 return Err(de::Error::duplicate_field("vec_h"))
-//                             }
-//                             vec_h = Some(
-//                                 map.next_value::<Vec<AffinePoint<C>>>()?.into_iter()
-//                                 .map(|each| ProjectivePoint::<C>::from(each))
-//                                 .collect::<Vec<_>>()
-//                                 .try_into().map_err(|_| de::Error::custom("invalid data for [Point; MAXN]"))?
-//                             );
-//                         }
-//                     }
-//                 }
+        let message = [0, 3, 6, 9];
+        let other_message = [0, 3, 6];
+        let sign = |index: usize, message: &[u8]| {
+            let commitment = IncognitoParams::<Secp256k1, 8>::message_commitment::<Sha256>(message);
+            let committed_message = IncognitoParams::<Secp256k1, 8>::committed_message(&commitment);
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &committed_message);
+            let committed = params.convert_with_message_commitment::<Sha256>(&pks, &commitment, &signature, index).unwrap();
+            params.verify_committed::<Sha256>(&pks, &committed).unwrap();
+            committed
+        };
+        let first = sign(2, &message);
+        let second = sign(5, &message);
+        let third = sign(2, &other_message);
+
+        assert!(first.same_message(&second));
+        assert!(!first.same_message(&third));
+        assert!(!second.same_message(&third));
+
+        // The commitment is part of what was signed, so it cannot be swapped out.
+        let mut moved = first.clone();
+        moved.message_commitment = third.message_commitment;
+        assert!(params.verify_committed::<Sha256>(&pks, &moved).is_err());
+        assert!(params.verify::<Sha256>(&pks, &message, &first.signature).is_err());
+
+        // A signature over the plaintext cannot be passed off as a committed one.
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[2], &message);
+        let plain = params.convert::<Sha256>(&pks, &message, &signature, 2).unwrap();
+        let forged = CommittedSignature { signature: plain, message_commitment: first.message_commitment };
+        assert!(params.verify_committed::<Sha256>(&pks, &forged).is_err());
+    }
 
-//                 Ok(IncognitoParams{
-//                     g: g.ok_or_else(|| de::Error::missing_field("g"))?,
-//                     h: h.ok_or_else(|| de::Error::missing_field("h"))?,
-//                     vec_g: vec_g.ok_or_else(|| de::Error::missing_field("vec_g"))?,
-//                     vec_h: vec_h.ok_or_else(|| de::Error::missing_field("vec_h"))?,
-//                 })
-//             }
+    #[test]
+    fn test_structural_check() {
+        let n = 8;
+        let index = 3;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
 
-//         }
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        incsig.structural_check(n).unwrap();
+        assert_eq!(incsig.structural_check(6), Err(StructuralError::RingSize(6)));
+
+        let mut truncated = incsig.clone();
+        truncated.bulletproof.vec_point_l.pop();
+        let expected = StructuralError::BulletproofRounds { expected: 3, left: 2, right: 3 };
+        assert_eq!(truncated.structural_check(n), Err(expected.clone()));
+        let error = params.verify::<Sha256>(&pks, &message, &truncated).unwrap_err();
+        assert_eq!(error.downcast_ref::<StructuralError>(), Some(&expected));
+
+        let mut identity = incsig.clone();
+        identity.point_r = ProjectivePoint::IDENTITY;
+        let error = params.verify::<Sha256>(&pks, &message, &identity).unwrap_err();
+        assert_eq!(error.downcast_ref::<StructuralError>(), Some(&StructuralError::IdentityPoint("R")));
+    }
 
-//         const FIELDS: &'static [&'static str] = &["g", "h", "vec_g", "vec_h"];
-//         deserializer.deserialize_struct("IncognitoParams", FIELDS, SignatureVisitor::<C, MAXN>{_phantom1: PhantomData::default()})
-//     }
-// }
+    #[test]
+    fn test_required_rounds() {
+        assert_eq!(required_rounds(1), Some(0));
+        assert_eq!(required_rounds(8), Some(3));
+        assert_eq!(required_rounds(0), None);
+        assert_eq!(required_rounds(7), None);
 
-#[cfg(test)]
-mod tests {
-    use elliptic_curve::Field;
-    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
-    use rand::{rngs::ThreadRng, Rng};
+        let n = 7;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[0], &message);
+        let error = params.convert::<Sha256>(&pks, &message, &signature, 0).unwrap_err();
+        assert_eq!(error.to_string(), "Ring size 7 is not a power of two");
+    }
+
+    #[test]
+    fn test_iter_ring() {
+        // Derives each key only when it is requested.
+        struct KeyStream<'a> {
+            sks: &'a [Scalar],
+            next: usize
+        }
 
-    use crate::{incognito::IncognitoSignature, schnorr::SchnorrSignature};
+        impl Iterator for KeyStream<'_> {
+            type Item = ProjectivePoint;
 
-    use super::IncognitoParams;
+            fn next(&mut self) -> Option<ProjectivePoint> {
+                let sk = self.sks.get(self.next)?;
+                self.next += 1;
+                Some(ProjectivePoint::GENERATOR * sk)
+            }
+        }
+
+        let n = 8;
+        let index = 6;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..16).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks[..n].iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let stream = || KeyStream { sks: &sks[..n], next: 0 };
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert_iter::<Sha256, _>(stream(), &message, &signature, index).unwrap();
+        params.verify_iter::<Sha256, _>(stream(), &message, &incsig).unwrap();
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        assert!(params.verify_iter::<Sha256, _>(stream().skip(1), &message, &incsig).is_err());
+        assert!(params.verify_iter::<Sha256, _>(KeyStream { sks: &sks, next: 0 }, &message, &incsig).is_err());
+    }
 
     #[test]
+    fn test_compute_t0() {
+        let mut rng = ThreadRng::default();
+        for n in 1..=5 {
+            for signers in 0..=n {
+                let w = Scalar::random(&mut rng);
+                let y = Scalar::random(&mut rng);
+                let vec_yn = IncognitoParams::<Secp256k1, 8>::build_vec_yn(n, &y);
+
+                // l(0)_i = b_i - w and r(0)_i = y^i (a_i + w) + w^2 with a = b - 1.
+                let vec_b: Vec<_> = (0..n).map(|i| if i < signers { Scalar::ONE } else { Scalar::ZERO }).collect();
+                let expected: Scalar = (0..n).map(|i| (vec_b[i] - w) * (vec_yn[i] * (vec_b[i] - Scalar::ONE + w) + w * w)).sum();
+                let sum_yn = vec_yn.iter().sum();
+                assert_eq!(IncognitoParams::<Secp256k1, 8>::compute_t0(n, signers, &w, &sum_yn), expected);
+            }
+        }
+    }
 
-    fn test_correctness() {
-        for n in [1, 2, 4, 8, 16] {
-            let i = ThreadRng::default().gen_range(0..n);
-            test_correctness_n(n, i);
+    #[cfg(feature = "test-internals")]
+    #[test]
+    fn test_verify_with_challenges() {
+        let n = 8;
+        let index = 2;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let challenges = params.challenges::<Sha256>(&pks, &message, &incsig);
+        params.verify_with_challenges::<Sha256>(&pks, &incsig, &challenges).unwrap();
+
+        // At x = 0 the polynomial equation is g * tx + h * taux == g * t0, whatever T1 and T2.
+        let zero = Challenges { x: Scalar::ZERO, ..challenges };
+        let sum_yn = IncognitoParams::<Secp256k1, 8>::build_vec_yn(n, &challenges.y).iter().sum();
+        let t0 = IncognitoParams::<Secp256k1, 8>::compute_t0(n, 1, &challenges.w, &sum_yn);
+        let mut probe = incsig.clone();
+        probe.point_t1 = ProjectivePoint::GENERATOR * Scalar::random(&mut rng);
+        probe.point_t2 = ProjectivePoint::GENERATOR * Scalar::random(&mut rng);
+        probe.tx = t0;
+        probe.taux = Scalar::ZERO;
+        let error = params.verify_with_challenges::<Sha256>(&pks, &probe, &zero).unwrap_err();
+        assert_eq!(error.to_string(), "Target equation failed");
+        probe.tx += Scalar::ONE;
+        let error = params.verify_with_challenges::<Sha256>(&pks, &probe, &zero).unwrap_err();
+        assert_eq!(error.to_string(), "Polynomial equation failed");
+
+        // With y = 0 as well the polynomial equation still holds, and the target's
+        // inversion of y reports the zero challenge instead of panicking.
+        let zero_y = Challenges { y: Scalar::ZERO, ..zero };
+        let sum_yn = IncognitoParams::<Secp256k1, 8>::build_vec_yn(n, &Scalar::ZERO).iter().sum();
+        probe.tx = IncognitoParams::<Secp256k1, 8>::compute_t0(n, 1, &challenges.w, &sum_yn);
+        let error = params.verify_with_challenges::<Sha256>(&pks, &probe, &zero_y).unwrap_err();
+        assert_eq!(error.downcast_ref::<VerifyError>(), Some(&VerifyError::ZeroChallenge));
+    }
+
+    #[test]
+    fn test_pure_checks() {
+        let n = 8;
+        let index = 1;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let challenges = params.challenges::<Sha256>(&pks, &message, &incsig);
+        assert!(params.check_linkage(&incsig, &challenges));
+        assert!(params.check_polynomial(&incsig, &challenges, n, 1));
+        assert!(params.check_inner_product::<Sha256>(&pks, &incsig, &challenges));
+
+        // Perturbations keep the challenges fixed, so each breaks exactly its own equation.
+        let mut linkage = incsig.clone();
+        linkage.s_beta += Scalar::ONE;
+        assert!(!params.check_linkage(&linkage, &challenges));
+        assert!(params.check_polynomial(&linkage, &challenges, n, 1));
+        let mut polynomial = incsig.clone();
+        polynomial.taux += Scalar::ONE;
+        assert!(!params.check_polynomial(&polynomial, &challenges, n, 1));
+        assert!(!params.check_polynomial(&incsig, &challenges, n, 2));
+        assert!(params.check_inner_product::<Sha256>(&pks, &polynomial, &challenges));
+        let mut inner_product = incsig.clone();
+        inner_product.bulletproof.r += Scalar::ONE;
+        assert!(!params.check_inner_product::<Sha256>(&pks, &inner_product, &challenges));
+        assert!(params.check_linkage(&inner_product, &challenges));
+        let mut target = incsig.clone();
+        target.mu += Scalar::ONE;
+        assert!(!params.check_inner_product::<Sha256>(&pks, &target, &challenges));
+        assert!(!params.check_inner_product::<Sha256>(&pks[..4], &incsig, &challenges));
+    }
+
+    #[cfg(feature = "transcript-log")]
+    #[test]
+    fn test_transcript_log() {
+        use elliptic_curve::group::GroupEncoding;
+
+        use crate::ring::ring_hash;
+
+        let n = 4;
+        let index = 3;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let (incsig, convert_log) = params.convert_with_log::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let (result, verify_log) = params.verify_with_log::<Sha256>(&pks, &message, &incsig).unwrap();
+        result.unwrap();
+        assert_eq!(convert_log, verify_log);
+        let names: Vec<_> = verify_log.entries().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["c", "c_z", "y", "w", "x", "d"]);
+
+        let expected: Vec<Vec<u8>> = [params.g, incsig.point_a, incsig.point_s, incsig.point_s_pk, incsig.point_c_pk].iter()
+            .map(|point| point.to_bytes().to_vec())
+            .chain([ring_hash::<Secp256k1, Sha256>(&pks).to_vec(), vec![0]])
+            .collect();
+        assert_eq!(verify_log.segments("y").unwrap(), expected);
+
+        let (result, tampered_log) = params.verify_with_log::<Sha256>(&pks, &[0, 3, 6], &incsig).unwrap();
+        assert!(result.is_err());
+        assert_ne!(tampered_log.segments("c"), verify_log.segments("c"));
+        assert_eq!(tampered_log.segments("y"), verify_log.segments("y"));
+    }
+
+    #[test]
+    fn test_challenge_uniformity() {
+        use elliptic_curve::PrimeField;
+
+        // Pearson's chi-squared over 256 buckets of the top and bottom byte. With 255
+        // degrees of freedom the statistic has mean 255 and standard deviation ~22.6,
+        // so 400 is over six deviations out; the inputs are fixed, so this cannot flake.
+        let samples = 1 << 14;
+        let mut top = [0u32; 256];
+        let mut bottom = [0u32; 256];
+        for i in 0..samples as u64 {
+            let challenge = IncognitoParams::<Secp256k1, 8>::hash_transcript::<Sha256>(|absorb| absorb(&i.to_le_bytes()));
+            let repr = challenge.to_repr();
+            top[repr[0] as usize] += 1;
+            bottom[repr[31] as usize] += 1;
         }
+        let expected = samples as f64 / 256.0;
+        let chi_squared = |counts: &[u32; 256]| counts.iter().map(|count| (*count as f64 - expected).powi(2) / expected).sum::<f64>();
+        assert!(chi_squared(&top) < 400.0, "top byte skewed: {}", chi_squared(&top));
+        assert!(chi_squared(&bottom) < 400.0, "bottom byte skewed: {}", chi_squared(&bottom));
     }
 
-    fn test_correctness_n(n: usize, index: usize) {
+    #[test]
+    fn test_prescreen() {
+        let n = 8;
+        let index = 7;
         let mut rng = ThreadRng::default();
-        let params = IncognitoParams::<Secp256k1, 256>::new();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
         let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
         let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
 
         let message = [0, 3, 6, 9];
         let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.prescreen::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        let mut polynomial = incsig.clone();
+        polynomial.tx += Scalar::ONE;
+        let error = params.prescreen::<Sha256>(&pks, &message, &polynomial).unwrap_err();
+        assert_eq!(error.to_string(), "Polynomial equation failed");
+        assert!(params.prescreen::<Sha256>(&pks, &[0, 3, 6], &incsig).is_err());
+
+        // A broken inner-product argument slips through, and only `verify` catches it.
+        let mut inner_product = incsig.clone();
+        inner_product.bulletproof.l += Scalar::ONE;
+        params.prescreen::<Sha256>(&pks, &message, &inner_product).unwrap();
+        assert!(params.verify::<Sha256>(&pks, &message, &inner_product).is_err());
+    }
 
-        signature.verify::<Sha256>(&pks[index], &message).unwrap();
+    #[test]
+    fn test_split_parts() {
+        let n = 8;
+        let index = 2;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
 
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
         let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
-        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        let (linkage, bulletproof) = incsig.clone().split();
+        #[cfg(feature = "serde")]
+        let (linkage, bulletproof) = (
+            bincode::deserialize(&bincode::serialize(&linkage).unwrap()).unwrap(),
+            bincode::deserialize(&bincode::serialize(&bulletproof).unwrap()).unwrap()
+        );
+        let recombined = IncognitoSignature::from_parts(linkage, bulletproof);
+        assert_eq!(recombined, incsig);
+        params.verify::<Sha256>(&pks, &message, &recombined).unwrap();
     }
 
     #[test]
+    fn test_repeated_key_bases() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let mut pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        pks[2] = pks[0];
 
-    fn test_serialization() {
-        let n = 256;
-        let index = 128;
+        // Listing a key twice would let its owner count as two signers, so it is refused.
+        let message = [0, 3, 6, 9];
+        let error = params.convert_threshold::<Sha256>(&pks, &message, &[(sks[0], 0), (sks[0], 2)]).unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate public key at index 2");
+
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[1], &message);
+        let mut distinct = pks.clone();
+        distinct[2] = ProjectivePoint::GENERATOR * sks[2];
+        let incsig = params.convert::<Sha256>(&distinct, &message, &signature, 1).unwrap();
+        let error = params.verify_threshold::<Sha256>(&pks, &message, &incsig, 1).unwrap_err();
+        assert_eq!(error.to_string(), "Duplicate public key at index 2");
+
+        // Even so, the independent `g_i` keep the combined bases apart.
+        let d = Scalar::random(&mut rng);
+        let bases: Vec<_> = (0..4).map(|i| params.vec_g[i] + pks[i] * d).collect();
+        assert_ne!(bases[0], bases[2]);
+        IncognitoParams::<Secp256k1, 4>::debug_assert_distinct_bases(&bases);
+    }
+
+    #[test]
+    fn test_indexed_ring() {
         let mut rng = ThreadRng::default();
-        let params = IncognitoParams::<Secp256k1, 256>::new();
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let sks: Vec<_> = (0..16).map(|_| Scalar::random(&mut rng)).collect();
+        let key_table: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let ids = [11, 3, 14, 6];
+        let pks: Vec<_> = ids.iter().map(|i| key_table[*i]).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[14], &message);
+        let explicit = params.convert::<Sha256>(&pks, &message, &signature, 2).unwrap();
+        let indexed = params.convert_indexed::<Sha256>(&key_table, &ids, &message, &signature, 2).unwrap();
+        params.verify_indexed::<Sha256>(&key_table, &ids, &message, &explicit).unwrap();
+        params.verify::<Sha256>(&pks, &message, &indexed).unwrap();
+
+        assert!(params.verify_indexed::<Sha256>(&key_table, &[11, 3, 6, 14], &message, &indexed).is_err());
+        let error = params.verify_indexed::<Sha256>(&key_table, &[11, 3, 16, 6], &message, &indexed).unwrap_err();
+        assert_eq!(error.to_string(), "Ring index 16 out of range");
+    }
+
+    #[test]
+    fn test_non_power_of_two_maxn() {
+        assert_eq!(IncognitoParams::<Secp256k1, 100>::MAX_RING_SIZE, 64);
+        assert_eq!(IncognitoParams::<Secp256k1, 64>::MAX_RING_SIZE, 64);
+        assert_eq!(IncognitoParams::<Secp256k1, 0>::MAX_RING_SIZE, 0);
+
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 100>::new();
+        let sks: Vec<_> = (0..100).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[40], &message);
+
+        let incsig = params.convert::<Sha256>(&pks[..64], &message, &signature, 40).unwrap();
+        params.verify::<Sha256>(&pks[..64], &message, &incsig).unwrap();
+
+        // A ring of exactly MAXN keys is refused, rather than padded.
+        let error = params.convert::<Sha256>(&pks, &message, &signature, 40).unwrap_err();
+        assert_eq!(error.to_string(), "Ring size 100 is not a power of two");
+        let error = params.verify::<Sha256>(&pks, &message, &incsig).unwrap_err();
+        assert_eq!(error.to_string(), "Ring size 100 is not a power of two");
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let params = IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(b"fingerprint");
+        let fingerprint: [u8; 32] = params.fingerprint::<Sha256>().into();
+        let same = IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(b"fingerprint");
+        assert_eq!(fingerprint, <[u8; 32]>::from(same.fingerprint::<Sha256>()));
+
+        assert_ne!(params.fingerprint::<Sha256>(), IncognitoParams::<Secp256k1, 8>::new().fingerprint::<Sha256>());
+        assert_ne!(params.fingerprint::<Sha256>(), IncognitoParams::<Secp256k1, 8>::from_seed::<Sha256>(b"fingerprints").fingerprint::<Sha256>());
+        let extended = params.extend_to::<Sha256, 16>(b"fingerprint").unwrap();
+        assert_ne!(params.fingerprint::<Sha256>(), extended.fingerprint::<Sha256>());
+        let mut swapped = params.clone();
+        swapped.vec_g.swap(0, 1);
+        assert_ne!(params.fingerprint::<Sha256>(), swapped.fingerprint::<Sha256>());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_streamed_params() {
+        let params = IncognitoParams::<Secp256k1, 64>::new();
+        let mut bytes = Vec::new();
+        params.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes, bincode::serialize(&params).unwrap());
+        assert_eq!(IncognitoParams::<Secp256k1, 64>::read_from(bytes.as_slice()).unwrap(), params);
+
+        assert!(IncognitoParams::<Secp256k1, 32>::read_from(bytes.as_slice()).is_err());
+        assert!(IncognitoParams::<Secp256k1, 64>::read_from(&bytes[..bytes.len() - 1]).is_err());
+        let mut corrupted = bytes.clone();
+        // The x-coordinate of `g`, after bincode's length prefix and the SEC1 tag, past the field modulus.
+        corrupted[9..41].fill(0xff);
+        assert!(IncognitoParams::<Secp256k1, 64>::read_from(corrupted.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_verify_lazy() {
+        let n = 8;
+        let index = 6;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
         let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
         let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
 
         let message = [0, 3, 6, 9];
         let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        let equations = params.verify_lazy::<Sha256>(&pks, &message, &incsig).unwrap();
+        assert_eq!(equations.len(), 4);
+        assert!(equations.iter().all(|equation| equation.holds()));
+        params.verify::<Sha256>(&pks, &message, &incsig).unwrap();
 
-        signature.verify::<Sha256>(&pks[index], &message).unwrap();
+        let mut polynomial = incsig.clone();
+        polynomial.tx += Scalar::ONE;
+        let mut inner_product = incsig.clone();
+        inner_product.bulletproof.l += Scalar::ONE;
+        let mut linkage = incsig.clone();
+        linkage.s_z += Scalar::ONE;
+        for (forged, failing) in [(&polynomial, 1), (&inner_product, 3), (&linkage, 0)] {
+            let equations = params.verify_lazy::<Sha256>(&pks, &message, forged).unwrap();
+            let holds: Vec<_> = equations.iter().map(|equation| equation.holds()).collect();
+            assert_eq!(holds.iter().position(|holds| !holds), Some(failing));
+            assert!(params.verify::<Sha256>(&pks, &message, forged).is_err());
+        }
+        let equations = params.verify_lazy::<Sha256>(&pks, &[0, 3, 6], &incsig).unwrap();
+        assert!(!equations.iter().all(|equation| equation.holds()));
+        assert!(params.verify_lazy::<Sha256>(&pks[..7], &message, &incsig).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_size_constants() {
+        let n = 16;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 16>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[9], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 9).unwrap();
+
+        // Exhaustive, so a new field fails to compile here until it is counted.
+        let IncognitoSignature {
+            point_r, point_c_pk, point_r_z, s_z, s_beta, point_a, point_s, point_s_pk, point_t1, point_t2, taux, mu, nu, tx, bulletproof
+        } = &incsig;
+        let points = [point_r, point_c_pk, point_r_z, point_a, point_s, point_s_pk, point_t1, point_t2, &bulletproof.target];
+        let scalars = [s_z, s_beta, taux, mu, nu, tx, &bulletproof.l, &bulletproof.r];
+        assert_eq!(points.len(), IncognitoSignature::<Secp256k1>::NUM_POINTS);
+        assert_eq!(scalars.len(), IncognitoSignature::<Secp256k1>::NUM_SCALARS);
+        assert_eq!(bulletproof.vec_point_l.len() + bulletproof.vec_point_r.len(), IncognitoSignature::<Secp256k1>::bulletproof_points(n));
+        assert_eq!(IncognitoSignature::<Secp256k1>::bulletproof_points(1), 0);
+        assert_eq!(IncognitoSignature::<Secp256k1>::bulletproof_points(100), 14);
+
+        // Compressed points behind bincode's 8-byte length prefix, and 32-byte scalars.
+        let total = IncognitoSignature::<Secp256k1>::NUM_POINTS + IncognitoSignature::<Secp256k1>::bulletproof_points(n);
+        let fixed = total * (8 + 33) + IncognitoSignature::<Secp256k1>::NUM_SCALARS * 32;
+        let bytes = bincode::serialize(&incsig).unwrap();
+        assert_eq!(bytes.len(), fixed + 2 * 8);
+        assert_eq!(bytes.len(), IncognitoSignature::<Secp256k1>::serialized_len(n));
+        assert_eq!(IncognitoSignature::<Secp256k1>::serialized_len(9), IncognitoSignature::<Secp256k1>::serialized_len(16));
+    }
+
+    #[test]
+    fn test_verify_constant_time() {
+        let n = 8;
+        let index = 4;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
 
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
         let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+        params.verify_constant_time::<Sha256>(&pks, &message, &incsig).unwrap();
+
+        // Each makes `verify` stop at a different equation.
+        let mut linkage = incsig.clone();
+        linkage.s_z += Scalar::ONE;
+        let mut polynomial = incsig.clone();
+        polynomial.point_t2 += ProjectivePoint::GENERATOR;
+        let mut inner_product = incsig.clone();
+        inner_product.bulletproof.l += Scalar::ONE;
+        for (tampered, equation) in [(linkage, "Linkage"), (polynomial, "Polynomial"), (inner_product, "Bulletproof")] {
+            let error = params.verify::<Sha256>(&pks, &message, &tampered).unwrap_err();
+            assert_eq!(error.to_string(), format!("{} equation failed", equation));
+            let error = params.verify_constant_time::<Sha256>(&pks, &message, &tampered).unwrap_err();
+            assert_eq!(error.to_string(), "Invalid incognito signature");
+        }
+        let mut target = incsig.clone();
+        target.nu += Scalar::ONE;
+        assert!(params.verify::<Sha256>(&pks, &message, &target).is_err());
+        assert!(params.verify_constant_time::<Sha256>(&pks, &message, &target).is_err());
+        assert!(params.verify_constant_time::<Sha256>(&pks, &[0, 3, 6], &incsig).is_err());
+    }
 
-        let params_new: IncognitoParams::<Secp256k1, 256> = bincode::deserialize(&bincode::serialize(&params).unwrap()).unwrap();
-        let incsig_new: IncognitoSignature::<Secp256k1> = bincode::deserialize(&bincode::serialize(&incsig).unwrap()).unwrap();
-        params_new.verify::<Sha256>(&pks, &message, &incsig_new).unwrap();
-        assert_eq!(params, params_new);
+    #[test]
+    fn test_messages() {
+        let n = 8;
+        let index = 1;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let messages: [&[u8]; 2] = [b"transfer 10", b"to account 7"];
+
+        let signature = SchnorrSignature::<Secp256k1>::sign_messages::<Sha256>(&sks[index], &messages);
+        let incsig = params.convert_messages::<Sha256>(&pks, &messages, &signature, index).unwrap();
+        params.verify_messages::<Sha256>(&pks, &messages, &incsig).unwrap();
+        assert!(params.verify_messages::<Sha256>(&pks, &[messages[1], messages[0]], &incsig).is_err());
+        assert!(params.verify_messages::<Sha256>(&pks, &[b"transfer 10to account 7"], &incsig).is_err());
     }
-}
\ No newline at end of file
+}
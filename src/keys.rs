@@ -0,0 +1,155 @@
+//! Keys implementing the `signature` crate's `Signer` and `Verifier` traits, so
+//! Schnorr signatures drop into code generic over those traits.
+//!
+//! The traits take only the message, so the keys carry the digest `D` the challenge
+//! is hashed with; a signature verifies under a `VerifyingKey` with the same `D`.
+
+use std::{fmt, marker::PhantomData};
+
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, Group, ProjectivePoint, Scalar};
+#[cfg(feature = "zeroize")]
+use elliptic_curve::zeroize::{Zeroize, ZeroizeOnDrop};
+use signature::{Error, Signer, Verifier};
+
+use crate::schnorr::SchnorrSignature;
+
+/// A secret key signing with digest `D`.
+pub struct SigningKey<C: CurveArithmetic, D> {
+    sk: Scalar<C>,
+    verifying_key: VerifyingKey<C, D>
+}
+
+/// A public key verifying signatures made with digest `D`.
+pub struct VerifyingKey<C: CurveArithmetic, D> {
+    pk: ProjectivePoint<C>,
+    _digest: PhantomData<D>
+}
+
+impl <C: CurveArithmetic, D> SigningKey<C, D> {
+    pub fn new(sk: Scalar<C>) -> Self {
+        Self {
+            sk,
+            verifying_key: VerifyingKey::new(ProjectivePoint::<C>::generator() * sk)
+        }
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<C, D> {
+        &self.verifying_key
+    }
+}
+
+impl <C: CurveArithmetic, D> VerifyingKey<C, D> {
+    pub fn new(pk: ProjectivePoint<C>) -> Self {
+        Self {
+            pk,
+            _digest: PhantomData
+        }
+    }
+
+    pub fn pk(&self) -> &ProjectivePoint<C> {
+        &self.pk
+    }
+}
+
+// Implemented by hand: deriving would require `D` itself to be `Clone`, `Eq`, etc.
+impl <C: CurveArithmetic, D> Clone for SigningKey<C, D> {
+    fn clone(&self) -> Self {
+        Self {
+            sk: self.sk,
+            verifying_key: self.verifying_key
+        }
+    }
+}
+
+impl <C: CurveArithmetic, D> Clone for VerifyingKey<C, D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl <C: CurveArithmetic, D> Copy for VerifyingKey<C, D> {}
+
+impl <C: CurveArithmetic, D> PartialEq for VerifyingKey<C, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pk == other.pk
+    }
+}
+
+impl <C: CurveArithmetic, D> Eq for VerifyingKey<C, D> {}
+
+impl <C: CurveArithmetic, D> fmt::Debug for VerifyingKey<C, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyingKey").field("pk", &self.pk).finish()
+    }
+}
+
+#[cfg(feature = "hd")]
+impl <C: CurveArithmetic, D> From<&crate::hd::KeyPair<C>> for SigningKey<C, D> {
+    fn from(key_pair: &crate::hd::KeyPair<C>) -> Self {
+        Self {
+            sk: key_pair.sk,
+            verifying_key: VerifyingKey::new(key_pair.pk)
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic, D> Drop for SigningKey<C, D> {
+    fn drop(&mut self) {
+        self.sk.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic, D> ZeroizeOnDrop for SigningKey<C, D> {}
+
+impl <C: CurveArithmetic, D> Signer<SchnorrSignature<C>> for SigningKey<C, D>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    fn try_sign(&self, message: &[u8]) -> Result<SchnorrSignature<C>, Error> {
+        Ok(SchnorrSignature::<C>::sign::<D>(&self.sk, message))
+    }
+}
+
+impl <C: CurveArithmetic, D> Verifier<SchnorrSignature<C>> for VerifyingKey<C, D>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    fn verify(&self, message: &[u8], signature: &SchnorrSignature<C>) -> Result<(), Error> {
+        signature.verify::<D>(&self.pk, message).map_err(|_| Error::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::{Sha256, Sha512}, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+    use signature::{Signer, Verifier};
+
+    use crate::schnorr::SchnorrSignature;
+
+    use super::{SigningKey, VerifyingKey};
+
+    fn sign_generic<S: Signer<SchnorrSignature<Secp256k1>>>(signer: &S, message: &[u8]) -> SchnorrSignature<Secp256k1> {
+        signer.sign(message)
+    }
+
+    #[test]
+    fn test_signer_verifier() {
+        let mut rng = ThreadRng::default();
+        let signing_key = SigningKey::<Secp256k1, Sha256>::new(Scalar::random(&mut rng));
+        let verifying_key = *signing_key.verifying_key();
+
+        let message = [0, 3, 6, 9];
+        let signature = sign_generic(&signing_key, &message);
+        verifying_key.verify(&message, &signature).unwrap();
+        signature.verify::<Sha256>(verifying_key.pk(), &message).unwrap();
+        assert!(verifying_key.verify(&[0, 3, 6], &signature).is_err());
+        assert!(VerifyingKey::<Secp256k1, Sha512>::new(*verifying_key.pk()).verify(&message, &signature).is_err());
+    }
+}
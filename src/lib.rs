@@ -1,3 +1,32 @@
 pub mod schnorr;
+pub mod challenge;
+pub mod curve;
 pub mod bulletproof;
-pub mod incognito;
\ No newline at end of file
+pub mod range;
+pub mod commitment;
+pub mod incognito;
+pub mod context;
+pub mod backend;
+pub mod ring;
+pub mod limiter;
+#[cfg(feature = "serde")]
+pub mod cache;
+pub mod error;
+pub mod verifiable;
+pub mod view;
+#[cfg(feature = "transcript-log")]
+pub mod transcript;
+#[cfg(feature = "hd")]
+pub mod hd;
+#[cfg(feature = "signature")]
+pub mod keys;
+#[cfg(feature = "encrypt")]
+pub mod encrypt;
+#[cfg(feature = "dyn-digest")]
+pub mod dyn_digest;
+#[cfg(feature = "serde")]
+pub mod serde_util;
+#[cfg(feature = "arbitrary")]
+mod fuzzing;
+#[cfg(feature = "tokio")]
+pub mod net;
\ No newline at end of file
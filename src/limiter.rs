@@ -0,0 +1,77 @@
+use std::{collections::HashMap, time::{Duration, Instant}};
+
+/// Fixed-window rate limit on conversions, consulted by `IncognitoParams::convert_limited`.
+///
+/// Conversions are keyed by the signer's `IncognitoParams::linkability_tag`, which the
+/// converter derives from the ring index. An incognito signature itself carries no
+/// tag, so the limit can only be enforced at conversion time.
+#[derive(Debug, Clone)]
+pub struct ConversionLimiter {
+    limit: usize,
+    interval: Duration,
+    windows: HashMap<Vec<u8>, (Instant, usize)>
+}
+
+impl ConversionLimiter {
+    /// Allows at most `limit` conversions per key within each `interval`.
+    pub fn new(limit: usize, interval: Duration) -> Self {
+        Self {
+            limit,
+            interval,
+            windows: HashMap::new()
+        }
+    }
+
+    pub fn acquire(&mut self, key: &[u8]) -> anyhow::Result<()> {
+        self.acquire_at(key, Instant::now())
+    }
+
+    /// Same as `acquire` with an explicit clock reading.
+    pub fn acquire_at(&mut self, key: &[u8], now: Instant) -> anyhow::Result<()> {
+        let (start, count) = self.windows.entry(key.to_vec()).or_insert((now, 0));
+        if now.saturating_duration_since(*start) >= self.interval {
+            *start = now;
+            *count = 0;
+        }
+        anyhow::ensure!(*count < self.limit, "Conversion limit of {} per {:?} reached", self.limit, self.interval);
+        *count += 1;
+        Ok(())
+    }
+
+    /// Refunds one conversion charged to `key` by `acquire`, for a conversion that
+    /// did not go through.
+    pub fn release(&mut self, key: &[u8]) {
+        if let Some((_, count)) = self.windows.get_mut(key) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Drops windows that have expired by `now`.
+    pub fn prune(&mut self, now: Instant) {
+        let interval = self.interval;
+        self.windows.retain(|_, (start, _)| now.saturating_duration_since(*start) < interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::ConversionLimiter;
+
+    #[test]
+    fn test_window_resets() {
+        let mut limiter = ConversionLimiter::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        limiter.acquire_at(b"a", now).unwrap();
+        limiter.acquire_at(b"a", now).unwrap();
+        assert!(limiter.acquire_at(b"a", now).is_err());
+        limiter.acquire_at(b"b", now).unwrap();
+        limiter.release(b"a");
+        limiter.acquire_at(b"a", now).unwrap();
+        assert!(limiter.acquire_at(b"a", now).is_err());
+        limiter.acquire_at(b"a", now + Duration::from_secs(60)).unwrap();
+        limiter.prune(now + Duration::from_secs(120));
+        assert!(limiter.windows.is_empty());
+    }
+}
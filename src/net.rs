@@ -0,0 +1,361 @@
+use std::{io, marker::PhantomData, sync::Arc};
+
+use digest::Digest;
+use elliptic_curve::{group::{Curve, GroupEncoding}, AffinePoint, CurveArithmetic, Group, ProjectivePoint, Scalar};
+use futures::{Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_util::{bytes::{Bytes, BytesMut}, codec::{Decoder, Encoder, LengthDelimitedCodec}, sync::CancellationToken};
+
+use crate::incognito::{IncognitoParams, IncognitoSignature};
+
+/// A settlement frame: ring indices into the shared key table, the message and its signature.
+pub type SettlementFrame<C> = (Vec<usize>, Vec<u8>, IncognitoSignature<C>);
+
+/// Longest bincode-encoded `SettlementFrame` over a ring of up to `MAXN` keys with a
+/// message of up to `max_message_len` bytes.
+pub fn settlement_frame_len<C: CurveArithmetic, const MAXN: usize>(max_message_len: usize) -> usize
+where
+    ProjectivePoint<C>: GroupEncoding,
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned
+{
+    (8 + 8 * MAXN) + (8 + max_message_len) + IncognitoSignature::<C>::serialized_len(MAXN)
+}
+
+/// Length-delimited codec for settlement frames. The default codec accepts frames of
+/// up to 8 MiB, so a peer could make the verifier buffer that much per connection by
+/// sending a large length prefix; this one rejects any frame longer than
+/// `settlement_frame_len` as soon as the prefix is read.
+pub fn settlement_codec<C: CurveArithmetic, const MAXN: usize>(max_message_len: usize) -> LengthDelimitedCodec
+where
+    ProjectivePoint<C>: GroupEncoding,
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned
+{
+    LengthDelimitedCodec::builder().max_frame_length(settlement_frame_len::<C, MAXN>(max_message_len)).new_codec()
+}
+
+/// Longest bincode-encoded key table (`Vec<AffinePoint<C>>`) of up to `max_keys` keys.
+pub fn key_table_frame_len<C: CurveArithmetic>(max_keys: usize) -> usize
+where
+    AffinePoint<C>: Serialize
+{
+    let point = bincode::serialized_size(&ProjectivePoint::<C>::generator().to_affine()).expect("points serialize") as usize;
+    8 + max_keys * point
+}
+
+/// Length-delimited codec for the key table sent ahead of the settlement frames. The
+/// table is far larger than any settlement frame, so it gets its own cap instead of
+/// raising `settlement_codec`'s; swap to that codec with `Framed::map_codec` once the
+/// table has been read.
+pub fn key_table_codec<C: CurveArithmetic>(max_keys: usize) -> LengthDelimitedCodec
+where
+    AffinePoint<C>: Serialize
+{
+    LengthDelimitedCodec::builder().max_frame_length(key_table_frame_len::<C>(max_keys)).new_codec()
+}
+
+/// Frame cap of `SignatureCodec::new`. An `IncognitoSignature` over a ring of 2^32 keys
+/// encodes to under 4 KiB, so this leaves room for small items sent alongside it.
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 64 * 1024;
+
+/// Length-delimited codec carrying bincode-encoded `T`s, so that
+/// `Framed<_, SignatureCodec<T>>` sends and yields typed items.
+#[derive(Debug)]
+pub struct SignatureCodec<T> {
+    inner: LengthDelimitedCodec,
+    _item: PhantomData<fn() -> T>
+}
+
+impl <T> SignatureCodec<T> {
+    /// Refuses frames longer than `DEFAULT_MAX_FRAME_LENGTH` bytes.
+    pub fn new() -> Self {
+        Self::with_max_frame_length(DEFAULT_MAX_FRAME_LENGTH)
+    }
+
+    /// Same as `new`, refusing frames longer than `max_frame_length` bytes instead.
+    pub fn with_max_frame_length(max_frame_length: usize) -> Self {
+        Self {
+            inner: LengthDelimitedCodec::builder().max_frame_length(max_frame_length).new_codec(),
+            _item: PhantomData
+        }
+    }
+}
+
+impl <T> Default for SignatureCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <T> Clone for SignatureCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            _item: PhantomData
+        }
+    }
+}
+
+impl <T: Serialize> Encoder<T> for SignatureCodec<T> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        let bytes = bincode::serialize(&item).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.inner.encode(Bytes::from(bytes), dst)
+    }
+}
+
+impl <T: DeserializeOwned> Decoder for SignatureCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        match self.inner.decode(src)? {
+            Some(frame) => bincode::deserialize(&frame).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            None => Ok(None)
+        }
+    }
+}
+
+fn verify_frame<C: CurveArithmetic, D, const MAXN: usize>(
+    params: &IncognitoParams<C, MAXN>,
+    key_table: &[ProjectivePoint<C>],
+    frame: io::Result<BytesMut>
+) -> anyhow::Result<()>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned,
+    D: Digest
+{
+    let (ids, message, signature): SettlementFrame<C> = bincode::deserialize(&frame?)?;
+    params.verify_indexed::<D>(key_table, &ids, &message, &signature)
+}
+
+/// Verifies every `SettlementFrame` pulled from `frames` until the stream ends.
+pub async fn verify_stream<C: CurveArithmetic, D, S, const MAXN: usize>(
+    params: &IncognitoParams<C, MAXN>,
+    key_table: &[ProjectivePoint<C>],
+    frames: S
+) -> Vec<anyhow::Result<()>>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned,
+    D: Digest,
+    S: Stream<Item = io::Result<BytesMut>> + Unpin
+{
+    frames.map(|frame| verify_frame::<C, D, MAXN>(params, key_table, frame)).collect().await
+}
+
+/// Like `verify_stream`, but stops pulling frames once `token` is cancelled and
+/// returns the results gathered so far.
+pub async fn verify_stream_with_cancel<C: CurveArithmetic, D, S, const MAXN: usize>(
+    params: &IncognitoParams<C, MAXN>,
+    key_table: &[ProjectivePoint<C>],
+    mut frames: S,
+    token: CancellationToken
+) -> Vec<anyhow::Result<()>>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    Scalar<C>: Serialize + DeserializeOwned,
+    AffinePoint<C>: Serialize + DeserializeOwned,
+    D: Digest,
+    S: Stream<Item = io::Result<BytesMut>> + Unpin
+{
+    let mut results = Vec::new();
+    loop {
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => break,
+            frame = frames.next() => match frame {
+                Some(frame) => results.push(verify_frame::<C, D, MAXN>(params, key_table, frame)),
+                None => break,
+            }
+        }
+    }
+    results
+}
+
+/// Runs `verify` on tokio's blocking thread pool and awaits its result.
+///
+/// Verification takes milliseconds of CPU time per signature, so calling `verify`
+/// inside an async task holds a runtime worker that long, and a server verifying in
+/// every connection task stops polling sockets and timers. Await this instead, or
+/// group signatures and call `verify_batch` from one `spawn_blocking` task. The
+/// blocking pool is bounded too, so keep a limit on signatures in flight.
+pub async fn verify_spawn_blocking<C: CurveArithmetic + 'static, D, const MAXN: usize>(
+    params: Arc<IncognitoParams<C, MAXN>>,
+    pks: Arc<[ProjectivePoint<C>]>,
+    message: Vec<u8>,
+    signature: IncognitoSignature<C>
+) -> anyhow::Result<()>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest + 'static
+{
+    tokio::task::spawn_blocking(move || params.verify::<D>(&pks, &message, &signature)).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use elliptic_curve::Field;
+    use futures::{stream, SinkExt, StreamExt};
+    use k256::{sha2::Sha256, AffinePoint, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+    use tokio_util::{bytes::BytesMut, codec::{Decoder, Framed}, sync::CancellationToken};
+
+    use crate::{incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+
+    use super::{
+        key_table_codec, key_table_frame_len, settlement_codec, settlement_frame_len, verify_spawn_blocking, verify_stream,
+        verify_stream_with_cancel, SignatureCodec, DEFAULT_MAX_FRAME_LENGTH
+    };
+
+    fn frames(params: &IncognitoParams<Secp256k1, 8>, sks: &[Scalar], pks: &[ProjectivePoint], count: usize) -> Vec<std::io::Result<BytesMut>> {
+        let ids: Vec<usize> = (0..8).collect();
+        (0..count).map(|i| {
+            let message = format!("the {}-th transaction", i).into_bytes();
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[3], &message);
+            let incsig = params.convert::<Sha256>(pks, &message, &signature, 3).unwrap();
+            Ok(BytesMut::from(&bincode::serialize(&(ids.clone(), message, incsig)).unwrap()[..]))
+        }).collect()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_verify_stream_with_cancel() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let frames = frames(&params, &sks, &pks, 5);
+
+        let results = verify_stream::<_, Sha256, _, 8>(&params, &pks, stream::iter(frames.iter().map(|f| Ok(f.as_ref().unwrap().clone())))).await;
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|res| res.is_ok()));
+
+        let token = CancellationToken::new();
+        let trigger = token.clone();
+        let mut pulled = 0;
+        let source = stream::iter(frames).inspect(move |_| {
+            pulled += 1;
+            if pulled == 2 {
+                trigger.cancel();
+            }
+        });
+        let results = verify_stream_with_cancel::<_, Sha256, _, 8>(&params, &pks, source, token).await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|res| res.is_ok()));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_signature_codec() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[6], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 6).unwrap();
+
+        let (client, server) = tokio::io::duplex(1 << 16);
+        let mut sender = Framed::new(client, SignatureCodec::<IncognitoSignature<Secp256k1>>::new());
+        let receiver = Framed::new(server, SignatureCodec::<IncognitoSignature<Secp256k1>>::new());
+        sender.send(incsig.clone()).await.unwrap();
+        sender.send(incsig.clone()).await.unwrap();
+        drop(sender);
+
+        let received: Vec<_> = receiver.map(|item| item.unwrap()).collect().await;
+        assert_eq!(received, vec![incsig.clone(), incsig]);
+        params.verify::<Sha256>(&pks, &message, &received[0]).unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_frame_length_cap() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let frames = frames(&params, &sks, &pks, 2);
+        let cap = settlement_frame_len::<Secp256k1, 8>(64);
+        assert!(frames.iter().all(|frame| frame.as_ref().unwrap().len() <= cap));
+
+        let (client, server) = tokio::io::duplex(1 << 16);
+        let mut sender = Framed::new(client, settlement_codec::<Secp256k1, 8>(64));
+        let receiver = Framed::new(server, settlement_codec::<Secp256k1, 8>(64));
+        for frame in frames {
+            sender.send(frame.unwrap().freeze()).await.unwrap();
+        }
+        drop(sender);
+        let results = verify_stream::<_, Sha256, _, 8>(&params, &pks, receiver).await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|res| res.is_ok()));
+
+        // A 4 GiB length prefix is refused before any of the frame is buffered.
+        let mut codec = settlement_codec::<Secp256k1, 8>(64);
+        let mut src = BytesMut::from(&u32::MAX.to_be_bytes()[..]);
+        assert!(codec.decode(&mut src).is_err());
+        assert!(src.capacity() < cap);
+
+        let mut codec = SignatureCodec::<IncognitoSignature<Secp256k1>>::with_max_frame_length(cap);
+        let mut src = BytesMut::from(&((cap + 1) as u32).to_be_bytes()[..]);
+        assert!(codec.decode(&mut src).is_err());
+
+        // The default codec is capped too.
+        assert!(IncognitoSignature::<Secp256k1>::serialized_len(1 << 32) < DEFAULT_MAX_FRAME_LENGTH);
+        let mut codec = SignatureCodec::<IncognitoSignature<Secp256k1>>::default();
+        let mut src = BytesMut::from(&((DEFAULT_MAX_FRAME_LENGTH + 1) as u32).to_be_bytes()[..]);
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_key_table_then_settlement_frames() {
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let table = bincode::serialize(&pks.iter().map(|pk| pk.to_affine()).collect::<Vec<_>>()).unwrap();
+        assert_eq!(table.len(), key_table_frame_len::<Secp256k1>(8));
+
+        let (client, server) = tokio::io::duplex(1 << 16);
+        let mut sender = Framed::new(client, key_table_codec::<Secp256k1>(8));
+        sender.send(table.into()).await.unwrap();
+        let mut sender = sender.map_codec(|_| settlement_codec::<Secp256k1, 8>(64));
+        for frame in frames(&params, &sks, &pks, 2) {
+            sender.send(frame.unwrap().freeze()).await.unwrap();
+        }
+        drop(sender);
+
+        let mut receiver = Framed::new(server, key_table_codec::<Secp256k1>(8));
+        let table: Vec<AffinePoint> = bincode::deserialize(&receiver.next().await.unwrap().unwrap()).unwrap();
+        let table: Vec<ProjectivePoint> = table.into_iter().map(ProjectivePoint::from).collect();
+        let receiver = receiver.map_codec(|_| settlement_codec::<Secp256k1, 8>(64));
+        let results = verify_stream::<_, Sha256, _, 8>(&params, &table, receiver).await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|res| res.is_ok()));
+
+        // A table one key longer than the cap is refused.
+        let mut codec = key_table_codec::<Secp256k1>(8);
+        let mut src = BytesMut::from(&((key_table_frame_len::<Secp256k1>(9)) as u32).to_be_bytes()[..]);
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_verify_spawn_blocking() {
+        let mut rng = ThreadRng::default();
+        let params = Arc::new(IncognitoParams::<Secp256k1, 8>::new());
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Arc<[ProjectivePoint]> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[2], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 2).unwrap();
+
+        verify_spawn_blocking::<_, Sha256, 8>(params.clone(), pks.clone(), message.to_vec(), incsig.clone()).await.unwrap();
+        let error = verify_spawn_blocking::<_, Sha256, 8>(params.clone(), pks.clone(), vec![0, 3, 6], incsig.clone()).await.unwrap_err();
+        assert_eq!(error.to_string(), params.verify::<Sha256>(&pks, &[0, 3, 6], &incsig).unwrap_err().to_string());
+        assert!(verify_spawn_blocking::<_, Sha256, 8>(params, pks[..4].into(), message.to_vec(), incsig).await.is_err());
+    }
+}
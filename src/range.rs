@@ -0,0 +1,212 @@
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, Field, Group, PrimeField, ProjectivePoint, Scalar};
+use rand::rngs::ThreadRng;
+#[cfg(feature = "serde")]
+use elliptic_curve::AffinePoint;
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{bulletproof::BulletProof, challenge::{invert_challenge, reduce_digest}};
+
+/// Value and blinding bases `g`, `h` and the bit bases `vec_g`, `vec_h`.
+pub type Generators<'a, C> = (&'a ProjectivePoint<C>, &'a ProjectivePoint<C>, &'a [ProjectivePoint<C>], &'a [ProjectivePoint<C>]);
+
+/// Proof that the amount `v` in a Pedersen commitment `V = g^v h^blind` lies in
+/// `[0, 2^bits)`, where `bits` is the number of generators it is checked against.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct RangeProof<C: CurveArithmetic> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_a: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_s: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_t1: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    point_t2: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    taux: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    mu: Scalar<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    tx: Scalar<C>,
+    bulletproof: BulletProof<C>
+}
+
+impl <C: CurveArithmetic> RangeProof<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    #[inline]
+    fn challenge_yz<D>(
+        context: &[u8],
+        point_v: &ProjectivePoint<C>,
+        point_a: &ProjectivePoint<C>,
+        point_s: &ProjectivePoint<C>,
+        index: u8
+    ) -> Scalar<C>
+    where
+        D: Digest
+    {
+        let digest = D::new()
+            .chain_update(b"range")
+            .chain_update(context)
+            .chain_update(point_v.to_bytes())
+            .chain_update(point_a.to_bytes())
+            .chain_update(point_s.to_bytes())
+            .chain_update([index])
+            .finalize();
+        reduce_digest::<C>(&digest)
+    }
+
+    #[inline]
+    fn challenge_x<D>(
+        point_t1: &ProjectivePoint<C>,
+        point_t2: &ProjectivePoint<C>,
+        y: &Scalar<C>,
+        z: &Scalar<C>
+    ) -> Scalar<C>
+    where
+        D: Digest
+    {
+        let digest = D::new()
+            .chain_update(point_t1.to_bytes())
+            .chain_update(point_t2.to_bytes())
+            .chain_update(y.to_repr())
+            .chain_update(z.to_repr())
+            .finalize();
+        reduce_digest::<C>(&digest)
+    }
+
+    #[inline]
+    fn challenge_w<D>(x: &Scalar<C>, taux: &Scalar<C>, mu: &Scalar<C>, tx: &Scalar<C>) -> Scalar<C>
+    where
+        D: Digest
+    {
+        let digest = D::new()
+            .chain_update(x.to_repr())
+            .chain_update(taux.to_repr())
+            .chain_update(mu.to_repr())
+            .chain_update(tx.to_repr())
+            .finalize();
+        reduce_digest::<C>(&digest)
+    }
+
+    #[inline]
+    fn powers(n: usize, base: &Scalar<C>) -> Vec<Scalar<C>> {
+        let mut vec = Vec::with_capacity(n);
+        let mut cur = Scalar::<C>::ONE;
+        for _ in 0..n {
+            vec.push(cur);
+            cur *= base;
+        }
+        vec
+    }
+
+    /// Proves that `point_v = g * amount + h * blind` opens to an amount below
+    /// `2^vec_g.len()`. Only the low `vec_g.len()` bits of `amount` are committed
+    /// to, so an out-of-range amount yields a proof that does not verify.
+    /// `context` is bound into the transcript.
+    pub(crate) fn prove<D>(
+        generators: Generators<'_, C>,
+        point_v: &ProjectivePoint<C>,
+        amount: u64,
+        blind: &Scalar<C>,
+        context: &[u8]
+    ) -> Self
+    where
+        D: Digest
+    {
+        let (g, h, vec_g, vec_h) = generators;
+        let n = vec_g.len();
+        let mut rng = ThreadRng::default();
+
+        let vec_al: Vec<_> = (0..n).map(|i| Scalar::<C>::from(amount.checked_shr(i as u32).unwrap_or(0) & 1)).collect();
+        let vec_ar: Vec<_> = vec_al.iter().map(|bit| *bit - Scalar::<C>::ONE).collect();
+        let vec_sl: Vec<_> = (0..n).map(|_| Scalar::<C>::random(&mut rng)).collect();
+        let vec_sr: Vec<_> = (0..n).map(|_| Scalar::<C>::random(&mut rng)).collect();
+        let alpha = Scalar::<C>::random(&mut rng);
+        let rho = Scalar::<C>::random(&mut rng);
+
+        let point_a = *h * alpha + (0..n).map(|i| vec_g[i] * vec_al[i] + vec_h[i] * vec_ar[i]).sum::<ProjectivePoint<C>>();
+        let point_s = *h * rho + (0..n).map(|i| vec_g[i] * vec_sl[i] + vec_h[i] * vec_sr[i]).sum::<ProjectivePoint<C>>();
+
+        let y = Self::challenge_yz::<D>(context, point_v, &point_a, &point_s, 0);
+        let z = Self::challenge_yz::<D>(context, point_v, &point_a, &point_s, 1);
+        let vec_yn = Self::powers(n, &y);
+        let vec_2n = Self::powers(n, &Scalar::<C>::from(2u64));
+
+        let vec_l0: Vec<_> = (0..n).map(|i| vec_al[i] - z).collect();
+        let vec_r0: Vec<_> = (0..n).map(|i| vec_yn[i] * (vec_ar[i] + z) + z * z * vec_2n[i]).collect();
+        let vec_r1: Vec<_> = (0..n).map(|i| vec_yn[i] * vec_sr[i]).collect();
+        let t1: Scalar<C> = (0..n).map(|i| vec_l0[i] * vec_r1[i] + vec_sl[i] * vec_r0[i]).sum();
+        let t2: Scalar<C> = (0..n).map(|i| vec_sl[i] * vec_r1[i]).sum();
+
+        let tau1 = Scalar::<C>::random(&mut rng);
+        let tau2 = Scalar::<C>::random(&mut rng);
+        let point_t1 = *g * t1 + *h * tau1;
+        let point_t2 = *g * t2 + *h * tau2;
+
+        let x = Self::challenge_x::<D>(&point_t1, &point_t2, &y, &z);
+        let vec_l: Vec<_> = (0..n).map(|i| vec_l0[i] + vec_sl[i] * x).collect();
+        let vec_r: Vec<_> = (0..n).map(|i| vec_r0[i] + vec_r1[i] * x).collect();
+        let tx: Scalar<C> = (0..n).map(|i| vec_l[i] * vec_r[i]).sum();
+        let taux = tau2 * x * x + tau1 * x + z * z * blind;
+        let mu = alpha + rho * x;
+
+        let w = Self::challenge_w::<D>(&x, &taux, &mu, &tx);
+        let point_q = ProjectivePoint::<C>::generator() * w;
+        let vec_yn_inv = Self::powers(n, &y.invert().unwrap());
+        let base_h: Vec<_> = (0..n).map(|i| vec_h[i] * vec_yn_inv[i]).collect();
+        let target = (0..n).map(|i| vec_g[i] * vec_l[i] + base_h[i] * vec_r[i]).sum::<ProjectivePoint<C>>() + point_q * tx;
+        let bulletproof = BulletProof::<C>::prove_inner_product::<D>(vec_g, &base_h, &vec_l, &vec_r, &target, &point_q);
+
+        Self {
+            point_a,
+            point_s,
+            point_t1,
+            point_t2,
+            taux,
+            mu,
+            tx,
+            bulletproof
+        }
+    }
+
+    pub(crate) fn verify<D>(
+        &self,
+        generators: Generators<'_, C>,
+        point_v: &ProjectivePoint<C>,
+        context: &[u8]
+    ) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let (g, h, vec_g, vec_h) = generators;
+        let n = vec_g.len();
+
+        let y = Self::challenge_yz::<D>(context, point_v, &self.point_a, &self.point_s, 0);
+        let z = Self::challenge_yz::<D>(context, point_v, &self.point_a, &self.point_s, 1);
+        let x = Self::challenge_x::<D>(&self.point_t1, &self.point_t2, &y, &z);
+        let vec_yn = Self::powers(n, &y);
+        let vec_2n = Self::powers(n, &Scalar::<C>::from(2u64));
+
+        let sum_yn: Scalar<C> = vec_yn.iter().sum();
+        let sum_2n: Scalar<C> = vec_2n.iter().sum();
+        let delta = (z - z * z) * sum_yn - z * z * z * sum_2n;
+        anyhow::ensure!(
+            *g * self.tx + *h * self.taux == *point_v * (z * z) + *g * delta + self.point_t1 * x + self.point_t2 * x * x,
+            "Range proof polynomial check failed"
+        );
+
+        let w = Self::challenge_w::<D>(&x, &self.taux, &self.mu, &self.tx);
+        let point_q = ProjectivePoint::<C>::generator() * w;
+        let vec_yn_inv = Self::powers(n, &invert_challenge::<C>(&y)?);
+        let base_h: Vec<_> = (0..n).map(|i| vec_h[i] * vec_yn_inv[i]).collect();
+        let target = self.point_a + self.point_s * x - *h * self.mu + point_q * self.tx
+            + (0..n).map(|i| vec_g[i] * -z + base_h[i] * (z * vec_yn[i] + z * z * vec_2n[i])).sum::<ProjectivePoint<C>>();
+        anyhow::ensure!(self.bulletproof.target == target, "Range proof target mismatch");
+        self.bulletproof.verify_inner_product::<D>(vec_g, &base_h, &point_q)
+    }
+}
@@ -0,0 +1,467 @@
+use std::{cmp::Ordering, collections::HashSet, ops::Deref, sync::Arc};
+#[cfg(feature = "mmap")]
+use std::{fs::File, marker::PhantomData, path::Path};
+
+use digest::{Digest, Output};
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, ProjectivePoint};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::error::VerifyError;
+
+/// An anonymity set: the public keys an incognito signature hides the signer among.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ring<C: CurveArithmetic> {
+    pks: Vec<ProjectivePoint<C>>
+}
+
+impl <C: CurveArithmetic> Ring<C> {
+    pub fn new(pks: Vec<ProjectivePoint<C>>) -> Self {
+        Self {
+            pks
+        }
+    }
+
+    /// Builds a ring of `size` keys holding `signer_pk` at a random position, with the
+    /// remaining members drawn without repetition from `candidate_pool`.
+    /// Returns the ring together with the signer's index.
+    pub fn random_with_signer<R: Rng + ?Sized>(
+        rng: &mut R,
+        signer_pk: &ProjectivePoint<C>,
+        size: usize,
+        candidate_pool: &[ProjectivePoint<C>]
+    ) -> anyhow::Result<(Self, usize)> {
+        anyhow::ensure!(size >= 1, "Ring size must be at least 1");
+        let decoys: Vec<_> = candidate_pool.iter().filter(|pk| *pk != signer_pk).collect();
+        anyhow::ensure!(decoys.len() >= size - 1, "Candidate pool has {} decoys, {} required", decoys.len(), size - 1);
+
+        let mut pks: Vec<_> = decoys.choose_multiple(rng, size - 1).map(|pk| **pk).collect();
+        let index = rng.gen_range(0..size);
+        pks.insert(index, *signer_pk);
+        Ok((Self::new(pks), index))
+    }
+
+    /// Same as `random_with_signer` for `pool[signer_index]`, with decoys and position
+    /// drawn from `seed`, so the same seed always yields the same ring. Meant for
+    /// reproducible benchmarks and tests: anyone who knows the seed learns the signer.
+    pub fn deterministic_decoys(
+        seed: [u8; 32],
+        signer_index: usize,
+        pool: &[ProjectivePoint<C>],
+        size: usize
+    ) -> anyhow::Result<(Self, usize)> {
+        let signer_pk = pool.get(signer_index).ok_or_else(|| anyhow::anyhow!("Index {} out of range", signer_index))?;
+        Self::random_with_signer(&mut ChaCha20Rng::from_seed(seed), signer_pk, size, pool)
+    }
+
+    pub fn as_slice(&self) -> &[ProjectivePoint<C>] {
+        &self.pks
+    }
+
+    pub fn into_inner(self) -> Vec<ProjectivePoint<C>> {
+        self.pks
+    }
+}
+
+impl <C: CurveArithmetic> Ring<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Fails if the ring holds the same key more than once.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure_distinct::<C>(&self.pks)
+    }
+
+    /// Sorts the members by `cmp_by_encoding`, so the same set of keys always yields
+    /// the same ring and `RingCommitment::id`. Signer indices must be looked up again
+    /// afterwards.
+    pub fn canonicalize(&mut self) {
+        self.pks.sort_by(cmp_by_encoding::<C>);
+    }
+}
+
+/// Orders points by their compressed encoding.
+pub fn cmp_by_encoding<C: CurveArithmetic>(a: &ProjectivePoint<C>, b: &ProjectivePoint<C>) -> Ordering
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    a.to_bytes().as_ref().cmp(b.to_bytes().as_ref())
+}
+
+/// A ring sent once and then referred to by `id`, so that signatures over it do not
+/// have to carry the keys. Keys keep the order they were given in, which is the order
+/// signer indices and the proofs refer to.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RingCommitment<C: CurveArithmetic> {
+    ring: Ring<C>
+}
+
+impl <C: CurveArithmetic> RingCommitment<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Fails if the ring holds the same key more than once.
+    pub fn new(ring: Ring<C>) -> anyhow::Result<Self> {
+        ring.validate()?;
+        Ok(Self {
+            ring
+        })
+    }
+
+    pub fn ring(&self) -> &Ring<C> {
+        &self.ring
+    }
+
+    /// Identifier of the ring, equal to `VerifiedSignature::ring_hash` for signatures
+    /// accepted against it.
+    pub fn id<D: Digest>(&self) -> Output<D> {
+        ring_hash::<C, D>(&self.ring)
+    }
+
+    /// Key count as a little-endian `u32`, followed by the keys' compressed encodings.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.ring.len() * Self::point_len());
+        bytes.extend_from_slice(&(self.ring.len() as u32).to_le_bytes());
+        for pk in self.ring.iter() {
+            bytes.extend_from_slice(pk.to_bytes().as_ref());
+        }
+        bytes
+    }
+
+    /// Inverse of `to_bytes`; rejects invalid or repeated keys and trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        anyhow::ensure!(bytes.len() >= 4, "Ring commitment is missing its length");
+        let (count, rest) = bytes.split_at(4);
+        let count = u32::from_le_bytes(count.try_into()?) as usize;
+        let point_len = Self::point_len();
+        anyhow::ensure!(rest.len() == count * point_len, "Ring commitment of {} keys has {} bytes of keys", count, rest.len());
+        let pks = rest.chunks(point_len).enumerate().map(|(index, chunk)| {
+            let mut repr = <ProjectivePoint<C> as GroupEncoding>::Repr::default();
+            repr.as_mut().copy_from_slice(chunk);
+            Option::<ProjectivePoint<C>>::from(ProjectivePoint::<C>::from_bytes(&repr))
+                .ok_or_else(|| anyhow::anyhow!("Invalid public key at index {}", index))
+        }).collect::<anyhow::Result<Vec<_>>>()?;
+        Self::new(Ring::new(pks))
+    }
+
+    fn point_len() -> usize {
+        <ProjectivePoint<C> as GroupEncoding>::Repr::default().as_ref().len()
+    }
+}
+
+/// Digest of the ring's point encodings, in order.
+pub(crate) fn ring_hash<C: CurveArithmetic, D: Digest>(pks: &[ProjectivePoint<C>]) -> Output<D>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    let mut hasher = D::new().chain_update(b"ring");
+    for pk in pks {
+        Digest::update(&mut hasher, pk.to_bytes());
+    }
+    hasher.finalize()
+}
+
+/// Rejects the empty ring. Every check over it would be an empty sum, so nothing
+/// else stops it from verifying vacuously.
+pub(crate) fn ensure_nonempty<C: CurveArithmetic>(pks: &[ProjectivePoint<C>]) -> Result<(), VerifyError> {
+    if pks.is_empty() {
+        return Err(VerifyError::EmptyRing);
+    }
+    Ok(())
+}
+
+/// Random access to a shared key table, such as a ledger's, from which
+/// `IncognitoParams::convert_indexed` and `verify_indexed` pick their rings. Tables too
+/// large to keep in memory can be served from a file with `MmapKeySource`.
+pub trait KeySource<C: CurveArithmetic> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Key at `index`, failing if it is out of range or cannot be decoded.
+    fn get(&self, index: usize) -> anyhow::Result<ProjectivePoint<C>>;
+}
+
+impl <C: CurveArithmetic> KeySource<C> for [ProjectivePoint<C>] {
+    fn len(&self) -> usize {
+        <[ProjectivePoint<C>]>::len(self)
+    }
+
+    fn get(&self, index: usize) -> anyhow::Result<ProjectivePoint<C>> {
+        <[ProjectivePoint<C>]>::get(self, index).copied().ok_or_else(|| anyhow::anyhow!("Ring index {} out of range", index))
+    }
+}
+
+impl <C: CurveArithmetic> KeySource<C> for Vec<ProjectivePoint<C>> {
+    fn len(&self) -> usize {
+        KeySource::<C>::len(self.as_slice())
+    }
+
+    fn get(&self, index: usize) -> anyhow::Result<ProjectivePoint<C>> {
+        KeySource::<C>::get(self.as_slice(), index)
+    }
+}
+
+/// Key tables shared between tasks, as in the settlement verifier.
+impl <C: CurveArithmetic, K: KeySource<C> + ?Sized> KeySource<C> for Arc<K> {
+    fn len(&self) -> usize {
+        K::len(self)
+    }
+
+    fn get(&self, index: usize) -> anyhow::Result<ProjectivePoint<C>> {
+        K::get(self, index)
+    }
+}
+
+/// Key table in a memory-mapped file of `GroupEncoding` encodings laid end to end, e.g.
+/// 33-byte compressed points on secp256k1. Keys are decoded on each `get`, so only the
+/// pages of the keys a ring uses are read.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct MmapKeySource<C: CurveArithmetic> {
+    map: memmap2::Mmap,
+    _curve: PhantomData<C>
+}
+
+#[cfg(feature = "mmap")]
+impl <C: CurveArithmetic> MmapKeySource<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    /// Maps the key file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, for as
+    /// long as the returned source is alive. Changing it turns the mapped keys into
+    /// undefined behaviour, not merely into keys that fail to decode.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: forwarded to the caller, see above.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        anyhow::ensure!(map.len() % Self::key_len() == 0, "Key file of {} bytes is not a whole number of {}-byte keys", map.len(), Self::key_len());
+        Ok(Self {
+            map,
+            _curve: PhantomData
+        })
+    }
+
+    fn key_len() -> usize {
+        <ProjectivePoint<C> as GroupEncoding>::Repr::default().as_ref().len()
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl <C: CurveArithmetic> KeySource<C> for MmapKeySource<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    fn len(&self) -> usize {
+        self.map.len() / Self::key_len()
+    }
+
+    fn get(&self, index: usize) -> anyhow::Result<ProjectivePoint<C>> {
+        anyhow::ensure!(index < KeySource::len(self), "Ring index {} out of range", index);
+        let mut repr = <ProjectivePoint<C> as GroupEncoding>::Repr::default();
+        let start = index * Self::key_len();
+        repr.as_mut().copy_from_slice(&self.map[start..start + Self::key_len()]);
+        Option::from(ProjectivePoint::<C>::from_bytes(&repr)).ok_or_else(|| anyhow::anyhow!("Invalid key at index {}", index))
+    }
+}
+
+/// Looks up the ring named by `ids` in a shared `key_table`.
+pub(crate) fn ring_from_table<C: CurveArithmetic, K: KeySource<C> + ?Sized>(key_table: &K, ids: &[usize]) -> anyhow::Result<Vec<ProjectivePoint<C>>> {
+    ids.iter().map(|i| key_table.get(*i)).collect()
+}
+
+/// Rejects rings containing a repeated key. A repeated key lets its owner set both
+/// positions in the membership proof, so one signer counts twice towards a threshold,
+/// and the ring is smaller than it looks.
+pub(crate) fn ensure_distinct<C: CurveArithmetic>(pks: &[ProjectivePoint<C>]) -> anyhow::Result<()>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    let mut seen = HashSet::with_capacity(pks.len());
+    for (index, pk) in pks.iter().enumerate() {
+        anyhow::ensure!(seen.insert(pk.to_bytes().as_ref().to_vec()), "Duplicate public key at index {}", index);
+    }
+    Ok(())
+}
+
+impl <C: CurveArithmetic> Deref for Ring<C> {
+    type Target = [ProjectivePoint<C>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.pks
+    }
+}
+
+impl <C: CurveArithmetic> From<Vec<ProjectivePoint<C>>> for Ring<C> {
+    fn from(pks: Vec<ProjectivePoint<C>>) -> Self {
+        Self::new(pks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::{rngs::ThreadRng, seq::SliceRandom};
+
+    use crate::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::{cmp_by_encoding, Ring, RingCommitment};
+
+    #[test]
+    fn test_random_with_signer() {
+        let mut rng = ThreadRng::default();
+        let pool: Vec<_> = (0..32).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+
+        for _ in 0..16 {
+            let (ring, index) = Ring::<Secp256k1>::random_with_signer(&mut rng, &pk, 8, &pool).unwrap();
+            assert_eq!(ring.len(), 8);
+            assert_eq!(ring[index], pk);
+        }
+        assert!(Ring::<Secp256k1>::random_with_signer(&mut rng, &pk, 34, &pool).is_err());
+
+        let (ring, index) = Ring::<Secp256k1>::random_with_signer(&mut rng, &pk, 8, &pool).unwrap();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &message);
+        let incsig = params.convert::<Sha256>(&ring, &message, &signature, index).unwrap();
+        params.verify::<Sha256>(&ring, &message, &incsig).unwrap();
+    }
+
+    #[test]
+    fn test_deterministic_decoys() {
+        let mut rng = ThreadRng::default();
+        let pool: Vec<_> = (0..32).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+
+        let (ring, index) = Ring::<Secp256k1>::deterministic_decoys([7; 32], 5, &pool, 8).unwrap();
+        assert_eq!(ring.len(), 8);
+        assert_eq!(ring[index], pool[5]);
+        assert_eq!(Ring::<Secp256k1>::deterministic_decoys([7; 32], 5, &pool, 8).unwrap(), (ring.clone(), index));
+        assert_ne!(Ring::<Secp256k1>::deterministic_decoys([8; 32], 5, &pool, 8).unwrap().0, ring);
+        ring.validate().unwrap();
+
+        assert!(Ring::<Secp256k1>::deterministic_decoys([7; 32], 32, &pool, 8).is_err());
+        assert!(Ring::<Secp256k1>::deterministic_decoys([7; 32], 5, &pool, 33).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let mut rng = ThreadRng::default();
+        let sks: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let mut pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[2], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, 2).unwrap();
+        Ring::<Secp256k1>::new(pks.clone()).validate().unwrap();
+
+        pks[5] = pks[2];
+        assert!(Ring::<Secp256k1>::new(pks.clone()).validate().is_err());
+        assert!(params.convert::<Sha256>(&pks, &message, &signature, 2).is_err());
+        assert!(params.verify::<Sha256>(&pks, &message, &incsig).is_err());
+        assert!(params.verify_batch::<Sha256>(&[(&pks, &message, &incsig)]).is_err());
+    }
+
+    #[test]
+    fn test_ring_commitment() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+
+        // Sender: the ring once, then signatures tagged with its id.
+        let commitment = RingCommitment::<Secp256k1>::new(Ring::new(pks.clone())).unwrap();
+        let ring_bytes = commitment.to_bytes();
+        assert_eq!(ring_bytes.len(), 4 + 33 * n);
+        let id = commitment.id::<Sha256>();
+        let tagged: Vec<_> = [1, 6].into_iter().map(|index| {
+            let message = vec![index as u8, 3, 6, 9];
+            let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+            (id, message.clone(), params.convert::<Sha256>(&pks, &message, &signature, index).unwrap())
+        }).collect();
+
+        // Receiver: looks each signature's ring up by id.
+        let received = RingCommitment::<Secp256k1>::from_bytes(&ring_bytes).unwrap();
+        assert_eq!(received, commitment);
+        let rings = HashMap::from([(received.id::<Sha256>(), received)]);
+        for (id, message, incsig) in &tagged {
+            let ring = rings[id].ring();
+            let verified = params.verify_with_ring::<Sha256>(ring, message, incsig).unwrap();
+            assert_eq!(verified.ring_hash(), id);
+        }
+
+        assert!(RingCommitment::<Secp256k1>::from_bytes(&ring_bytes[..ring_bytes.len() - 1]).is_err());
+        let mut duplicated = ring_bytes.clone();
+        duplicated.copy_within(4..37, 37);
+        assert!(RingCommitment::<Secp256k1>::from_bytes(&duplicated).is_err());
+        let mut corrupted = ring_bytes.clone();
+        corrupted[5..37].fill(0xff);
+        assert!(RingCommitment::<Secp256k1>::from_bytes(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let mut rng = ThreadRng::default();
+        let pks: Vec<_> = (0..16).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let mut shuffled = pks.clone();
+        shuffled.shuffle(&mut rng);
+        shuffled.swap(0, 15);
+
+        let mut ring = Ring::<Secp256k1>::new(pks);
+        let mut other = Ring::<Secp256k1>::new(shuffled);
+        assert_ne!(ring, other);
+        ring.canonicalize();
+        other.canonicalize();
+        assert_eq!(ring, other);
+        assert!(ring.windows(2).all(|pair| cmp_by_encoding::<Secp256k1>(&pair[0], &pair[1]).is_lt()));
+
+        let id = RingCommitment::new(ring).unwrap().id::<Sha256>();
+        assert_eq!(RingCommitment::new(other).unwrap().id::<Sha256>(), id);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_key_source() {
+        use elliptic_curve::group::GroupEncoding;
+
+        use super::{KeySource, MmapKeySource};
+
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 64>::new();
+        let sks: Vec<_> = (0..10_000).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let path = std::env::temp_dir().join(format!("incognito-keys-{}", std::process::id()));
+        std::fs::write(&path, pks.iter().flat_map(|pk| pk.to_bytes()).collect::<Vec<_>>()).unwrap();
+        // Safety: the file is private to this test and left alone until `mmap` is dropped.
+        let mmap = unsafe { MmapKeySource::<Secp256k1>::open(&path) }.unwrap();
+        let source: &dyn KeySource<Secp256k1> = &mmap;
+        assert_eq!(source.len(), 10_000);
+        assert_eq!(source.get(9_999).unwrap(), pks[9_999]);
+        assert!(source.get(10_000).is_err());
+
+        let ids: Vec<_> = rand::seq::index::sample(&mut rng, 10_000, 64).into_vec();
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[ids[17]], &message);
+        let incsig = params.convert_indexed::<Sha256>(source, &ids, &message, &signature, 17).unwrap();
+        params.verify_indexed::<Sha256>(source, &ids, &message, &incsig).unwrap();
+        params.verify_indexed::<Sha256>(&pks, &ids, &message, &incsig).unwrap();
+        assert!(params.verify_indexed::<Sha256>(source, &ids[..32], &message, &incsig).is_err());
+
+        drop(mmap);
+        std::fs::write(&path, [0; 50]).unwrap();
+        // Safety: as above.
+        assert!(unsafe { MmapKeySource::<Secp256k1>::open(&path) }.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,105 +1,407 @@
 use std::ops::{Add, Mul};
 
-use digest::{Digest, FixedOutput};
-use elliptic_curve::{group::{Curve, GroupEncoding}, ops::Reduce, AffinePoint, CurveArithmetic, Field, FieldBytesSize, Group, ProjectivePoint, Scalar};
-use rand::rngs::ThreadRng;
+use digest::{Digest, Output};
+use elliptic_curve::{consts::U32, group::{Curve, GroupEncoding}, point::{AffineCoordinates, DecompressPoint}, subtle::Choice, zeroize::Zeroizing, AffinePoint, CurveArithmetic, Field, FieldBytes, Group, PrimeField, ProjectivePoint, Scalar};
+use rand::{rngs::ThreadRng, CryptoRng, RngCore};
+
+#[cfg(feature = "zeroize")]
+use elliptic_curve::zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::challenge::{check_digest_size, reduce_digest, ChallengeDerive, NarrowReduce};
+#[cfg(feature = "serde")]
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned", from = "SchnorrSignatureSerde<C>", into = "SchnorrSignatureSerde<C>")]
+/// A Schnorr signature `(R, z)`. Signing always picks a nonce whose `R` has an even
+/// y-coordinate, which `to_bytes` relies on; signatures decoded through serde may carry
+/// either parity, as older signers did not normalize `R`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
 pub struct SchnorrSignature<C: CurveArithmetic>{
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
     pub point_r: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
     pub z: Scalar<C>
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
-#[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned")]
-pub struct SchnorrSignatureSerde<C: CurveArithmetic>{
-    pub point_r: AffinePoint<C>,
-    pub z: Scalar<C>
+/// An adaptor pre-signature: a Schnorr signature on `R + T` that only becomes valid
+/// once it is completed with the discrete log of the adaptor point `T`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding"))]
+pub struct SchnorrPreSignature<C: CurveArithmetic>{
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    pub point_r: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::point_serde"))]
+    pub point_t: ProjectivePoint<C>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_util::scalar_serde::serialize::<C, _>", deserialize_with = "crate::serde_util::scalar_serde::deserialize::<C, _>"))]
+    pub s: Scalar<C>
+}
+
+fn has_odd_y<C: CurveArithmetic>(point: &ProjectivePoint<C>) -> bool {
+    bool::from(point.to_affine().y_is_odd())
 }
 
 impl <C: CurveArithmetic> SchnorrSignature<C>
 where
     ProjectivePoint<C>: GroupEncoding
 {
-    pub(crate) fn challenge<D>(point_r: &ProjectivePoint<C>, m: &[u8]) -> Scalar<C>
+    /// Challenge `H(R || m)`, passing both segments to `tap` as they are hashed.
+    pub(crate) fn challenge<D>(point_r: &ProjectivePoint<C>, m: &[u8], tap: &mut dyn FnMut(&[u8])) -> Scalar<C>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        <Scalar<C> as Reduce<C::Uint>>::reduce_bytes(
-            &D::new()
-            .chain_update(point_r.to_bytes())
-            .chain_update(m)
-            .finalize()
-        )
+        let point_r = point_r.to_bytes();
+        let parts = [point_r.as_ref(), m];
+        parts.iter().for_each(|part| tap(part));
+        <NarrowReduce as ChallengeDerive<C>>::hash_to_scalar::<D>(&parts)
     }
 
     pub fn sign<D>(sk: &Scalar<C>, message: &[u8]) -> Self
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
     {
-        let mut rng = ThreadRng::default();
-        let r = Scalar::<C>::random(&mut rng);
-        let point_r = ProjectivePoint::<C>::generator() * r;
-        let c = Self::challenge::<D>(&point_r, &message);
-        let z = r + sk.mul(c);
+        let mut signer = SchnorrSigner::<C, D>::new();
+        signer.update(message);
+        signer.finalize(sk)
+    }
+
+    /// Signs each of `messages`, drawing all nonces from `rng` in one pass instead of
+    /// setting up a `ThreadRng` per signature.
+    pub fn sign_batch<D, R>(sk: &Scalar<C>, messages: &[&[u8]], rng: &mut R) -> Vec<Self>
+    where
+        D: Digest,
+        R: RngCore + CryptoRng
+    {
+        let nonces: Vec<_> = messages.iter().map(|_| Scalar::<C>::random(&mut *rng)).collect();
+        messages.iter().zip(nonces).map(|(message, r)| {
+            let mut signer = SchnorrSigner::<C, D>::with_nonce(r);
+            signer.update(message);
+            signer.finalize(sk)
+        }).collect()
+    }
+
+    /// Signs with the nonce derived from `H(sk, extra_entropy, 32 bytes of rng, message)`.
+    ///
+    /// The secret key and message make the nonce unique per message even if `rng` is
+    /// broken or returns constant bytes, while fresh randomness keeps the nonce hidden
+    /// from fault and side-channel attacks that exploit purely deterministic nonces.
+    pub fn sign_hedged<D, R>(sk: &Scalar<C>, message: &[u8], extra_entropy: &[u8], rng: &mut R) -> Self
+    where
+        D: Digest,
+        R: RngCore + CryptoRng
+    {
+        let mut random = [0u8; 32];
+        rng.fill_bytes(&mut random);
+        let digest = D::new()
+            .chain_update(b"incognito-hedged-nonce")
+            .chain_update(sk.to_repr())
+            .chain_update((extra_entropy.len() as u64).to_le_bytes())
+            .chain_update(extra_entropy)
+            .chain_update(random)
+            .chain_update(message)
+            .finalize();
+        let mut signer = SchnorrSigner::<C, D>::with_nonce(reduce_digest::<C>(&digest));
+        signer.update(message);
+        signer.finalize(sk)
+    }
+
+    /// Same as `sign`, with the challenge derived by `H` instead of `NarrowReduce`.
+    pub fn sign_with<D, H>(sk: &Scalar<C>, message: &[u8]) -> Self
+    where
+        D: Digest,
+        H: ChallengeDerive<C>
+    {
+        let SchnorrSigner { r, point_r, .. } = SchnorrSigner::<C, D>::new();
+        let c = H::hash_to_scalar::<D>(&[point_r.to_bytes().as_ref(), message]);
         Self {
             point_r,
-            z
+            z: r + sk.mul(c)
         }
     }
 
     pub fn verify<D>(&self, pk: &ProjectivePoint<C>, message: &[u8]) -> anyhow::Result<()>
     where
-        D: Digest + FixedOutput<OutputSize = FieldBytesSize<C>>
+        D: Digest
+    {
+        self.verify_with::<D, NarrowReduce>(pk, message)
+    }
+
+    /// Verifies a signature produced by `sign_with` with the same `H`.
+    pub fn verify_with<D, H>(&self, pk: &ProjectivePoint<C>, message: &[u8]) -> anyhow::Result<()>
+    where
+        D: Digest,
+        H: ChallengeDerive<C>
     {
+        check_digest_size::<C, D>()?;
         let Self {
             point_r,
             z,
             ..
         } = self;
-        if pk.mul(Self::challenge::<D>(point_r, message)).add(point_r) == ProjectivePoint::<C>::generator() * z{
+        let c = H::hash_to_scalar::<D>(&[point_r.to_bytes().as_ref(), message]);
+        if pk.mul(c).add(point_r) == ProjectivePoint::<C>::generator() * z{
             Ok(())
         } else {
             anyhow::bail!("Invalid Schnorr signature")
         }
     }
+
+    /// Signs a digest computed by the caller. `R` is still bound into the challenge,
+    /// only the hashing of the message itself is skipped. The digest is signed under a
+    /// domain tag, see `encode_prehash`.
+    pub fn sign_prehashed<D>(sk: &Scalar<C>, prehash: &Output<D>) -> Self
+    where
+        D: Digest
+    {
+        Self::sign::<D>(sk, &encode_prehash::<D>(prehash))
+    }
+
+    pub fn verify_prehashed<D>(&self, pk: &ProjectivePoint<C>, prehash: &Output<D>) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify::<D>(pk, &encode_prehash::<D>(prehash))
+    }
+
+    /// Signs a list of messages, see `encode_messages`.
+    pub fn sign_messages<D>(sk: &Scalar<C>, messages: &[&[u8]]) -> Self
+    where
+        D: Digest
+    {
+        Self::sign::<D>(sk, &encode_messages(messages))
+    }
+
+    /// Accepts only the exact list, in order, that was passed to `sign_messages`.
+    pub fn verify_messages<D>(&self, pk: &ProjectivePoint<C>, messages: &[&[u8]]) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        self.verify::<D>(pk, &encode_messages(messages))
+    }
+
+    /// Produces a pre-signature on `message` locked to `point_t`.
+    ///
+    /// The nonce is redrawn until `R + T` has an even y-coordinate, the `R` of the
+    /// completed signature, and is wiped once `s` is computed.
+    pub fn sign_adaptor<D>(sk: &Scalar<C>, message: &[u8], point_t: &ProjectivePoint<C>) -> SchnorrPreSignature<C>
+    where
+        D: Digest
+    {
+        let mut rng = ThreadRng::default();
+        let mut r = Zeroizing::new(Scalar::<C>::ZERO);
+        let point_r = loop {
+            *r = Scalar::<C>::random(&mut rng);
+            let point_r = ProjectivePoint::<C>::generator() * *r;
+            if !has_odd_y::<C>(&(point_r + point_t)) {
+                break point_r;
+            }
+        };
+        let c = Self::challenge::<D>(&(point_r + point_t), message, &mut |_| ());
+        SchnorrPreSignature {
+            point_r,
+            point_t: *point_t,
+            s: *r + sk.mul(c)
+        }
+    }
+}
+
+/// Encodes a digest as the message that `sign_prehashed` actually signs: a domain tag,
+/// then the digest. A prehashed signature therefore never verifies as a plain one over
+/// the digest bytes, nor the other way round.
+pub fn encode_prehash<D: Digest>(prehash: &Output<D>) -> Vec<u8> {
+    [b"incognito-prehash".as_slice(), prehash.as_slice()].concat()
+}
+
+/// Encodes a list of messages as the single message that is actually signed: a
+/// domain tag and the number of messages, then each message prefixed by its length.
+/// Reordering, merging or splitting messages therefore changes the encoding.
+pub fn encode_messages(messages: &[&[u8]]) -> Vec<u8> {
+    let mut encoded = b"incognito-messages".to_vec();
+    encoded.extend_from_slice(&(messages.len() as u64).to_le_bytes());
+    for message in messages {
+        encoded.extend_from_slice(&(message.len() as u64).to_le_bytes());
+        encoded.extend_from_slice(message);
+    }
+    encoded
 }
 
-impl <C:CurveArithmetic> From<SchnorrSignature<C>> for SchnorrSignatureSerde<C>
+impl <C: CurveArithmetic> SchnorrPreSignature<C>
 where
-    Scalar<C>: Serialize + DeserializeOwned,
-    AffinePoint<C>: Serialize + DeserializeOwned
+    ProjectivePoint<C>: GroupEncoding
 {
-    fn from(value: SchnorrSignature<C>) -> Self {
+    pub fn verify_adaptor<D>(&self, pk: &ProjectivePoint<C>, message: &[u8]) -> anyhow::Result<()>
+    where
+        D: Digest
+    {
+        let c = SchnorrSignature::<C>::challenge::<D>(&(self.point_r + self.point_t), message, &mut |_| ());
+        anyhow::ensure!(
+            ProjectivePoint::<C>::generator() * self.s == self.point_r + pk.mul(c),
+            "Invalid Schnorr pre-signature"
+        );
+        Ok(())
+    }
+
+    /// Completes the pre-signature with `t`, the discrete log of `point_t`.
+    pub fn complete(&self, t: &Scalar<C>) -> anyhow::Result<SchnorrSignature<C>> {
+        anyhow::ensure!(ProjectivePoint::<C>::generator() * t == self.point_t, "Adaptor secret does not match T");
+        let point_r = self.point_r + self.point_t;
+        anyhow::ensure!(!has_odd_y::<C>(&point_r), "R + T has an odd y-coordinate");
+        Ok(SchnorrSignature {
+            point_r,
+            z: self.s + t
+        })
+    }
+
+    /// Recovers the adaptor secret from the completed signature.
+    pub fn extract(&self, signature: &SchnorrSignature<C>) -> anyhow::Result<Scalar<C>> {
+        anyhow::ensure!(signature.point_r == self.point_r + self.point_t, "Signature was not completed from this pre-signature");
+        let t = signature.z - self.s;
+        anyhow::ensure!(ProjectivePoint::<C>::generator() * t == self.point_t, "Extracted secret does not match T");
+        Ok(t)
+    }
+}
+
+/// Incremental signer for messages that are too large to buffer.
+///
+/// The nonce is drawn when the signer is created, since `R` is hashed before the
+/// message; `finalize` consumes the signer so the nonce cannot be reused.
+pub struct SchnorrSigner<C: CurveArithmetic, D> {
+    r: Scalar<C>,
+    point_r: ProjectivePoint<C>,
+    digest: D
+}
+
+impl <C: CurveArithmetic, D> SchnorrSigner<C, D>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    pub fn new() -> Self {
+        Self::with_nonce(Scalar::<C>::random(&mut ThreadRng::default()))
+    }
+
+    pub(crate) fn with_nonce(mut r: Scalar<C>) -> Self {
+        let mut point_r = ProjectivePoint::<C>::generator() * r;
+        // Keep `R` with an even y-coordinate so the signature has a 64-byte x-only encoding.
+        if has_odd_y::<C>(&point_r) {
+            r = -r;
+            point_r = -point_r;
+        }
         Self {
-            point_r: value.point_r.to_affine(),
-            z: value.z
+            r,
+            point_r,
+            digest: D::new().chain_update(point_r.to_bytes())
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        Digest::update(&mut self.digest, chunk);
+    }
+
+    pub fn finalize(mut self, sk: &Scalar<C>) -> SchnorrSignature<C> {
+        debug_assert!(check_digest_size::<C, D>().is_ok(), "digest shorter than the scalar field");
+        let c = reduce_digest::<C>(&std::mem::replace(&mut self.digest, D::new()).finalize());
+        SchnorrSignature {
+            point_r: self.point_r,
+            z: self.r + sk.mul(c)
         }
     }
 }
 
-impl <C:CurveArithmetic> From<SchnorrSignatureSerde<C>> for SchnorrSignature<C>
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic, D> Zeroize for SchnorrSigner<C, D> {
+    fn zeroize(&mut self) {
+        self.r.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic, D> Drop for SchnorrSigner<C, D> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic, D> ZeroizeOnDrop for SchnorrSigner<C, D> {}
+
+impl <C: CurveArithmetic, D> Default for SchnorrSigner<C, D>
+where
+    ProjectivePoint<C>: GroupEncoding,
+    D: Digest
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <C> SchnorrSignature<C>
+where
+    C: CurveArithmetic<FieldBytesSize = U32>,
+    AffinePoint<C>: DecompressPoint<C>
+{
+    /// Encodes the signature as `x(R) || z`. `R` must have an even y-coordinate,
+    /// which holds for every signature produced by signing or by `from_bytes`.
+    pub fn to_bytes(&self) -> anyhow::Result<[u8; 64]> {
+        let point_r = self.point_r.to_affine();
+        anyhow::ensure!(!bool::from(point_r.y_is_odd()), "R has an odd y-coordinate");
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&point_r.x());
+        bytes[32..].copy_from_slice(&self.z.to_repr());
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8; 64]) -> anyhow::Result<Self> {
+        let x = FieldBytes::<C>::clone_from_slice(&bytes[..32]);
+        let z = FieldBytes::<C>::clone_from_slice(&bytes[32..]);
+        let point_r = Option::<AffinePoint<C>>::from(AffinePoint::<C>::decompress(&x, Choice::from(0)))
+            .ok_or_else(|| anyhow::anyhow!("Invalid x-coordinate for R"))?;
+        let z = Option::<Scalar<C>>::from(Scalar::<C>::from_repr(z))
+            .ok_or_else(|| anyhow::anyhow!("Non-canonical scalar z"))?;
+        Ok(Self {
+            point_r: ProjectivePoint::<C>::from(point_r),
+            z
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <C: CurveArithmetic> SchnorrSignature<C>
 where
     Scalar<C>: Serialize + DeserializeOwned,
-    AffinePoint<C>: Serialize + DeserializeOwned
+    AffinePoint<C>: Serialize + DeserializeOwned,
+    ProjectivePoint<C>: GroupEncoding
 {
-    fn from(value: SchnorrSignatureSerde<C>) -> Self {
-        Self {
-            point_r: ProjectivePoint::<C>::from(value.point_r),
-            z: value.z
-        }
+    /// Bincode with varint lengths, see `serde_util::to_bytes_compact`.
+    pub fn to_bytes_compact(&self) -> anyhow::Result<Vec<u8>> {
+        crate::serde_util::to_bytes_compact(self)
+    }
+
+    pub fn from_bytes_compact(bytes: &[u8]) -> anyhow::Result<Self> {
+        crate::serde_util::from_bytes_compact(bytes)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl <'a, C: CurveArithmetic> arbitrary::Arbitrary<'a> for SchnorrSignature<C> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            point_r: crate::fuzzing::point::<C>(u)?,
+            z: crate::fuzzing::scalar::<C>(u)?
+        })
     }
 }
 
 #[cfg(test)]
 mod tests{
     use elliptic_curve::Field;
-    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
-    use rand::rngs::ThreadRng;
+    use k256::{sha2::{Digest, Sha256}, ProjectivePoint, Scalar, Secp256k1};
+    use rand::{rngs::ThreadRng, CryptoRng, RngCore};
+
+    use crate::challenge::WideReduce;
 
-    use super::SchnorrSignature;
+    use super::{SchnorrSignature, SchnorrSigner};
 
     #[test]
     fn test_sign() {
@@ -125,6 +427,7 @@ mod tests{
         sig.verify::<Sha256>(&pk, &m2).unwrap();
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_serialization() {
         let mut rng = ThreadRng::default();
@@ -137,4 +440,212 @@ mod tests{
         let sig_new: SchnorrSignature<Secp256k1> = bincode::deserialize(&bincode::serialize(&sig).unwrap()).unwrap();
         sig_new.verify::<Sha256>(&pk, &m).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fixed_bytes() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+
+        let m = [0, 3, 5, 8, 1];
+        let sig = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &m);
+        let bytes = sig.to_bytes().unwrap();
+        let sig_new = SchnorrSignature::<Secp256k1>::from_bytes(&bytes).unwrap();
+        assert_eq!(sig, sig_new);
+        sig_new.verify::<Sha256>(&pk, &m).unwrap();
+
+        let mut bytes_bad = bytes;
+        bytes_bad[32..].copy_from_slice(&[0xff; 32]);
+        assert!(SchnorrSignature::<Secp256k1>::from_bytes(&bytes_bad).is_err());
+
+        // The x-only encoding cannot carry an odd `R`.
+        let sig_odd = SchnorrSignature::<Secp256k1> { point_r: -sig.point_r, z: sig.z };
+        assert!(sig_odd.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_adaptor() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let t = Scalar::random(&mut rng);
+        let point_t = ProjectivePoint::GENERATOR * t;
+
+        let m = [0, 3, 5, 8, 1];
+        let presig = SchnorrSignature::<Secp256k1>::sign_adaptor::<Sha256>(&sk, &m, &point_t);
+        presig.verify_adaptor::<Sha256>(&pk, &m).unwrap();
+        assert!(presig.verify_adaptor::<Sha256>(&pk, &[0, 3, 5]).is_err());
+        assert!(presig.complete(&Scalar::random(&mut rng)).is_err());
+
+        let sig = presig.complete(&t).unwrap();
+        sig.verify::<Sha256>(&pk, &m).unwrap();
+        assert_eq!(presig.extract(&sig).unwrap(), t);
+
+        // Completed signatures have the 64-byte encoding whichever parity R and T have.
+        for _ in 0..16 {
+            let t = Scalar::random(&mut rng);
+            let presig = SchnorrSignature::<Secp256k1>::sign_adaptor::<Sha256>(&sk, &m, &(ProjectivePoint::GENERATOR * t));
+            let sig = presig.complete(&t).unwrap();
+            assert_eq!(SchnorrSignature::<Secp256k1>::from_bytes(&sig.to_bytes().unwrap()).unwrap(), sig);
+        }
+    }
+
+    #[test]
+    fn test_streaming_signer() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let nonce = Scalar::random(&mut rng);
+        let message: Vec<u8> = (0..=255).collect();
+
+        let mut whole = SchnorrSigner::<Secp256k1, Sha256>::with_nonce(nonce);
+        whole.update(&message);
+        let whole = whole.finalize(&sk);
+
+        let mut chunked = SchnorrSigner::<Secp256k1, Sha256>::with_nonce(nonce);
+        for chunk in [&message[..7], &message[7..100], &message[100..]] {
+            chunked.update(chunk);
+        }
+        let chunked = chunked.finalize(&sk);
+
+        assert_eq!(whole, chunked);
+        chunked.verify::<Sha256>(&pk, &message).unwrap();
+    }
+
+    #[test]
+    fn test_prehashed() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let message = [7u8; 32];
+        let prehash = Sha256::digest(message);
+
+        let signature = SchnorrSignature::<Secp256k1>::sign_prehashed::<Sha256>(&sk, &prehash);
+        signature.verify_prehashed::<Sha256>(&pk, &prehash).unwrap();
+        assert!(signature.verify_prehashed::<Sha256>(&pk, &message.into()).is_err());
+        assert!(signature.verify::<Sha256>(&pk, &message).is_err());
+
+        // The two modes do not cross-verify over the same digest bytes.
+        assert!(signature.verify::<Sha256>(&pk, &prehash).is_err());
+        let plain = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &prehash);
+        plain.verify::<Sha256>(&pk, &prehash).unwrap();
+        assert!(plain.verify_prehashed::<Sha256>(&pk, &prehash).is_err());
+    }
+
+    #[test]
+    fn test_messages() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let messages: [&[u8]; 3] = [b"debit", b"credit", b"fee"];
+
+        let signature = SchnorrSignature::<Secp256k1>::sign_messages::<Sha256>(&sk, &messages);
+        signature.verify_messages::<Sha256>(&pk, &messages).unwrap();
+        assert!(signature.verify_messages::<Sha256>(&pk, &[b"credit", b"debit", b"fee"]).is_err());
+        assert!(signature.verify_messages::<Sha256>(&pk, &[b"debitcredit", b"fee"]).is_err());
+        assert!(signature.verify_messages::<Sha256>(&pk, &[b"deb", b"itcredit", b"fee"]).is_err());
+        assert!(signature.verify_messages::<Sha256>(&pk, &messages[..2]).is_err());
+        assert!(signature.verify::<Sha256>(&pk, b"debitcreditfee").is_err());
+    }
+
+    #[test]
+    fn test_short_digest() {
+        use k256::sha2::Sha224;
+
+        use crate::error::VerifyError;
+
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sk, &message);
+        let error = signature.verify::<Sha224>(&pk, &message).unwrap_err();
+        assert_eq!(error.downcast_ref::<VerifyError>(), Some(&VerifyError::DigestSize { expected: 32, actual: 28 }));
+    }
+
+    #[test]
+    fn test_wide_challenge() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let message = [0, 3, 6, 9];
+
+        let signature = SchnorrSignature::<Secp256k1>::sign_with::<Sha256, WideReduce>(&sk, &message);
+        signature.verify_with::<Sha256, WideReduce>(&pk, &message).unwrap();
+        assert!(signature.verify::<Sha256>(&pk, &message).is_err());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        use elliptic_curve::zeroize::Zeroize;
+
+        // `Drop` wipes the signer through the same `zeroize`.
+        let mut signer = SchnorrSigner::<Secp256k1, Sha256>::new();
+        assert_ne!(signer.r, Scalar::ZERO);
+        Zeroize::zeroize(&mut signer);
+        assert_eq!(signer.r, Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_sign_batch() {
+        let mut rng = ThreadRng::default();
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+
+        let messages: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 4]).collect();
+        let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+        let signatures = SchnorrSignature::<Secp256k1>::sign_batch::<Sha256, _>(&sk, &messages, &mut rng);
+        assert_eq!(signatures.len(), messages.len());
+        for (signature, message) in signatures.iter().zip(&messages) {
+            signature.verify::<Sha256>(&pk, message).unwrap();
+        }
+        for (i, signature) in signatures.iter().enumerate() {
+            assert!(signatures[..i].iter().all(|other| other.point_r != signature.point_r));
+        }
+        assert!(SchnorrSignature::<Secp256k1>::sign_batch::<Sha256, _>(&sk, &[], &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_sign_hedged() {
+        struct ZeroRng;
+
+        impl RngCore for ZeroRng {
+            fn next_u32(&mut self) -> u32 {
+                0
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                0
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                dest.fill(0);
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+                dest.fill(0);
+                Ok(())
+            }
+        }
+
+        impl CryptoRng for ZeroRng {}
+
+        let sk = Scalar::random(&mut ThreadRng::default());
+        let pk = ProjectivePoint::GENERATOR * sk;
+        let sign = |message: &[u8], extra: &[u8]| SchnorrSignature::<Secp256k1>::sign_hedged::<Sha256, _>(&sk, message, extra, &mut ZeroRng);
+
+        let signature = sign(&[0, 3, 6, 9], b"");
+        signature.verify::<Sha256>(&pk, &[0, 3, 6, 9]).unwrap();
+        assert!(signature.verify::<Sha256>(&pk, &[0, 3, 6]).is_err());
+
+        // Without entropy the nonce still depends on the message, so it is never reused.
+        assert_eq!(sign(&[0, 3, 6, 9], b""), signature);
+        assert_ne!(sign(&[0, 3, 6], b"").point_r, signature.point_r);
+        assert_ne!(sign(&[0, 3, 6, 9], b"counter 1").point_r, signature.point_r);
+
+        let hedged = SchnorrSignature::<Secp256k1>::sign_hedged::<Sha256, _>(&sk, &[0, 3, 6, 9], b"", &mut ThreadRng::default());
+        hedged.verify::<Sha256>(&pk, &[0, 3, 6, 9]).unwrap();
+        assert_ne!(hedged.point_r, signature.point_r);
+    }
+}
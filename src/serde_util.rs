@@ -0,0 +1,426 @@
+//! `#[serde(with = ...)]` helpers that encode projective points through their
+//! affine representation, so signature types can derive serde directly. Vectors are
+//! normalized with one inversion through `batch_to_affine`.
+//!
+//! # No projective mode
+//!
+//! There is deliberately no `ProjectiveSerde` mode writing projective coordinates to
+//! skip the inversion in `to_affine`. The `elliptic_curve` traits only expose points
+//! through `to_affine` and `GroupEncoding`, and curve crates such as k256 keep the
+//! projective coordinates private, so such a mode could only copy a point's in-memory
+//! representation. Decoding that is `unsafe`, valid only between identical builds of
+//! the curve crate, and cannot be checked the way `point_serde::validate` checks
+//! points. The use case it targets, handing a signature to another task in the same
+//! process, needs no encoding at all: clone the signature, which keeps the points
+//! projective. `benches/bench_serialize.rs` times that against a bincode round trip.
+
+use elliptic_curve::{group::Curve, AffinePoint, CurveArithmetic, ProjectivePoint};
+
+pub mod point_serde {
+    use elliptic_curve::group::{Curve, GroupEncoding};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<P, S>(point: &P, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        P: Curve,
+        P::AffineRepr: Serialize,
+        S: Serializer
+    {
+        point.to_affine().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, P, D>(deserializer: D) -> Result<P, D::Error>
+    where
+        P: Curve + GroupEncoding + From<P::AffineRepr>,
+        P::AffineRepr: Deserialize<'de>,
+        D: Deserializer<'de>
+    {
+        P::AffineRepr::deserialize(deserializer).map(P::from).and_then(validate)
+    }
+
+    /// Rejects points that do not survive a round trip through the curve's canonical
+    /// encoding, for curves whose affine `Deserialize` is more lenient than `from_bytes`.
+    pub(crate) fn validate<P: GroupEncoding + PartialEq, E: Error>(point: P) -> Result<P, E> {
+        Option::<P>::from(P::from_bytes(&point.to_bytes()))
+            .filter(|decoded| *decoded == point)
+            .ok_or_else(|| E::custom("non-canonical point encoding"))
+    }
+}
+
+/// Converts `points` to affine form with `Curve::batch_normalize`, which curves such as
+/// k256 implement with Montgomery's trick: a single field inversion for the whole slice.
+pub fn batch_to_affine<C: CurveArithmetic>(points: &[ProjectivePoint<C>]) -> Vec<AffinePoint<C>> {
+    batch_normalize(points)
+}
+
+fn batch_normalize<P>(points: &[P]) -> Vec<P::AffineRepr>
+where
+    P: Curve,
+    P::AffineRepr: Clone + Default
+{
+    let mut affine = vec![P::AffineRepr::default(); points.len()];
+    // k256 fails to invert an empty batch.
+    if !points.is_empty() {
+        P::batch_normalize(points, &mut affine);
+    }
+    affine
+}
+
+pub mod vec_point_serde {
+    use elliptic_curve::group::{Curve, GroupEncoding};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<P, S>(points: &[P], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        P: Curve,
+        P::AffineRepr: Serialize + Clone + Default,
+        S: Serializer
+    {
+        serializer.collect_seq(super::batch_normalize(points))
+    }
+
+    pub fn deserialize<'de, P, D>(deserializer: D) -> Result<Vec<P>, D::Error>
+    where
+        P: Curve + GroupEncoding + From<P::AffineRepr>,
+        P::AffineRepr: Deserialize<'de>,
+        D: Deserializer<'de>
+    {
+        Vec::<P::AffineRepr>::deserialize(deserializer)?
+            .into_iter()
+            .map(|point| super::point_serde::validate(P::from(point)))
+            .collect()
+    }
+}
+
+/// Fixed-size arrays are encoded like a `Vec`, with a length prefix.
+pub mod array_point_serde {
+    use elliptic_curve::group::{Curve, GroupEncoding};
+    use serde::{de::Error, Deserializer, Serialize, Serializer, Deserialize};
+
+    pub fn serialize<P, S, const N: usize>(points: &[P; N], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        P: Curve,
+        P::AffineRepr: Serialize + Clone + Default,
+        S: Serializer
+    {
+        super::vec_point_serde::serialize(points, serializer)
+    }
+
+    pub fn deserialize<'de, P, D, const N: usize>(deserializer: D) -> Result<[P; N], D::Error>
+    where
+        P: Curve + GroupEncoding + From<P::AffineRepr>,
+        P::AffineRepr: Deserialize<'de>,
+        D: Deserializer<'de>
+    {
+        let points: Vec<P> = super::vec_point_serde::deserialize(deserializer)?;
+        let len = points.len();
+        points.try_into().map_err(|_| D::Error::invalid_length(len, &"exactly MAXN points"))
+    }
+}
+
+/// Scalars are written through `ScalarPrimitive`, which is how the curve crates encode
+/// them, so the bytes are unchanged. Decoding reads the raw field bytes and checks them
+/// with `from_repr` here, rejecting encodings that are not below the group order instead
+/// of relying on each curve's own `Deserialize` to do so.
+pub mod scalar_serde {
+    use elliptic_curve::{CurveArithmetic, FieldBytes, PrimeField, Scalar, ScalarPrimitive};
+    use serde::{de::Error, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<C, S>(scalar: &Scalar<C>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        C: CurveArithmetic,
+        S: Serializer
+    {
+        Into::<ScalarPrimitive<C>>::into(*scalar).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, C, D>(deserializer: D) -> Result<Scalar<C>, D::Error>
+    where
+        C: CurveArithmetic,
+        D: Deserializer<'de>
+    {
+        let mut repr = FieldBytes::<C>::default();
+        serdect::array::deserialize_hex_or_bin(&mut repr, deserializer)?;
+        validate::<C, _>(repr)
+    }
+
+    pub(crate) fn validate<C: CurveArithmetic, E: Error>(repr: FieldBytes<C>) -> Result<Scalar<C>, E> {
+        Option::from(Scalar::<C>::from_repr(repr)).ok_or_else(|| E::custom("non-canonical scalar encoding"))
+    }
+}
+
+/// Encodes a `BulletProof` without its `target`, which decodes as the identity, for
+/// proofs whose target the verifier recomputes (see `CompactSignature`).
+pub mod untargeted_bulletproof_serde {
+    use elliptic_curve::{group::GroupEncoding, AffinePoint, CurveArithmetic, Group, ProjectivePoint, Scalar};
+    use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::bulletproof::BulletProof;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(bound = "Scalar<C>: Serialize + DeserializeOwned, AffinePoint<C>: Serialize + DeserializeOwned, ProjectivePoint<C>: GroupEncoding")]
+    struct Untargeted<C: CurveArithmetic> {
+        #[serde(with = "super::vec_point_serde")]
+        vec_point_l: Vec<ProjectivePoint<C>>,
+        #[serde(with = "super::vec_point_serde")]
+        vec_point_r: Vec<ProjectivePoint<C>>,
+        #[serde(serialize_with = "super::scalar_serde::serialize::<C, _>", deserialize_with = "super::scalar_serde::deserialize::<C, _>")]
+        l: Scalar<C>,
+        #[serde(serialize_with = "super::scalar_serde::serialize::<C, _>", deserialize_with = "super::scalar_serde::deserialize::<C, _>")]
+        r: Scalar<C>
+    }
+
+    pub fn serialize<C, S>(proof: &BulletProof<C>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        C: CurveArithmetic,
+        Scalar<C>: Serialize + DeserializeOwned,
+        AffinePoint<C>: Serialize + DeserializeOwned,
+        ProjectivePoint<C>: GroupEncoding,
+        S: Serializer
+    {
+        Untargeted::<C> {
+            vec_point_l: proof.vec_point_l.clone(),
+            vec_point_r: proof.vec_point_r.clone(),
+            l: proof.l,
+            r: proof.r
+        }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, C, D>(deserializer: D) -> Result<BulletProof<C>, D::Error>
+    where
+        C: CurveArithmetic,
+        Scalar<C>: Serialize + DeserializeOwned,
+        AffinePoint<C>: Serialize + DeserializeOwned,
+        ProjectivePoint<C>: GroupEncoding,
+        D: Deserializer<'de>
+    {
+        let Untargeted { vec_point_l, vec_point_r, l, r } = Untargeted::<C>::deserialize(deserializer)?;
+        Ok(BulletProof { target: ProjectivePoint::<C>::identity(), vec_point_l, vec_point_r, l, r })
+    }
+}
+
+/// Bincode with varint-encoded lengths. Every point carries its own length prefix,
+/// which takes one byte here instead of the eight of `bincode::serialize`.
+pub fn to_bytes_compact<T: serde::Serialize>(value: &T) -> anyhow::Result<Vec<u8>> {
+    use bincode::Options;
+    Ok(bincode::DefaultOptions::new().with_varint_encoding().serialize(value)?)
+}
+
+/// Inverse of `to_bytes_compact`; trailing bytes are rejected.
+pub fn from_bytes_compact<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> anyhow::Result<T> {
+    use bincode::Options;
+    Ok(bincode::DefaultOptions::new().with_varint_encoding().reject_trailing_bytes().deserialize(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::{group::GroupEncoding, point::AffineCoordinates, subtle::{Choice, CtOption}, Field};
+    use k256::{sha2::Sha256, AffinePoint, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+    use serde::{de::value, Deserialize, Serialize};
+
+    use crate::{bulletproof::BulletProof, incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+
+    use super::{batch_to_affine, point_serde::validate};
+
+    // Layouts of the shadow structs these helpers replaced.
+    #[derive(Serialize, Deserialize)]
+    struct LegacySchnorrSignature {
+        point_r: AffinePoint,
+        z: Scalar
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LegacyBulletProof {
+        target: AffinePoint,
+        vec_point_l: Vec<AffinePoint>,
+        vec_point_r: Vec<AffinePoint>,
+        l: Scalar,
+        r: Scalar
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LegacyParams {
+        g: AffinePoint,
+        h: AffinePoint,
+        vec_g: Vec<AffinePoint>,
+        vec_h: Vec<AffinePoint>
+    }
+
+    #[test]
+    fn test_legacy_wire_format() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&Scalar::random(&mut rng), &[1, 2, 3]);
+        let legacy = LegacySchnorrSignature { point_r: signature.point_r.to_affine(), z: signature.z };
+        let bytes = bincode::serialize(&signature).unwrap();
+        assert_eq!(bytes, bincode::serialize(&legacy).unwrap());
+        let decoded: LegacySchnorrSignature = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(ProjectivePoint::from(decoded.point_r), signature.point_r);
+
+        // Signatures from before `R` was normalized still decode and verify, whatever
+        // the parity of their `R`.
+        let sk = Scalar::random(&mut rng);
+        let pk = ProjectivePoint::GENERATOR * sk;
+        for _ in 0..8 {
+            let nonce = Scalar::random(&mut rng);
+            let point_r = ProjectivePoint::GENERATOR * nonce;
+            let c = SchnorrSignature::<Secp256k1>::challenge::<Sha256>(&point_r, &[1, 2, 3], &mut |_| ());
+            let legacy = LegacySchnorrSignature { point_r: point_r.to_affine(), z: nonce + sk * c };
+            let decoded: SchnorrSignature<Secp256k1> = bincode::deserialize(&bincode::serialize(&legacy).unwrap()).unwrap();
+            assert_eq!(decoded.point_r, point_r);
+            decoded.verify::<Sha256>(&pk, &[1, 2, 3]).unwrap();
+        }
+
+        let l: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let g: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let h: Vec<_> = (0..n).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        let target: ProjectivePoint = (0..n).map(|i| g[i] * l[i] + h[i] * r[i]).sum();
+        let proof = BulletProof::<Secp256k1>::prove::<Sha256>(&g, &h, &l, &r, &target);
+        let legacy = LegacyBulletProof {
+            target: proof.target.to_affine(),
+            vec_point_l: proof.vec_point_l.iter().map(|point| point.to_affine()).collect(),
+            vec_point_r: proof.vec_point_r.iter().map(|point| point.to_affine()).collect(),
+            l: proof.l,
+            r: proof.r
+        };
+        let legacy_bytes = bincode::serialize(&legacy).unwrap();
+        assert_eq!(bincode::serialize(&proof).unwrap(), legacy_bytes);
+        let decoded: BulletProof<Secp256k1> = bincode::deserialize(&legacy_bytes).unwrap();
+        decoded.verify::<Sha256>(&g, &h).unwrap();
+
+        let params = IncognitoParams::<Secp256k1, 4>::new();
+        let bytes = bincode::serialize(&params).unwrap();
+        let legacy: LegacyParams = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&legacy).unwrap(), bytes);
+        assert_eq!(legacy.vec_g.len(), 4);
+    }
+
+    #[test]
+    fn test_array_length_mismatch() {
+        let bytes = bincode::serialize(&IncognitoParams::<Secp256k1, 4>::new()).unwrap();
+        assert!(bincode::deserialize::<IncognitoParams<Secp256k1, 8>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_invalid_point_encoding() {
+        let mut rng = ThreadRng::default();
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&Scalar::random(&mut rng), &[1, 2, 3]);
+        let bytes = bincode::serialize(&signature).unwrap();
+        // bincode length prefix, then the 33-byte compressed `R`.
+        assert_eq!(bytes[..8], 33u64.to_le_bytes());
+        let corrupt = |tag: u8, x: [u8; 32]| {
+            let mut bytes = bytes.clone();
+            bytes[8] = tag;
+            bytes[9..41].copy_from_slice(&x);
+            bincode::deserialize::<SchnorrSignature<Secp256k1>>(&bytes)
+        };
+
+        assert!(corrupt(0x01, signature.point_r.to_affine().x().into()).is_err());
+        // The negated point still decodes: serde accepts either parity of `R`.
+        assert_eq!(corrupt(0x03, signature.point_r.to_affine().x().into()).unwrap().point_r, -signature.point_r);
+        // x >= p is not a canonical field element.
+        assert!(corrupt(0x02, [0xff; 32]).is_err());
+        // x = 5 has no point on secp256k1 (5^3 + 7 is not a square).
+        let mut x = [0; 32];
+        x[31] = 5;
+        assert!(corrupt(0x02, x).is_err());
+        assert_eq!(corrupt(0x02, signature.point_r.to_affine().x().into()).unwrap(), signature);
+    }
+
+    /// A point whose encoding keeps only the low seven bits and rejects `0x7f`, standing
+    /// in for a curve whose affine `Deserialize` accepts more than `from_bytes` does.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Truncated(u8);
+
+    impl GroupEncoding for Truncated {
+        type Repr = [u8; 1];
+
+        fn from_bytes(bytes: &[u8; 1]) -> CtOption<Self> {
+            CtOption::new(Truncated(bytes[0]), Choice::from((bytes[0] != 0x7f) as u8))
+        }
+
+        fn from_bytes_unchecked(bytes: &[u8; 1]) -> CtOption<Self> {
+            Self::from_bytes(bytes)
+        }
+
+        fn to_bytes(&self) -> [u8; 1] {
+            [self.0 & 0x7f]
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate::<_, value::Error>(Truncated(0x05)).unwrap(), Truncated(0x05));
+        // Encodes to 0x05, which decodes to another point.
+        assert!(validate::<_, value::Error>(Truncated(0x85)).is_err());
+        // Encodes to bytes that do not decode at all.
+        assert!(validate::<_, value::Error>(Truncated(0x7f)).is_err());
+        assert!(validate::<_, value::Error>(ProjectivePoint::GENERATOR).is_ok());
+    }
+
+    #[test]
+    fn test_non_canonical_scalar() {
+        let mut rng = ThreadRng::default();
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&Scalar::random(&mut rng), &[0, 3, 6, 9]);
+        let mut bytes = bincode::serialize(&signature).unwrap();
+        let len = bytes.len();
+        assert_eq!(bincode::deserialize::<SchnorrSignature<Secp256k1>>(&bytes).unwrap(), signature);
+
+        // Rejected by `scalar_serde::validate`, not by the curve's own `Deserialize`.
+        let non_canonical = |bytes: &[u8]| bincode::deserialize::<SchnorrSignature<Secp256k1>>(bytes).unwrap_err().to_string() == "non-canonical scalar encoding";
+        bytes[len - 32..].copy_from_slice(&[0xff; 32]);
+        assert!(non_canonical(&bytes));
+        // The group order itself is the smallest out-of-range encoding.
+        bytes[len - 32..].copy_from_slice(&(-Scalar::ONE).to_bytes());
+        bytes[len - 1] += 1;
+        assert!(non_canonical(&bytes));
+
+        #[derive(Debug, Deserialize)]
+        struct Opening {
+            #[serde(deserialize_with = "super::scalar_serde::deserialize::<Secp256k1, _>")]
+            blind: Scalar
+        }
+        assert_eq!(serde_json::from_str::<Opening>(&format!(r#"{{"blind":"{}"}}"#, "00".repeat(31) + "01")).unwrap().blind, Scalar::ONE);
+        let error = serde_json::from_str::<Opening>(&format!(r#"{{"blind":"{}"}}"#, "FF".repeat(32))).unwrap_err();
+        assert!(error.to_string().starts_with("non-canonical scalar encoding"));
+    }
+
+    #[test]
+    fn test_compact_encoding() {
+        let n = 8;
+        let index = 3;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let incsig = params.convert::<Sha256>(&pks, &message, &signature, index).unwrap();
+
+        let compact = incsig.to_bytes_compact().unwrap();
+        let default = bincode::serialize(&incsig).unwrap();
+        assert!(compact.len() < default.len(), "{} >= {}", compact.len(), default.len());
+        let decoded = IncognitoSignature::<Secp256k1>::from_bytes_compact(&compact).unwrap();
+        assert_eq!(decoded, incsig);
+        params.verify::<Sha256>(&pks, &message, &decoded).unwrap();
+        assert!(IncognitoSignature::<Secp256k1>::from_bytes_compact(&[compact.as_slice(), &[0]].concat()).is_err());
+
+        let compact = signature.to_bytes_compact().unwrap();
+        assert!(compact.len() < bincode::serialize(&signature).unwrap().len());
+        SchnorrSignature::<Secp256k1>::from_bytes_compact(&compact).unwrap().verify::<Sha256>(&pks[index], &message).unwrap();
+    }
+
+    #[test]
+    fn test_batch_to_affine() {
+        let mut rng = ThreadRng::default();
+        let mut points: Vec<_> = (0..9).map(|_| ProjectivePoint::GENERATOR * Scalar::random(&mut rng)).collect();
+        points.insert(4, ProjectivePoint::IDENTITY);
+        let expected: Vec<_> = points.iter().map(|point| point.to_affine()).collect();
+        assert_eq!(batch_to_affine::<Secp256k1>(&points), expected);
+        assert!(batch_to_affine::<Secp256k1>(&[]).is_empty());
+    }
+}
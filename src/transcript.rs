@@ -0,0 +1,36 @@
+/// Byte segments fed to the digest for each Fiat–Shamir challenge, in hashing order.
+///
+/// Filled by `IncognitoParams::convert_with_log` and `verify_with_log` to debug
+/// mismatched challenges between implementations: two implementations agree on a
+/// signature exactly when their logs are equal. Segments are recorded by the challenge
+/// derivations themselves as they pass them to the digest, so their concatenation is
+/// the hashed input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptLog {
+    entries: Vec<(&'static str, Vec<Vec<u8>>)>
+}
+
+impl TranscriptLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Challenges in derivation order, each with its segments.
+    pub fn entries(&self) -> &[(&'static str, Vec<Vec<u8>>)] {
+        &self.entries
+    }
+
+    /// Segments hashed for the challenge `name`, e.g. `"y"`.
+    pub fn segments(&self, name: &str) -> Option<&[Vec<u8>]> {
+        self.entries.iter().find(|(each, _)| *each == name).map(|(_, segments)| segments.as_slice())
+    }
+
+    /// Appends a segment hashed for the challenge `name`, starting a new entry when the
+    /// previous segment belonged to another challenge.
+    pub(crate) fn absorb(&mut self, name: &'static str, bytes: &[u8]) {
+        match self.entries.last_mut() {
+            Some((last, segments)) if *last == name => segments.push(bytes.to_vec()),
+            _ => self.entries.push((name, vec![bytes.to_vec()]))
+        }
+    }
+}
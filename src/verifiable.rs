@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, ProjectivePoint};
+
+use crate::{error::VerifyError, incognito::{IncognitoParams, IncognitoSignature}, schnorr::SchnorrSignature};
+
+/// What a signature is checked against. A Schnorr signature expects exactly one key
+/// in `pks`; an incognito signature takes `pks` as its ring. The context is the same
+/// for both so that mixed signatures can be verified through `dyn Verifiable`.
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyContext<'a, C: CurveArithmetic, const MAXN: usize> {
+    pub params: &'a IncognitoParams<C, MAXN>,
+    pub pks: &'a [ProjectivePoint<C>],
+    pub message: &'a [u8]
+}
+
+/// A signature that can be verified without knowing whether it is a plain Schnorr
+/// signature or an incognito one. The digest is fixed by the marker `D`, e.g.
+/// `Box<dyn Verifiable<Secp256k1, Digested<Sha256>, 16>>`.
+pub trait Verifiable<C: CurveArithmetic, D, const MAXN: usize> {
+    fn verify(&self, context: &VerifyContext<'_, C, MAXN>) -> Result<(), VerifyError>;
+}
+
+/// Selects the digest used by a `Verifiable` implementation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Digested<D>(PhantomData<D>);
+
+impl <C: CurveArithmetic, D: Digest, const MAXN: usize> Verifiable<C, Digested<D>, MAXN> for SchnorrSignature<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    fn verify(&self, context: &VerifyContext<'_, C, MAXN>) -> Result<(), VerifyError> {
+        match context.pks {
+            [pk] => SchnorrSignature::<C>::verify::<D>(self, pk, context.message).map_err(VerifyError::from_anyhow),
+            pks => Err(VerifyError::RingSize { expected: 1, actual: pks.len() })
+        }
+    }
+}
+
+impl <C: CurveArithmetic, D: Digest, const MAXN: usize> Verifiable<C, Digested<D>, MAXN> for IncognitoSignature<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    fn verify(&self, context: &VerifyContext<'_, C, MAXN>) -> Result<(), VerifyError> {
+        context.params.verify::<D>(context.pks, context.message, self).map_err(VerifyError::from_anyhow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::{error::VerifyError, incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::{Digested, Verifiable, VerifyContext};
+
+    #[test]
+    fn test_mixed_signatures() {
+        let n = 8;
+        let index = 5;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+
+        let message = [0, 3, 6, 9];
+        let retail = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[index], &message);
+        let anonymous = params.convert::<Sha256>(&pks, &message, &retail, index).unwrap();
+
+        let payments: Vec<Box<dyn Verifiable<Secp256k1, Digested<Sha256>, 8>>> = vec![
+            Box::new(retail.clone()),
+            Box::new(anonymous.clone())
+        ];
+        let rings = [&pks[index..index + 1], &pks];
+        for (signature, pks) in payments.iter().zip(rings) {
+            signature.verify(&VerifyContext { params: &params, pks, message: &message }).unwrap();
+            assert!(signature.verify(&VerifyContext { params: &params, pks, message: &[0, 3, 6] }).is_err());
+        }
+
+        let context = VerifyContext { params: &params, pks: &pks, message: &message };
+        assert_eq!(
+            Verifiable::<Secp256k1, Digested<Sha256>, 8>::verify(&retail, &context),
+            Err(VerifyError::RingSize { expected: 1, actual: n })
+        );
+        assert!(matches!(
+            Verifiable::<Secp256k1, Digested<Sha256>, 8>::verify(&anonymous, &VerifyContext { pks: &pks[..4], ..context }),
+            Err(VerifyError::Invalid(_))
+        ));
+        assert_eq!(
+            Verifiable::<Secp256k1, Digested<Sha256>, 8>::verify(&anonymous, &VerifyContext { pks: &[], ..context }),
+            Err(VerifyError::EmptyRing)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use digest::Digest;
+use elliptic_curve::{group::GroupEncoding, CurveArithmetic, Group, PrimeField, ProjectivePoint, Scalar};
+#[cfg(feature = "zeroize")]
+use elliptic_curve::zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{challenge::reduce_digest, incognito::{IncognitoParams, IncognitoSignature}};
+
+/// Lets a signer recognize their own incognito signatures without revealing them to
+/// anyone else.
+///
+/// Signatures made with `IncognitoParams::convert_with_view_key` take the opening
+/// `beta` of `point_c_pk` as `H(view key, R, message)`. Whoever holds the view key can
+/// recompute `beta` and check `point_c_pk - g * beta == pk`; to everyone else `beta`
+/// is indistinguishable from a random blinder. The view key is derived one-way from
+/// the secret key, so it can be handed to a wallet watcher without spending rights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewKey<C: CurveArithmetic> {
+    key: Scalar<C>,
+    pk: ProjectivePoint<C>
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> Drop for ViewKey<C> {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl <C: CurveArithmetic> ZeroizeOnDrop for ViewKey<C> {}
+
+impl <C: CurveArithmetic> ViewKey<C>
+where
+    ProjectivePoint<C>: GroupEncoding
+{
+    pub fn from_secret<D>(sk: &Scalar<C>) -> Self
+    where
+        D: Digest
+    {
+        let digest = D::new()
+            .chain_update(b"incognito-view-key")
+            .chain_update(sk.to_repr())
+            .finalize();
+        Self {
+            key: reduce_digest::<C>(&digest),
+            pk: ProjectivePoint::<C>::generator() * sk
+        }
+    }
+
+    /// Public key of the signer this view key belongs to.
+    pub fn pk(&self) -> &ProjectivePoint<C> {
+        &self.pk
+    }
+
+    /// Opening used for the signature with nonce commitment `point_r` on `message`.
+    pub fn beta<D>(&self, point_r: &ProjectivePoint<C>, message: &[u8]) -> Scalar<C>
+    where
+        D: Digest
+    {
+        let digest = D::new()
+            .chain_update(b"incognito-view-beta")
+            .chain_update(self.key.to_repr())
+            .chain_update(point_r.to_bytes())
+            .chain_update(message)
+            .finalize();
+        reduce_digest::<C>(&digest)
+    }
+
+    /// Whether `signature` on `message` was made by this signer via `convert_with_view_key`.
+    pub fn recognizes<D, const MAXN: usize>(
+        &self,
+        params: &IncognitoParams<C, MAXN>,
+        signature: &IncognitoSignature<C>,
+        message: &[u8]
+    ) -> bool
+    where
+        D: Digest
+    {
+        let beta = self.beta::<D>(signature.point_r(), message);
+        signature.could_be_signer::<D, MAXN>(params, &self.pk, message, &beta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elliptic_curve::Field;
+    use k256::{sha2::Sha256, ProjectivePoint, Scalar, Secp256k1};
+    use rand::rngs::ThreadRng;
+
+    use crate::{incognito::IncognitoParams, schnorr::SchnorrSignature};
+
+    use super::ViewKey;
+
+    #[test]
+    fn test_view_key() {
+        let n = 8;
+        let mut rng = ThreadRng::default();
+        let params = IncognitoParams::<Secp256k1, 8>::new();
+        let sks: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let pks: Vec<_> = sks.iter().map(|s| ProjectivePoint::GENERATOR * s).collect();
+        let mine = ViewKey::<Secp256k1>::from_secret::<Sha256>(&sks[2]);
+        let theirs = ViewKey::<Secp256k1>::from_secret::<Sha256>(&sks[6]);
+
+        let message = [0, 3, 6, 9];
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[2], &message);
+        let own = params.convert_with_view_key::<Sha256>(&pks, &message, &signature, 2, &mine).unwrap();
+        params.verify::<Sha256>(&pks, &message, &own).unwrap();
+        assert!(mine.recognizes::<Sha256, 8>(&params, &own, &message));
+        assert!(!theirs.recognizes::<Sha256, 8>(&params, &own, &message));
+        assert!(!mine.recognizes::<Sha256, 8>(&params, &own, &[0, 3, 6]));
+
+        // Same message signed twice still yields unlinkable commitments.
+        let again = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[2], &message);
+        let second = params.convert_with_view_key::<Sha256>(&pks, &message, &again, 2, &mine).unwrap();
+        assert_ne!(own, second);
+        assert!(mine.recognizes::<Sha256, 8>(&params, &second, &message));
+
+        let signature = SchnorrSignature::<Secp256k1>::sign::<Sha256>(&sks[6], &message);
+        let other = params.convert_with_view_key::<Sha256>(&pks, &message, &signature, 6, &theirs).unwrap();
+        assert!(!mine.recognizes::<Sha256, 8>(&params, &other, &message));
+        assert!(theirs.recognizes::<Sha256, 8>(&params, &other, &message));
+        let plain = params.convert::<Sha256>(&pks, &message, &signature, 6).unwrap();
+        assert!(!theirs.recognizes::<Sha256, 8>(&params, &plain, &message));
+
+        assert!(params.convert_with_view_key::<Sha256>(&pks, &message, &signature, 6, &mine).is_err());
+    }
+}